@@ -0,0 +1,74 @@
+// Parses a big generated program and reports how long it takes and how many allocations it makes.
+// It's its own binary so that counting allocations doesn't slow down anything else.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use alisalang::parse::Parser;
+
+// Counts every allocation.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// `cargo bench --bench parse [-- copies]`. Times parsing a big generated file and counts how many allocations it
+// takes. The first parse is shown on its own since it's the one that interns every name.
+fn main() {
+    // Cargo passes `--bench` along with whatever comes after the `--`.
+    let copies: usize = std::env::args().skip(1).find_map(|arg| arg.parse().ok()).unwrap_or(2000);
+    let src: String = (0..copies).map(|i| format!("
+struct Point{i} {{ x: i32, y: i32 }}
+trait Length{i} {{ fn length(self) -> i32; }}
+impl Length{i} for Point{i} {{
+    fn length(self) -> i32 {{ {i} }}
+}}
+fn distance{i}(a: Point{i}, b: Point{i}, scale: i32) -> i32 {{
+    let dx = a.length() - b.length();
+    let dy = a.length() + scale;
+    let add = \\(x: i32, y: i32) -> i32 {{ x + y }};
+    if dx > dy {{ add(dx, dy) * scale }} else {{ add(-dx, dy) }}
+}}
+let origin{i} = [distance{i}, distance{i}];
+")).collect();
+
+    let parse = || {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let t = Instant::now();
+        let (tree, errors) = Parser::parse(&src);
+        let time = t.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        assert!(errors.is_empty(), "{}", errors[0]);
+        drop(tree);
+        (time, allocations)
+    };
+
+    println!("{copies} copies, {} bytes", src.len());
+    let (time, allocations) = parse();
+    println!("first parse: {time:?}, {allocations} allocations");
+
+    let mut best = (Duration::MAX, 0);
+    for _ in 0..5 {
+        best = best.min(parse());
+    }
+    println!("best of 5:   {:?}, {} allocations", best.0, best.1);
+}
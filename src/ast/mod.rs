@@ -1,123 +1,512 @@
 pub mod token;
+mod symbol;
+mod print;
+pub mod visit;
+pub mod visit_mut;
 use token::*;
 
-#[derive(Debug)]
+pub use symbol::{Symbol, Names, Entered};
+pub(crate) use print::{operand_parens, unary_operand_parens, open_prefix, condition_parens};
+
+#[derive(Debug, Default)]
 pub struct ASTree {
-    root: Vec<Statement>
+    pub root: Vec<Statement>,
+    // Every expression in the tree. The statements and expressions refer to the ones inside of
+    // them by their `ExprId`.
+    pub exprs: Exprs,
 }
 
 impl ASTree {
-    pub fn new(root: Vec<Statement>) -> Self {
-        Self { root }
+    pub fn new(root: Vec<Statement>, exprs: Exprs) -> Self {
+        Self { root, exprs }
+    }
+}
+
+// Where an expression is in its tree's `Exprs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExprId(u32);
+
+// The expressions of a tree, all in one `Vec` rather than each in an allocation of its own. An
+// expression always comes after the ones inside of it.
+#[derive(Clone, Default)]
+pub struct Exprs {
+    nodes: Vec<Expression>,
+}
+
+impl Exprs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { nodes: Vec::with_capacity(capacity) }
+    }
+
+    pub fn alloc(&mut self, expr: Expression) -> ExprId {
+        let id = ExprId(self.nodes.len() as u32);
+        self.nodes.push(expr);
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Forgets every expression allocated since there were `len` of them, like the ones of a
+    // statement that didn't parse.
+    pub fn truncate(&mut self, len: usize) {
+        self.nodes.truncate(len);
+    }
+
+    // Takes `id` out so that it can be changed along with the expressions inside of it. It has to
+    // be put back with `put()` before anything looks at it again.
+    pub fn take(&mut self, id: ExprId) -> Expression {
+        let placeholder = Expression::Literal(LiteralExpression { kind: LitKind::Bool(false), span: Span::default() });
+        std::mem::replace(&mut self[id], placeholder)
+    }
+
+    pub fn put(&mut self, id: ExprId, expr: Expression) {
+        self[id] = expr;
+    }
+
+    // Adds every expression of `other` after the ones already here. `statements` are from `other`,
+    // and get their ids moved along with the expressions.
+    pub fn append(&mut self, other: &Exprs, statements: &mut [Statement]) {
+        let offset = self.nodes.len() as u32;
+        let mut shift = |id: &mut ExprId| id.0 += offset;
+        self.nodes.extend(other.nodes.iter().map(|expr| {
+            let mut expr = expr.clone();
+            expr.for_each_id(&mut shift);
+            expr
+        }));
+        statements.iter_mut().for_each(|statement| statement.for_each_id(&mut shift));
+    }
+
+    // A copy of `block` with copies of its expressions, for when it has to be changed without
+    // changing the original.
+    pub fn copy_block(&mut self, block: &BlockExpression) -> BlockExpression {
+        let mut block = block.clone();
+        block.for_each_id(&mut |id| *id = self.copy(*id));
+        block
+    }
+
+    fn copy(&mut self, id: ExprId) -> ExprId {
+        let mut expr = self[id].clone();
+        expr.for_each_id(&mut |id| *id = self.copy(*id));
+        self.alloc(expr)
     }
 }
 
+// Each expression along with its id, so that the ids in the others can be followed.
+impl std::fmt::Debug for Exprs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.nodes.iter().enumerate().map(|(i, expr)| (ExprId(i as u32), expr))).finish()
+    }
+}
+
+impl std::ops::Index<ExprId> for Exprs {
+    type Output = Expression;
+
+    fn index(&self, id: ExprId) -> &Expression {
+        &self.nodes[id.0 as usize]
+    }
+}
+
+impl std::ops::IndexMut<ExprId> for Exprs {
+    fn index_mut(&mut self, id: ExprId) -> &mut Expression {
+        &mut self.nodes[id.0 as usize]
+    }
+}
+
+// Byte offsets into the source, `end` being exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
 
-#[derive(Debug)]
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+
+#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Statement {
     Function(FunctionStatement),
     Struct(StructStatement),
     Enum(EnumStatement),
+    Impl(ImplStatement),
+    Trait(TraitStatement),
+    Import(ImportStatement),
     Let(LetStatement),
-    Expression { expr: Expression, end_token: Token },
+    // `span` includes the `;` if `end_token` is one.
+    Expression { expr: ExprId, end_token: Token, span: Span },
+    // A statement that didn't parse, along with whatever got skipped after it.
+    Error { span: Span },
     EOF,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Expression {
-    Closure(Box<ClosureExpression>),
-    Block(Box<BlockExpression>),
-    Call(Box<CallExpression>),
-    If(Box<IfExpression>),
-    Binary(Box<BinaryExpression>),
-    Unary(Box<UnaryExpression>),
+    Closure(ClosureExpression),
+    Block(BlockExpression),
+    Call(CallExpression),
+    MethodCall(MethodCallExpression),
+    Struct(StructExpression),
+    Field(FieldExpression),
+    If(IfExpression),
+    Binary(BinaryExpression),
+    Unary(UnaryExpression),
     Literal(LiteralExpression),
     Identifier(IdentExpression),
 }
 
+impl Statement {
+    // `end_token` is whatever came after the expression, which only belongs to the statement if
+    // it's a `;`.
+    pub fn expression(expr: ExprId, end_token: Token, exprs: &Exprs) -> Statement {
+        let expr_span = exprs[expr].span();
+        let span = match end_token.kind {
+            TokenKind::Semi => Span::new(expr_span.start, end_token.end),
+            _ => expr_span,
+        };
+        Statement::Expression { expr, end_token, span }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Function(item) => item.span,
+            Statement::Struct(item) => item.span,
+            Statement::Enum(item) => item.span,
+            Statement::Impl(item) => item.span,
+            Statement::Trait(item) => item.span,
+            Statement::Import(item) => item.span,
+            Statement::Let(item) => item.span,
+            Statement::Expression { span, .. } | Statement::Error { span } => *span,
+            Statement::EOF => Span::default(),
+        }
+    }
+
+    // Calls `f` on the id of every expression the statement refers to directly, rather than
+    // through another expression.
+    pub fn for_each_id(&mut self, f: &mut impl FnMut(&mut ExprId)) {
+        match self {
+            Statement::Function(item) => item.block.for_each_id(f),
+            Statement::Impl(item) => item.methods.iter_mut().for_each(|method| method.block.for_each_id(f)),
+            Statement::Trait(item) => {
+                item.methods.iter_mut().filter_map(|method| method.block.as_mut()).for_each(|block| block.for_each_id(f))
+            }
+            Statement::Let(item) => f(&mut item.value),
+            Statement::Expression { expr, .. } => f(expr),
+            Statement::Struct(_) | Statement::Enum(_) | Statement::Import(_) | Statement::Error { .. } | Statement::EOF => (),
+        }
+    }
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Closure(expr) => expr.span,
+            Expression::Block(expr) => expr.span,
+            Expression::Call(expr) => expr.span,
+            Expression::MethodCall(expr) => expr.span,
+            Expression::Struct(expr) => expr.span,
+            Expression::Field(expr) => expr.span,
+            Expression::If(expr) => expr.span,
+            Expression::Binary(expr) => expr.span,
+            Expression::Unary(expr) => expr.span,
+            Expression::Literal(expr) => expr.span,
+            Expression::Identifier(expr) => expr.span,
+        }
+    }
+
+    // Same as `Statement::for_each_id()`, for the expressions directly inside of this one.
+    pub fn for_each_id(&mut self, f: &mut impl FnMut(&mut ExprId)) {
+        match self {
+            Expression::Closure(closure) => closure.block.for_each_id(f),
+            Expression::Block(block) => block.for_each_id(f),
+            Expression::Call(call) => {
+                f(&mut call.function);
+                call.arguments.iter_mut().for_each(f);
+            }
+            Expression::MethodCall(call) => {
+                f(&mut call.receiver);
+                call.arguments.iter_mut().for_each(f);
+            }
+            Expression::Struct(struct_expr) => struct_expr.fields.iter_mut().for_each(|field| f(&mut field.value)),
+            Expression::Field(field) => f(&mut field.receiver),
+            Expression::If(if_expr) => {
+                f(&mut if_expr.condition);
+                if_expr.body.for_each_id(f);
+                match &mut if_expr.else_body {
+                    Some(ElseExpression::Else(block)) => block.for_each_id(f),
+                    Some(ElseExpression::ElseIf(else_if)) => f(else_if),
+                    None => (),
+                }
+            }
+            Expression::Binary(binary) => {
+                f(&mut binary.lhs);
+                f(&mut binary.rhs);
+            }
+            Expression::Unary(unary) => f(&mut unary.rhs),
+            Expression::Literal(literal) => match &mut literal.kind {
+                LitKind::Tuple(Tuple(elements)) | LitKind::List(List(elements)) => elements.iter_mut().for_each(f),
+                LitKind::Interpolated(parts) => parts.iter_mut().for_each(|part| match part {
+                    StrPart::Expr(expr) => f(expr),
+                    StrPart::Text(_) => (),
+                }),
+                _ => (),
+            },
+            Expression::Identifier(_) => (),
+        }
+    }
+}
+
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FunctionStatement {
-    pub name: String,
+    // Whether other modules can use it.
+    pub public: bool,
+    pub name: Symbol,
+    // Type parameters, like the `T` in `fn id<T>(x: T) -> T`.
+    pub generics: Vec<Symbol>,
     pub arguments: Vec<Parameter>,
     pub return_type: Type,
     pub block: BlockExpression,
+    pub name_span: Span,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StructStatement {
-    pub name: String,
+    pub public: bool,
+    pub name: Symbol,
+    pub generics: Vec<Symbol>,
     pub fields: Vec<Parameter>,
+    pub name_span: Span,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EnumStatement {
-    pub name: String,
-    pub variants: Vec<String>,
+    pub public: bool,
+    pub name: Symbol,
+    pub generics: Vec<Symbol>,
+    pub variants: Vec<Symbol>,
+    // Where each of `variants` is in the source.
+    pub variant_spans: Vec<Span>,
+    pub name_span: Span,
+    pub span: Span,
+}
+
+// Methods for `self_type`, which are also the trait's methods if there's a `trait_name`.
+#[derive(Debug, Clone)]
+pub struct ImplStatement {
+    pub generics: Vec<Symbol>,
+    pub trait_name: Option<Symbol>,
+    pub self_type: Type,
+    pub methods: Vec<FunctionStatement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitStatement {
+    pub public: bool,
+    pub name: Symbol,
+    pub methods: Vec<TraitMethod>,
+    pub name_span: Span,
+    pub span: Span,
+}
+
+// A method every implementation of a trait has. The ones with a `block` don't need to be
+// implemented, that's what they do by default.
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+    pub name: Symbol,
+    pub generics: Vec<Symbol>,
+    pub arguments: Vec<Parameter>,
+    pub return_type: Type,
+    pub block: Option<BlockExpression>,
+    pub name_span: Span,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+// `import geom::shapes;` loads `geom/shapes.alisa` from next to the importing file. Its public
+// items are then used as `shapes::Item`.
+#[derive(Debug, Clone)]
+pub struct ImportStatement {
+    pub path: Vec<Symbol>,
+    // Whether it's a `mod name;`, which loads the module from the declaring module's own
+    // directory instead of from next to it.
+    pub child: bool,
+    pub span: Span,
+}
+
+impl ImportStatement {
+    // What the module is called in the file importing it.
+    pub fn name(&self) -> std::sync::Arc<str> {
+        self.path.last().map_or("".into(), |name| name.as_str())
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self.child {
+            true => "mod",
+            false => "import",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct LetStatement {
-    pub name: String,
-    pub value: Expression,
+    pub name: Symbol,
+    pub var_type: Option<Type>,
+    pub value: ExprId,
+    pub name_span: Span,
+    pub span: Span,
 }
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ClosureExpression {
     pub arguments: Vec<Parameter>,
     pub block: BlockExpression,
     pub return_type: Type,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CallExpression {
-    pub function_name: String,
-    pub arguments: Vec<Parameter>
+    pub function: ExprId,
+    pub arguments: Vec<ExprId>,
+    pub span: Span,
+}
+
+// `receiver.method(arguments)`
+#[derive(Debug, Clone)]
+pub struct MethodCallExpression {
+    pub receiver: ExprId,
+    pub method: Symbol,
+    pub method_span: Span,
+    pub arguments: Vec<ExprId>,
+    // The type of the `impl` the method is from, filled in by the type checker. Values don't know
+    // their own type, so the interpreter goes by it to know which method to call.
+    pub self_type: Option<Type>,
+    pub span: Span,
+}
+
+// `Point { x: 1, y: 2 }`, with the fields in the order they were written in.
+#[derive(Debug, Clone)]
+pub struct StructExpression {
+    pub name: Symbol,
+    pub fields: Vec<FieldInit>,
+    pub name_span: Span,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+// The `x: 1` in `Point { x: 1, y: 2 }`.
+#[derive(Debug, Clone)]
+pub struct FieldInit {
+    pub name: Symbol,
+    pub value: ExprId,
+    pub name_span: Span,
+}
+
+// `receiver.field`
+#[derive(Debug, Clone)]
+pub struct FieldExpression {
+    pub receiver: ExprId,
+    pub field: Symbol,
+    pub field_span: Span,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
 pub struct Parameter {
-    pub name: String,
+    pub name: Symbol,
     pub param_type: Type,
+    pub name_span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlockExpression {
     pub statements: Vec<Statement>,
-    pub expression: Option<Expression>,
+    pub expression: Option<ExprId>,
+    pub span: Span,
+}
+
+impl BlockExpression {
+    // Same as `Statement::for_each_id()`.
+    pub fn for_each_id(&mut self, f: &mut impl FnMut(&mut ExprId)) {
+        self.statements.iter_mut().for_each(|statement| statement.for_each_id(f));
+        if let Some(expr) = &mut self.expression {
+            f(expr);
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IfExpression {
     // This will get type-checked to see if it boils down into the `bool` type.
-    pub condition: Expression,
+    pub condition: ExprId,
     pub body: BlockExpression,
-    pub else_body: Option<Box<ElseExpression>>,
+    pub else_body: Option<ElseExpression>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ElseExpression {
     Else(BlockExpression),
-    ElseIf(IfExpression),
+    // Always an `Expression::If`.
+    ElseIf(ExprId),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BinaryExpression {
-    pub lhs: Expression,
-    pub rhs: Expression,
+    pub lhs: ExprId,
+    pub rhs: ExprId,
     pub op: BinaryOperator,
+    // The type of the operands, filled in by the type checker. The interpreter goes by it to know
+    // how wide an integer operation is.
+    pub ty: Option<Type>,
+    pub span: Span,
+}
+
+impl BinaryExpression {
+    // The call a `|>` stands for: `x |> f(a, b)` is `f(x, a, b)`, and `x |> f` is `f(x)`.
+    pub fn desugar_pipe(&self, exprs: &Exprs) -> CallExpression {
+        let (function, rest) = match &exprs[self.rhs] {
+            Expression::Call(call) => (call.function, &call.arguments[..]),
+            _ => (self.rhs, &[][..]),
+        };
+        let mut arguments = vec![self.lhs];
+        arguments.extend_from_slice(rest);
+        CallExpression { function, arguments, span: self.span }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UnaryExpression {
-    pub rhs: Expression,
+    pub rhs: ExprId,
     pub op : UnaryOperator,
+    // Same as `BinaryExpression::ty`.
+    pub ty: Option<Type>,
+    pub span: Span,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BinaryOperator {
     Add,
     Sub,
@@ -145,7 +534,7 @@ pub enum BinaryOperator {
 }
 
 // Leading Plus/Minus signs
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum UnaryOperator {
     BoolNot,
 
@@ -155,33 +544,87 @@ pub enum UnaryOperator {
     Minus,
 }
 
-#[derive(Debug)]
+impl std::fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+            BinaryOperator::Mod => "%",
+
+            BinaryOperator::BitOr => "|",
+            BinaryOperator::BitAnd => "&",
+            BinaryOperator::BitXor => "^",
+            BinaryOperator::BitRight => ">>",
+            BinaryOperator::BitLeft => "<<",
+
+            BinaryOperator::BoolOr => "||",
+            BinaryOperator::BoolAnd => "&&",
+
+            BinaryOperator::Eq => "==",
+            BinaryOperator::Ne => "!=",
+            BinaryOperator::Ge => ">=",
+            BinaryOperator::Le => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::Lt => "<",
+
+            BinaryOperator::Pipe => "|>",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl std::fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            UnaryOperator::BoolNot => "!",
+            UnaryOperator::BitNot => "~",
+            UnaryOperator::Plus => "+",
+            UnaryOperator::Minus => "-",
+        };
+        write!(f, "{op}")
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct LiteralExpression {
-    pub kind: LitKind
+    pub kind: LitKind,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LitKind {
     Bool(bool),
-    Int(u128),
-    Float(f64),
+    // `ty` is the type the literal's suffix gives it, like the `u8` in `10u8`.
+    Int { value: u128, ty: Option<Type> },
+    Float { value: f64, ty: Option<Type> },
     Str(String),
+    // A string with expressions embedded in it, like `"hello {name}"`.
+    Interpolated(Vec<StrPart>),
     Char(char),
     Tuple(Tuple),
     List(List),
 }
 
-#[derive(Debug)]
-pub struct Tuple(pub Vec<Expression>);
-#[derive(Debug)]
-pub struct List(pub Vec<Expression>);
+#[derive(Debug, Clone)]
+pub enum StrPart {
+    Text(String),
+    Expr(ExprId),
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct Tuple(pub Vec<ExprId>);
+#[derive(Debug, Clone)]
+pub struct List(pub Vec<ExprId>);
+
+#[derive(Debug, Clone)]
 pub struct IdentExpression {
-    pub name: String,
+    pub name: Symbol,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Bool,
     Int { sign: bool, kind: IntKind },
@@ -192,10 +635,14 @@ pub enum Type {
     List(Box<Type>),
     Fn { arguments: Vec<Type>, return_type: Box<Type> },
     Void,
-    UserDefined { name: String },
+    UserDefined { name: Symbol },
+    // A type parameter of the item it's used in.
+    Generic { name: Symbol },
+    // A generic struct or enum with its type arguments filled in, like `Pair<i32, str>`.
+    Applied { name: Symbol, arguments: Box<[Type]> },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IntKind {
     Bit8,
     Bit16,
@@ -203,13 +650,83 @@ pub enum IntKind {
     Bit64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FloatKind {
     Bit32,
     Bit64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TupleType(pub Vec<Type>);
 
+impl IntKind {
+    pub fn bits(self) -> u32 {
+        match self {
+            IntKind::Bit8  => 8,
+            IntKind::Bit16 => 16,
+            IntKind::Bit32 => 32,
+            IntKind::Bit64 => 64,
+        }
+    }
+}
+
+
+
+impl std::fmt::Display for Type {
+    // Writes the type back out the same way `parse_type()` reads it in.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Bool => write!(f, "bool"),
+            Type::Int { sign, kind } => {
+                let sign = if *sign { 'i' } else { 'u' };
+                write!(f, "{sign}{}", kind.bits())
+            }
+            Type::Float { kind: FloatKind::Bit32 } => write!(f, "f32"),
+            Type::Float { kind: FloatKind::Bit64 } => write!(f, "f64"),
+            Type::Str => write!(f, "str"),
+            Type::Char => write!(f, "char"),
+            Type::Tuple(TupleType(types)) => {
+                write!(f, "(")?;
+                write_separated(f, types)?;
+                write!(f, ")")
+            }
+            Type::List(inner) => write!(f, "{{{inner}}}"),
+            Type::Fn { arguments, return_type } => {
+                write!(f, "fn(")?;
+                write_separated(f, arguments)?;
+                write!(f, ") -> {return_type}")
+            }
+            Type::Void => write!(f, "void"),
+            Type::UserDefined { name } | Type::Generic { name } => write!(f, "{name}"),
+            Type::Applied { name, arguments } => {
+                write!(f, "{name}<")?;
+                write_separated(f, arguments)?;
+                write!(f, ">")
+            }
+        }
+    }
+}
+
+// The `<A, B>` that goes after a generic item's name, or nothing if it doesn't have any.
+pub fn generic_params(generics: &[Symbol]) -> String {
+    match generics.is_empty() {
+        true => String::new(),
+        false => format!("<{}>", join_names(generics, ", ")),
+    }
+}
+
+// `names` written out with `separator` between them.
+pub fn join_names(names: &[Symbol], separator: &str) -> String {
+    names.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(separator)
+}
+
+fn write_separated(f: &mut std::fmt::Formatter<'_>, types: &[Type]) -> std::fmt::Result {
+    for (i, ty) in types.iter().enumerate() {
+        if i > 0 { write!(f, ", ")?; }
+        write!(f, "{ty}")?;
+    }
+    Ok(())
+}
 
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,444 @@
+// Writes trees back out as Alisa source. Unlike `format`, this doesn't need the source a tree came
+// from, so it works just as well on trees a pass built or changed, but comments and the way
+// literals were spelled are gone. Parentheses only go where the operators' binding powers need them.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::parse::{infix_binding_power, prefix_binding_power};
+use super::*;
+
+const INDENT: &str = "    ";
+
+impl Display for ASTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut printer = Printer { f, exprs: &self.exprs, indent: 0 };
+        for statement in self.root.iter().filter(|statement| printed(statement)) {
+            printer.statement(statement)?;
+            printer.f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl Exprs {
+    // Writes out `expr` the same way it is when the whole tree gets written out.
+    pub fn display(&self, expr: ExprId) -> impl Display + '_ {
+        DisplayExpr { exprs: self, expr }
+    }
+}
+
+struct DisplayExpr<'a> {
+    exprs: &'a Exprs,
+    expr: ExprId,
+}
+
+impl Display for DisplayExpr<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Printer { f, exprs: self.exprs, indent: 0 }.expr(self.expr)
+    }
+}
+
+// Nothing is left of a statement that didn't parse to print.
+fn printed(statement: &Statement) -> bool {
+    !matches!(statement, Statement::Error { .. } | Statement::EOF)
+}
+
+struct Printer<'a, 'f> {
+    f: &'a mut Formatter<'f>,
+    exprs: &'a Exprs,
+    indent: usize,
+}
+
+impl Printer<'_, '_> {
+    fn line(&mut self) -> fmt::Result {
+        self.f.write_char('\n')?;
+        for _ in 0..self.indent {
+            self.f.write_str(INDENT)?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> fmt::Result {
+        match statement {
+            Statement::Function(item) => self.function(item, None),
+
+            Statement::Struct(item) => {
+                self.visibility(item.public)?;
+                write!(self.f, "struct {}{} {{", item.name, generic_params(&item.generics))?;
+                self.members(&item.fields, |p, field| write!(p.f, "{}: {},", field.name, field.param_type))
+            }
+
+            Statement::Enum(item) => {
+                self.visibility(item.public)?;
+                write!(self.f, "enum {}{} {{", item.name, generic_params(&item.generics))?;
+                self.members(&item.variants, |p, variant| write!(p.f, "{variant}"))
+            }
+
+            Statement::Impl(item) => {
+                write!(self.f, "impl{} ", generic_params(&item.generics))?;
+                if let Some(trait_name) = item.trait_name {
+                    write!(self.f, "{trait_name} for ")?;
+                }
+                write!(self.f, "{} {{", item.self_type)?;
+                self.members(&item.methods, |p, method| p.function(method, Some(&item.self_type)))
+            }
+
+            Statement::Trait(item) => {
+                self.visibility(item.public)?;
+                write!(self.f, "trait {} {{", item.name)?;
+                let self_type = Type::Generic { name: "Self".into() };
+                self.members(&item.methods, |p, method| {
+                    let TraitMethod { name, generics, arguments, return_type, .. } = method;
+                    p.signature(*name, generics, arguments, return_type, Some(&self_type))?;
+                    match &method.block {
+                        Some(block) => {
+                            p.f.write_char(' ')?;
+                            p.block(block)
+                        }
+                        None => p.f.write_char(';'),
+                    }
+                })
+            }
+
+            Statement::Import(item) => write!(self.f, "{} {};", item.keyword(), join_names(&item.path, "::")),
+
+            Statement::Let(item) => {
+                write!(self.f, "let {}", item.name)?;
+                if let Some(var_type) = &item.var_type {
+                    write!(self.f, ": {var_type}")?;
+                }
+                self.f.write_str(" = ")?;
+                self.expr(item.value)?;
+                self.f.write_char(';')
+            }
+
+            Statement::Expression { expr, end_token, .. } => {
+                self.expr(*expr)?;
+                match end_token.kind {
+                    TokenKind::Semi => self.f.write_char(';'),
+                    _ => Ok(()),
+                }
+            }
+
+            Statement::Error { .. } | Statement::EOF => Ok(()),
+        }
+    }
+
+    fn visibility(&mut self, public: bool) -> fmt::Result {
+        match public {
+            true => self.f.write_str("pub "),
+            false => Ok(()),
+        }
+    }
+
+    // What's inside the braces of a struct, enum, `impl` or trait, each on its own line.
+    fn members<T>(&mut self, members: &[T], mut print: impl FnMut(&mut Self, &T) -> fmt::Result) -> fmt::Result {
+        if !members.is_empty() {
+            self.indent += 1;
+            for member in members {
+                self.line()?;
+                print(self, member)?;
+            }
+            self.indent -= 1;
+            self.line()?;
+        }
+        self.f.write_char('}')
+    }
+
+    fn function(&mut self, item: &FunctionStatement, self_type: Option<&Type>) -> fmt::Result {
+        self.visibility(item.public)?;
+        self.signature(item.name, &item.generics, &item.arguments, &item.return_type, self_type)?;
+        self.f.write_char(' ')?;
+        self.block(&item.block)
+    }
+
+    fn signature(
+        &mut self,
+        name: Symbol,
+        generics: &[Symbol],
+        arguments: &[Parameter],
+        return_type: &Type,
+        self_type: Option<&Type>,
+    ) -> fmt::Result {
+        write!(self.f, "fn {name}{}(", generic_params(generics))?;
+        self.params(arguments, self_type)?;
+        write!(self.f, ") -> {return_type}")
+    }
+
+    // A method's `self` is left without its type if it's just the type the method is for.
+    fn params(&mut self, params: &[Parameter], self_type: Option<&Type>) -> fmt::Result {
+        for (i, param) in params.iter().enumerate() {
+            if i > 0 { self.f.write_str(", ")?; }
+            match param.name == "self" && Some(&param.param_type) == self_type {
+                true => self.f.write_str("self")?,
+                false => write!(self.f, "{}: {}", param.name, param.param_type)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, block: &BlockExpression) -> fmt::Result {
+        if block.statements.iter().all(|statement| !printed(statement)) && block.expression.is_none() {
+            return self.f.write_str("{}");
+        }
+
+        self.f.write_char('{')?;
+        self.indent += 1;
+        for statement in block.statements.iter().filter(|statement| printed(statement)) {
+            self.line()?;
+            self.statement(statement)?;
+        }
+        if let Some(expr) = block.expression {
+            self.line()?;
+            self.expr(expr)?;
+        }
+        self.indent -= 1;
+        self.line()?;
+        self.f.write_char('}')
+    }
+
+    fn expr(&mut self, expr: ExprId) -> fmt::Result {
+        let exprs = self.exprs;
+        match &exprs[expr] {
+            Expression::Literal(literal) => self.literal(&literal.kind),
+
+            Expression::Identifier(ident) => write!(self.f, "{}", ident.name),
+
+            Expression::Block(block) => self.block(block),
+
+            Expression::If(if_expr) => self.if_expr(if_expr),
+
+            Expression::Closure(closure) => {
+                self.f.write_str("\\(")?;
+                self.params(&closure.arguments, None)?;
+                write!(self.f, ") -> {} ", closure.return_type)?;
+                self.block(&closure.block)
+            }
+
+            Expression::Call(call) => {
+                let parens = matches!(exprs[call.function], Expression::Binary(_) | Expression::Unary(_));
+                self.operand(call.function, parens)?;
+                self.f.write_char('(')?;
+                self.separated(&call.arguments)?;
+                self.f.write_char(')')
+            }
+
+            Expression::MethodCall(call) => {
+                let parens = matches!(exprs[call.receiver], Expression::Binary(_) | Expression::Unary(_));
+                self.operand(call.receiver, parens)?;
+                write!(self.f, ".{}(", call.method)?;
+                self.separated(&call.arguments)?;
+                self.f.write_char(')')
+            }
+
+            Expression::Struct(struct_expr) => {
+                write!(self.f, "{} {{", struct_expr.name)?;
+                for (i, field) in struct_expr.fields.iter().enumerate() {
+                    self.f.write_str(if i > 0 { ", " } else { " " })?;
+                    write!(self.f, "{}: ", field.name)?;
+                    self.expr(field.value)?;
+                }
+                match struct_expr.fields.is_empty() {
+                    true => self.f.write_char('}'),
+                    false => self.f.write_str(" }"),
+                }
+            }
+
+            Expression::Field(field) => {
+                let parens = matches!(exprs[field.receiver], Expression::Binary(_) | Expression::Unary(_));
+                self.operand(field.receiver, parens)?;
+                write!(self.f, ".{}", field.field)
+            }
+
+            Expression::Unary(unary) => {
+                write!(self.f, "{}", unary.op)?;
+                self.operand(unary.rhs, unary_operand_parens(exprs, unary))
+            }
+
+            Expression::Binary(binary) => {
+                let (l_bp, r_bp) = infix_binding_power(binary.op);
+
+                // Whatever's on the left can't have an operator that binds looser than this one
+                // sticking out at its end, since it would swallow everything to the right.
+                let lhs_parens = match &exprs[binary.lhs] {
+                    Expression::Binary(lhs) => infix_binding_power(lhs.op).1 <= l_bp,
+                    _ => false,
+                } || open_prefix(exprs, binary.lhs).is_some_and(|bp| bp <= l_bp);
+
+                self.operand(binary.lhs, lhs_parens)?;
+                write!(self.f, " {} ", binary.op)?;
+                self.operand(binary.rhs, operand_parens(exprs, binary.rhs, r_bp))
+            }
+        }
+    }
+
+    fn operand(&mut self, expr: ExprId, parens: bool) -> fmt::Result {
+        if parens { self.f.write_char('(')?; }
+        self.expr(expr)?;
+        if parens { self.f.write_char(')')?; }
+        Ok(())
+    }
+
+    fn separated(&mut self, expressions: &[ExprId]) -> fmt::Result {
+        for (i, &expr) in expressions.iter().enumerate() {
+            if i > 0 { self.f.write_str(", ")?; }
+            self.expr(expr)?;
+        }
+        Ok(())
+    }
+
+    fn if_expr(&mut self, if_expr: &IfExpression) -> fmt::Result {
+        self.f.write_str("if ")?;
+        self.operand(if_expr.condition, condition_parens(self.exprs, if_expr.condition))?;
+        self.f.write_char(' ')?;
+        self.block(&if_expr.body)?;
+
+        match &if_expr.else_body {
+            Some(ElseExpression::Else(block)) => {
+                self.f.write_str(" else ")?;
+                self.block(block)
+            }
+            Some(ElseExpression::ElseIf(else_if)) => {
+                self.f.write_str(" else ")?;
+                self.expr(*else_if)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn literal(&mut self, kind: &LitKind) -> fmt::Result {
+        match kind {
+            LitKind::Bool(value) => write!(self.f, "{value}"),
+            LitKind::Int { value, ty } => {
+                write!(self.f, "{value}")?;
+                self.suffix(ty)
+            }
+            // Debug always writes a float so that it reads back as one, like `1.0` or `1e-7`.
+            LitKind::Float { value, ty } => {
+                write!(self.f, "{value:?}")?;
+                self.suffix(ty)
+            }
+            LitKind::Str(text) => {
+                self.f.write_char('"')?;
+                self.escaped(text, '"')?;
+                self.f.write_char('"')
+            }
+            LitKind::Interpolated(parts) => {
+                self.f.write_char('"')?;
+                for part in parts {
+                    match part {
+                        StrPart::Text(text) => self.escaped(text, '"')?,
+                        StrPart::Expr(expr) => {
+                            self.f.write_char('{')?;
+                            self.expr(*expr)?;
+                            self.f.write_char('}')?;
+                        }
+                    }
+                }
+                self.f.write_char('"')
+            }
+            LitKind::Char(c) => {
+                self.f.write_char('\'')?;
+                self.escaped(c.encode_utf8(&mut [0; 4]), '\'')?;
+                self.f.write_char('\'')
+            }
+            LitKind::Tuple(Tuple(expressions)) => {
+                self.f.write_char('(')?;
+                self.separated(expressions)?;
+                // A single element needs the comma to not just be a parenthesized expression.
+                if expressions.len() == 1 {
+                    self.f.write_char(',')?;
+                }
+                self.f.write_char(')')
+            }
+            LitKind::List(List(expressions)) => {
+                self.f.write_char('[')?;
+                self.separated(expressions)?;
+                self.f.write_char(']')
+            }
+        }
+    }
+
+    fn suffix(&mut self, ty: &Option<Type>) -> fmt::Result {
+        match ty {
+            Some(ty) => write!(self.f, "{ty}"),
+            None => Ok(()),
+        }
+    }
+
+    // The contents of a string or character literal quoted with `quote`. Braces are escaped in
+    // strings so that they aren't taken for an embedded expression.
+    fn escaped(&mut self, text: &str, quote: char) -> fmt::Result {
+        for c in text.chars() {
+            match c {
+                '\\' => self.f.write_str("\\\\")?,
+                '\n' => self.f.write_str("\\n")?,
+                '\t' => self.f.write_str("\\t")?,
+                '\r' => self.f.write_str("\\r")?,
+                '\0' => self.f.write_str("\\0")?,
+                '{' | '}' if quote == '"' => write!(self.f, "\\{c}")?,
+                c if c == quote => write!(self.f, "\\{c}")?,
+                c if c.is_control() => write!(self.f, "\\u{{{:x}}}", c as u32)?,
+                c => self.f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Whether `expr` needs parentheses when it gets parsed with `parse_expr(min_bp)`.
+pub(crate) fn operand_parens(exprs: &Exprs, expr: ExprId, min_bp: u8) -> bool {
+    match &exprs[expr] {
+        Expression::Binary(binary) => infix_binding_power(binary.op).0 < min_bp,
+        _ => false,
+    }
+}
+
+// Whether the operand of `unary` needs parentheses. A `-` right before another one, like in `--1`,
+// would parse, but it reads like a decrement.
+pub(crate) fn unary_operand_parens(exprs: &Exprs, unary: &UnaryExpression) -> bool {
+    let ((), r_bp) = prefix_binding_power(unary.op);
+    let negated = unary.op == UnaryOperator::Minus && match &exprs[unary.rhs] {
+        Expression::Unary(rhs) => rhs.op == UnaryOperator::Minus,
+        Expression::Literal(literal) => matches!(literal.kind, LitKind::Float { value, .. } if value.is_sign_negative()),
+        _ => false,
+    };
+    negated || operand_parens(exprs, unary.rhs, r_bp)
+}
+
+// Whether an `if` condition needs parentheses, which it does if an empty struct literal sticks out
+// of it, since `if x == Empty {}` would take the `{}` for the body.
+pub(crate) fn condition_parens(exprs: &Exprs, expr: ExprId) -> bool {
+    match &exprs[expr] {
+        Expression::Struct(struct_expr) => struct_expr.fields.is_empty(),
+        Expression::Binary(binary) => condition_parens(exprs, binary.lhs) || condition_parens(exprs, binary.rhs),
+        Expression::Unary(unary) => condition_parens(exprs, unary.rhs),
+        Expression::Call(call) => condition_parens(exprs, call.function),
+        Expression::MethodCall(call) => condition_parens(exprs, call.receiver),
+        Expression::Field(field) => condition_parens(exprs, field.receiver),
+        _ => false,
+    }
+}
+
+// The loosest binding prefix operator at the very end of `expr`, if there is one. `!a == b` parses
+// as `!(a == b)`, so anything like that on the left of an operator needs to be wrapped.
+pub(crate) fn open_prefix(exprs: &Exprs, expr: ExprId) -> Option<u8> {
+    match &exprs[expr] {
+        Expression::Binary(binary) => {
+            let (_, r_bp) = infix_binding_power(binary.op);
+            match operand_parens(exprs, binary.rhs, r_bp) {
+                true => None,
+                false => open_prefix(exprs, binary.rhs),
+            }
+        }
+        Expression::Unary(unary) => {
+            let ((), r_bp) = prefix_binding_power(unary.op);
+            let inner = match unary_operand_parens(exprs, unary) {
+                true => None,
+                false => open_prefix(exprs, unary.rhs),
+            };
+            Some(inner.map_or(r_bp, |inner| inner.min(r_bp)))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,243 @@
+// Names are interned, so that each one is only allocated the first time it's seen and comparing
+// two of them is just comparing numbers. They go into an interner shared by the whole process that
+// keeps them for good, unless the thread interning them has entered a session of its own. A
+// long-running process like the language server uses one so that every half-typed name it's ever
+// seen doesn't pile up. Once it's done with the names it drops the session, and nothing outside of
+// it loses a name along with it.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, LazyLock, RwLock, Weak};
+
+// A handle to an interned name, good for as long as the interner it came from is. Symbols from
+// different interners are different even when their names are the same.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    index: u32,
+    // Which interner it's from, 0 being the shared one.
+    interner: u32,
+}
+
+struct Interner {
+    id: u32,
+    names: Vec<Arc<str>>,
+    symbols: HashMap<Arc<str>, Symbol>,
+}
+
+// Shared between threads, so that a tree can be parsed in one and checked in another.
+static SHARED: LazyLock<RwLock<Interner>> = LazyLock::new(|| RwLock::new(Interner::new(0)));
+
+// The interner of every session that might still be around, so that their symbols can be used
+// from any thread, including ones that never entered them.
+static SESSIONS: LazyLock<RwLock<HashMap<u32, Weak<RwLock<Interner>>>>> = LazyLock::new(Default::default);
+static NEXT_SESSION: AtomicU32 = AtomicU32::new(1);
+
+thread_local! {
+    // The session the thread has entered, if it's entered one.
+    static CURRENT: RefCell<Option<Current>> = const { RefCell::new(None) };
+
+    // The names of the shared interner that the thread has looked up so far.
+    static SHARED_NAMES: RefCell<Vec<Arc<str>>> = const { RefCell::new(Vec::new()) };
+}
+
+// A session's interner, along with the names of it that the thread has looked up so far.
+struct Current {
+    id: u32,
+    interner: Arc<RwLock<Interner>>,
+    names: RefCell<Vec<Arc<str>>>,
+}
+
+impl Interner {
+    fn new(id: u32) -> Self {
+        Self { id, names: Vec::new(), symbols: HashMap::new() }
+    }
+
+    fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+        let name: Arc<str> = name.into();
+        let symbol = Symbol { index: self.names.len() as u32, interner: self.id };
+        self.names.push(name.clone());
+        self.symbols.insert(name, symbol);
+        symbol
+    }
+
+    fn name(&self, symbol: Symbol) -> Arc<str> {
+        assert_eq!(symbol.interner, self.id, "symbol looked up in an interner it isn't from");
+        self.names[symbol.index as usize].clone()
+    }
+}
+
+// Whichever interner names go into on this thread.
+fn with_current<T>(f: impl FnOnce(&RwLock<Interner>) -> T) -> T {
+    CURRENT.with_borrow(|current| f(current.as_ref().map_or(&SHARED, |current| &current.interner)))
+}
+
+// Calls `f` on the name of `symbol`, going by `names`, the names of `interner` the thread has looked
+// up so far. Interners only ever add names, so those are never out of date, and the interner only
+// gets locked to copy over whatever it got since the last time when the name isn't there yet. `f`
+// can't look up any other names.
+fn cached<T>(names: &RefCell<Vec<Arc<str>>>, interner: &RwLock<Interner>, symbol: Symbol, f: impl FnOnce(&Arc<str>) -> T) -> T {
+    let mut names = names.borrow_mut();
+    let index = symbol.index as usize;
+    if index >= names.len() {
+        let interner = interner.read().unwrap();
+        assert_eq!(symbol.interner, interner.id, "symbol looked up in an interner it isn't from");
+        let len = names.len();
+        names.extend_from_slice(&interner.names[len..]);
+    }
+    f(&names[index])
+}
+
+// Names interned apart from the shared ones, by whichever threads have entered the session. They
+// all go away with it, after which using one of its symbols panics.
+pub struct Names {
+    id: u32,
+    interner: Arc<RwLock<Interner>>,
+}
+
+// Undoes `Names::enter()` when dropped. It belongs to the thread that entered the session.
+pub struct Entered {
+    previous: Option<Current>,
+    _thread: PhantomData<*const ()>,
+}
+
+impl Names {
+    pub fn new() -> Self {
+        let id = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+        let interner = Arc::new(RwLock::new(Interner::new(id)));
+
+        let mut sessions = SESSIONS.write().unwrap();
+        sessions.retain(|_, session| session.strong_count() > 0);
+        sessions.insert(id, Arc::downgrade(&interner));
+        Self { id, interner }
+    }
+
+    // Interns names into this session on the current thread, until what's returned is dropped.
+    pub fn enter(&self) -> Entered {
+        let current = Current { id: self.id, interner: self.interner.clone(), names: RefCell::default() };
+        let previous = CURRENT.replace(Some(current));
+        Entered { previous, _thread: PhantomData }
+    }
+
+    // How many names are interned in the session.
+    pub fn count(&self) -> usize {
+        self.interner.read().unwrap().names.len()
+    }
+}
+
+impl Default for Names {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        CURRENT.set(self.previous.take());
+    }
+}
+
+impl Symbol {
+    pub fn intern(name: &str) -> Symbol {
+        with_current(|interner| {
+            if let Some(&symbol) = interner.read().unwrap().symbols.get(name) {
+                return symbol;
+            }
+            // Someone else might have interned it in between, which `Interner::intern` checks for.
+            interner.write().unwrap().intern(name)
+        })
+    }
+
+    pub fn as_str(self) -> Arc<str> {
+        self.with_str(Arc::clone)
+    }
+
+    // Calls `f` on the name without copying it. Only names from a session the thread isn't in
+    // take a lock every time. `f` can't look up any other names.
+    fn with_str<T>(self, f: impl FnOnce(&Arc<str>) -> T) -> T {
+        if self.interner == 0 {
+            return SHARED_NAMES.with(|names| cached(names, &SHARED, self, f));
+        }
+
+        // Most of the time it's from the session the thread is in.
+        let current = CURRENT.with_borrow(|current| match current {
+            Some(current) if current.id == self.interner => Ok(cached(&current.names, &current.interner, self, f)),
+            _ => Err(f),
+        });
+        let f = match current {
+            Ok(result) => return result,
+            Err(f) => f,
+        };
+
+        let session = SESSIONS.read().unwrap().get(&self.interner).and_then(Weak::upgrade);
+        match session {
+            Some(interner) => f(&interner.read().unwrap().name(self)),
+            None => panic!("symbol used after the session it was interned in was dropped"),
+        }
+    }
+
+    // `names` one after the other with `separator` in between, like the `geom::Point` in a path.
+    pub fn join(names: &[Symbol], separator: &str) -> Symbol {
+        match names {
+            [name] => *name,
+            _ => Symbol::intern(&super::join_names(names, separator)),
+        }
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.as_str().as_ref().into()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.with_str(|name| &**name == other)
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.with_str(|name| &**name == *other)
+    }
+}
+
+// Ordered by name rather than by when they were interned.
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if self == other {
+            return std::cmp::Ordering::Equal;
+        }
+        self.as_str().cmp(&other.as_str())
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+// Shown the same way a string is, so that trees read the same when debugging.
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&*self.as_str(), f)
+    }
+}
@@ -0,0 +1,258 @@
+use super::*;
+use super::visit::{self, Visitor};
+use super::visit_mut::{self, VisitorMut};
+use crate::parse::Parser;
+
+const SRC: &str = r#"
+struct Wrapper<T> { inner: T }
+trait Named { fn name(self: Self) -> str { describe(self) } }
+impl Named for Wrapper<i32> {
+    fn name(self: Wrapper<i32>) -> str { "wrapper {count(self)}" }
+}
+fn main() -> void {
+    let f = \(x: Point) -> {Pair<i32, str>} { [pair(x)] };
+    if ready() { go() } else if !waiting() { (wait(), 1u8) } else { stop() |> log };
+    value.method(argument);
+}
+"#;
+
+fn parse(src: &str) -> ASTree {
+    let (tree, errors) = Parser::parse(src);
+    assert!(errors.is_empty(), "{errors:?}");
+    tree
+}
+
+// Every identifier and user defined type name, in the order they're visited.
+#[derive(Default)]
+struct Names {
+    idents: Vec<String>,
+    types: Vec<String>,
+}
+
+impl Visitor for Names {
+    fn visit_ident(&mut self, _exprs: &Exprs, ident: &IdentExpression) {
+        self.idents.push(ident.name.to_string());
+    }
+
+    fn visit_type(&mut self, exprs: &Exprs, ty: &Type) {
+        if let Type::UserDefined { name } | Type::Applied { name, .. } = ty {
+            self.types.push(name.to_string());
+        }
+        visit::walk_type(self, exprs, ty);
+    }
+}
+
+#[test]
+fn visiting() {
+    let mut names = Names::default();
+    names.visit_tree(&parse(SRC));
+    assert_eq!(
+        names.idents,
+        ["describe", "self", "count", "self", "pair", "x", "ready", "go", "waiting", "wait", "stop", "log", "value", "argument"],
+    );
+    assert_eq!(names.types, ["Wrapper", "Wrapper", "Point", "Pair"]);
+
+    // Not walking into a node skips everything in it.
+    struct OutsideClosures(Names);
+    impl Visitor for OutsideClosures {
+        fn visit_closure(&mut self, _exprs: &Exprs, _closure: &ClosureExpression) {}
+        fn visit_ident(&mut self, exprs: &Exprs, ident: &IdentExpression) {
+            self.0.visit_ident(exprs, ident);
+        }
+    }
+    let mut outside = OutsideClosures(Names::default());
+    outside.visit_tree(&parse("let f = \\() -> i32 { a }; b;"));
+    assert_eq!(outside.0.idents, ["b"]);
+}
+
+#[test]
+fn visiting_mutably() {
+    // Qualifies every name the way linking a module does.
+    struct Qualify;
+    impl VisitorMut for Qualify {
+        fn visit_ident(&mut self, _exprs: &mut Exprs, ident: &mut IdentExpression) {
+            ident.name = Symbol::intern(&format!("m::{}", ident.name));
+        }
+
+        fn visit_type(&mut self, exprs: &mut Exprs, ty: &mut Type) {
+            if let Type::UserDefined { name } = ty {
+                *name = Symbol::intern(&format!("m::{name}"));
+            }
+            visit_mut::walk_type(self, exprs, ty);
+        }
+    }
+
+    let mut tree = parse(SRC);
+    Qualify.visit_tree(&mut tree);
+    let mut names = Names::default();
+    names.visit_tree(&tree);
+    assert!(names.idents.iter().all(|name| name.starts_with("m::")), "{:?}", names.idents);
+    assert_eq!(names.types, ["Wrapper", "Wrapper", "m::Point", "Pair"]);
+
+    // A node can be replaced outright.
+    struct Negate;
+    impl VisitorMut for Negate {
+        fn visit_expr(&mut self, exprs: &mut Exprs, expr: ExprId) {
+            visit_mut::walk_expr(self, exprs, expr);
+            if let Expression::Literal(LiteralExpression { kind: LitKind::Bool(value), .. }) = &mut exprs[expr] {
+                *value = !*value;
+            }
+        }
+    }
+    let mut tree = parse("let a = (true, [false]);");
+    Negate.visit_tree(&mut tree);
+    let exprs = &tree.exprs;
+    let Statement::Let(item) = &tree.root[0] else { panic!() };
+    let Expression::Literal(LiteralExpression { kind: LitKind::Tuple(Tuple(elements)), .. }) = &exprs[item.value] else { panic!() };
+    assert!(matches!(exprs[elements[0]], Expression::Literal(LiteralExpression { kind: LitKind::Bool(false), .. })));
+    let Expression::Literal(LiteralExpression { kind: LitKind::List(List(list)), .. }) = &exprs[elements[1]] else { panic!() };
+    assert!(matches!(exprs[list[0]], Expression::Literal(LiteralExpression { kind: LitKind::Bool(true), .. })));
+}
+
+const PROGRAM: &str = r#"
+import geom::Point;
+mod  shapes ;
+pub struct Pair<A, B> { first: A, second: B }
+enum Light { Red Green }
+pub trait Shape {
+    fn area(self) -> f64;
+    fn describe(self) -> str { "a shape of {self.area()} \{units\}" }
+}
+impl<T> Shape for Pair<T, T> {
+    fn area(self) -> f64 { 0.0 }
+}
+fn scale(x: f64, by: {f64}) -> (f64, i32) {
+    let tab: char = '\t';
+    let quote = '\'';
+    (x * 2.5e10f32, -(3 + 4) * 1u8)
+}
+fn main() -> void {
+    let add = \(a: i32, b: i32) -> i32 { a + b };
+    let done = !(ready() && waiting()) || (!ready()) == false;
+    if a < b { "line\nbreak \"quoted\"" } else if a % 2 == 0 { [1, 2, 3] } else {};
+    (a - b).abs() |> print;
+    let pair = Pair { first: Light::Red, second: geom::Point { x: (a + b).x, y: 2, } };
+    if pair == (Pair {}) {}
+    {
+        let inner = (1,);
+        inner
+    }
+}
+"#;
+
+const PRINTED: &str = r#"import geom::Point;
+mod shapes;
+pub struct Pair<A, B> {
+    first: A,
+    second: B,
+}
+enum Light {
+    Red
+    Green
+}
+pub trait Shape {
+    fn area(self) -> f64;
+    fn describe(self) -> str {
+        "a shape of {self.area()} \{units\}"
+    }
+}
+impl<T> Shape for Pair<T, T> {
+    fn area(self) -> f64 {
+        0.0
+    }
+}
+fn scale(x: f64, by: {f64}) -> (f64, i32) {
+    let tab: char = '\t';
+    let quote = '\'';
+    (x * 25000000000.0f32, -(3 + 4) * 1u8)
+}
+fn main() -> void {
+    let add = \(a: i32, b: i32) -> i32 {
+        a + b
+    };
+    let done = !(ready() && waiting()) || (!ready()) == false;
+    if a < b {
+        "line\nbreak \"quoted\""
+    } else if a % 2 == 0 {
+        [1, 2, 3]
+    } else {};
+    (a - b).abs() |> print;
+    let pair = Pair { first: Light::Red, second: geom::Point { x: (a + b).x, y: 2 } };
+    if (pair == Pair {}) {}
+    {
+        let inner = (1,);
+        inner
+    }
+}
+"#;
+
+#[test]
+fn printing_round_trips() {
+    let printed = parse(PROGRAM).to_string();
+    // Printing what was printed gets the same thing back, so nothing was lost in between.
+    assert_eq!(parse(&printed).to_string(), printed);
+    assert_eq!(printed, PRINTED);
+}
+
+#[test]
+fn printing_minimal_parens() {
+    let cases = [
+        ("(a + b) * c", "(a + b) * c"),
+        ("a + (b * c)", "a + b * c"),
+        ("a - (b - c)", "a - (b - c)"),
+        ("(a - b) - c", "a - b - c"),
+        ("!(a == b)", "!a == b"),
+        ("(!a) == b", "(!a) == b"),
+        ("-(a + b)", "-(a + b)"),
+        ("(-a).abs()", "(-a).abs()"),
+        ("(-a).x.y", "(-a).x.y"),
+        ("((f))(x)", "f(x)"),
+        ("a |> (b |> c)", "a |> (b |> c)"),
+        ("-(-1)", "-(-1)"),
+        ("-(-(-a))", "-(-(-a))"),
+        ("!(!a)", "!!a"),
+    ];
+    for (src, expected) in cases {
+        let tree = parse(&format!("{src};"));
+        let Statement::Expression { expr, .. } = tree.root[0] else { panic!("{src}") };
+        assert_eq!(tree.exprs.display(expr).to_string(), expected);
+    }
+}
+
+#[test]
+fn printing_rewritten_trees() {
+    // A pass that renames every variable, the way a desugaring pass would leave a tree.
+    struct Rename;
+    impl VisitorMut for Rename {
+        fn visit_ident(&mut self, _exprs: &mut Exprs, ident: &mut IdentExpression) {
+            ident.name = Symbol::intern(&format!("{}_1", ident.name));
+        }
+    }
+
+    let mut tree = parse("fn f(x: i32) -> i32 { let y = x * 2; y + 1 }");
+    Rename.visit_tree(&mut tree);
+    assert_eq!(tree.to_string(), "fn f(x: i32) -> i32 {\n    let y = x_1 * 2;\n    y_1 + 1\n}\n");
+}
+
+#[test]
+fn symbol_sessions() {
+    let shared = Symbol::intern("shared_name");
+    let names = super::Names::new();
+    let entered = names.enter();
+    let name = Symbol::intern("session_name");
+    assert_eq!(Symbol::intern("session_name"), name);
+    assert_eq!(names.count(), 1);
+    // The same name from somewhere else is a different symbol.
+    assert_ne!(Symbol::intern("shared_name"), shared);
+    drop(entered);
+
+    // Its symbols can still be used once it's been left, from any thread.
+    assert_eq!(&*name.as_str(), "session_name");
+    assert_eq!(std::thread::spawn(move || name.to_string()).join().unwrap(), "session_name");
+    assert_eq!(Symbol::intern("shared_name"), shared);
+
+    // Until it's dropped, which doesn't take anything from the other interners with it.
+    drop(names);
+    assert!(std::panic::catch_unwind(|| name.as_str()).is_err());
+    assert_eq!(&*shared.as_str(), "shared_name");
+}
@@ -1,5 +1,9 @@
 use crate::lex;
 
+pub const KEYWORDS: [&str; 14] = [
+    "fn", "struct", "enum", "impl", "trait", "for", "pub", "import", "mod", "let", "if", "else", "true", "false",
+];
+
 macro_rules! T {
     ("ID") => { TokenKind::Identifier };
 
@@ -9,11 +13,18 @@ macro_rules! T {
     ("let") => { TokenKind::Let };
     ("if") => { TokenKind::If };
     ("else") => { TokenKind::Else };
+    ("impl") => { TokenKind::Impl };
+    ("trait") => { TokenKind::Trait };
+    ("for") => { TokenKind::For };
+    ("pub") => { TokenKind::Pub };
+    ("import") => { TokenKind::Import };
+    ("mod") => { TokenKind::Mod };
 
     // Punctuation
     ("->") => { TokenKind::RArrow };
     (";") => { TokenKind::Semi };
     (":") => { TokenKind::Colon };
+    ("::") => { TokenKind::ColonColon };
     (",") => { TokenKind::Comma };
     (".") => { TokenKind::Dot };
     ("(") => { TokenKind::OpenParen };
@@ -70,6 +81,7 @@ macro_rules! T {
 pub(crate) use T;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum TokenKind {
     Identifier,
 
@@ -86,6 +98,18 @@ pub enum TokenKind {
     If,
     // `else`
     Else,
+    // `impl`
+    Impl,
+    // `trait`
+    Trait,
+    // `for`
+    For,
+    // `pub`
+    Pub,
+    // `import`
+    Import,
+    // `mod`
+    Mod,
 
     // Punctuation
     // `->`
@@ -94,6 +118,8 @@ pub enum TokenKind {
     Semi,
     // `:`
     Colon,
+    // `::`
+    ColonColon,
     // `,`
     Comma,
     // `.`
@@ -144,6 +170,10 @@ pub enum TokenKind {
 
     Literal { kind: LiteralKind },
 
+    // Anything the lexer didn't recognize. The parser reports these instead of the lexer
+    // panicking so that tooling can keep going.
+    Unknown,
+
     EOF,
 }
 
@@ -178,6 +208,7 @@ pub enum LiteralKind {
     Int,
     Float, 
     Str { terminated: bool },
+    RawStr { terminated: bool },
     Char { terminated: bool },
 }
 
@@ -187,6 +218,7 @@ impl From<lex::LiteralKind> for LiteralKind {
             lex::LiteralKind::Int => Self::Int,
             lex::LiteralKind::Float => Self::Float,
             lex::LiteralKind::Str { terminated } => Self::Str { terminated },
+            lex::LiteralKind::RawStr { terminated } => Self::RawStr { terminated },
             lex::LiteralKind::Char { terminated }=> Self::Char { terminated },
         }
     }
@@ -203,4 +235,8 @@ impl Token {
     pub fn new(kind: TokenKind, start: usize, end: usize) -> Token {
         Token { kind, start, end }
     }
+
+    pub fn span(&self) -> super::Span {
+        super::Span::new(self.start, self.end)
+    }
 }
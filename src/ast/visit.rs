@@ -0,0 +1,293 @@
+// Walking a tree without having to match on every kind of node. Each `visit_*` method starts out
+// calling the `walk_*` function for its node, which visits everything inside of it, so a pass only
+// overrides the methods for the nodes it cares about. An overridden method can call `walk_*`
+// itself to keep going into the node, or not to skip it.
+
+use super::*;
+
+pub trait Visitor {
+    fn visit_tree(&mut self, tree: &ASTree) {
+        walk_tree(self, tree)
+    }
+
+    fn visit_statement(&mut self, exprs: &Exprs, statement: &Statement) {
+        walk_statement(self, exprs, statement)
+    }
+
+    fn visit_function(&mut self, exprs: &Exprs, item: &FunctionStatement) {
+        walk_function(self, exprs, item)
+    }
+
+    fn visit_struct(&mut self, exprs: &Exprs, item: &StructStatement) {
+        walk_struct(self, exprs, item)
+    }
+
+    fn visit_enum(&mut self, _exprs: &Exprs, _item: &EnumStatement) {}
+
+    fn visit_impl(&mut self, exprs: &Exprs, item: &ImplStatement) {
+        walk_impl(self, exprs, item)
+    }
+
+    fn visit_trait(&mut self, exprs: &Exprs, item: &TraitStatement) {
+        walk_trait(self, exprs, item)
+    }
+
+    fn visit_trait_method(&mut self, exprs: &Exprs, method: &TraitMethod) {
+        walk_trait_method(self, exprs, method)
+    }
+
+    fn visit_import(&mut self, _exprs: &Exprs, _item: &ImportStatement) {}
+
+    fn visit_let(&mut self, exprs: &Exprs, item: &LetStatement) {
+        walk_let(self, exprs, item)
+    }
+
+    fn visit_expr(&mut self, exprs: &Exprs, expr: ExprId) {
+        walk_expr(self, exprs, expr)
+    }
+
+    fn visit_closure(&mut self, exprs: &Exprs, closure: &ClosureExpression) {
+        walk_closure(self, exprs, closure)
+    }
+
+    fn visit_block(&mut self, exprs: &Exprs, block: &BlockExpression) {
+        walk_block(self, exprs, block)
+    }
+
+    fn visit_call(&mut self, exprs: &Exprs, call: &CallExpression) {
+        walk_call(self, exprs, call)
+    }
+
+    fn visit_method_call(&mut self, exprs: &Exprs, call: &MethodCallExpression) {
+        walk_method_call(self, exprs, call)
+    }
+
+    fn visit_struct_expr(&mut self, exprs: &Exprs, struct_expr: &StructExpression) {
+        walk_struct_expr(self, exprs, struct_expr)
+    }
+
+    fn visit_field(&mut self, exprs: &Exprs, field: &FieldExpression) {
+        walk_field(self, exprs, field)
+    }
+
+    fn visit_if(&mut self, exprs: &Exprs, if_expr: &IfExpression) {
+        walk_if(self, exprs, if_expr)
+    }
+
+    fn visit_else(&mut self, exprs: &Exprs, else_expr: &ElseExpression) {
+        walk_else(self, exprs, else_expr)
+    }
+
+    fn visit_binary(&mut self, exprs: &Exprs, binary: &BinaryExpression) {
+        walk_binary(self, exprs, binary)
+    }
+
+    fn visit_unary(&mut self, exprs: &Exprs, unary: &UnaryExpression) {
+        walk_unary(self, exprs, unary)
+    }
+
+    fn visit_literal(&mut self, exprs: &Exprs, literal: &LiteralExpression) {
+        walk_literal(self, exprs, literal)
+    }
+
+    fn visit_ident(&mut self, _exprs: &Exprs, _ident: &IdentExpression) {}
+
+    fn visit_param(&mut self, exprs: &Exprs, param: &Parameter) {
+        walk_param(self, exprs, param)
+    }
+
+    fn visit_type(&mut self, exprs: &Exprs, ty: &Type) {
+        walk_type(self, exprs, ty)
+    }
+}
+
+pub fn walk_tree<V: Visitor + ?Sized>(visitor: &mut V, tree: &ASTree) {
+    for statement in &tree.root {
+        visitor.visit_statement(&tree.exprs, statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, statement: &Statement) {
+    match statement {
+        Statement::Function(item) => visitor.visit_function(exprs, item),
+        Statement::Struct(item) => visitor.visit_struct(exprs, item),
+        Statement::Enum(item) => visitor.visit_enum(exprs, item),
+        Statement::Impl(item) => visitor.visit_impl(exprs, item),
+        Statement::Trait(item) => visitor.visit_trait(exprs, item),
+        Statement::Import(item) => visitor.visit_import(exprs, item),
+        Statement::Let(item) => visitor.visit_let(exprs, item),
+        Statement::Expression { expr, .. } => visitor.visit_expr(exprs, *expr),
+        Statement::Error { .. } | Statement::EOF => (),
+    }
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, item: &FunctionStatement) {
+    for param in &item.arguments {
+        visitor.visit_param(exprs, param);
+    }
+    visitor.visit_type(exprs, &item.return_type);
+    visitor.visit_block(exprs, &item.block);
+}
+
+pub fn walk_struct<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, item: &StructStatement) {
+    for field in &item.fields {
+        visitor.visit_param(exprs, field);
+    }
+}
+
+pub fn walk_impl<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, item: &ImplStatement) {
+    visitor.visit_type(exprs, &item.self_type);
+    for method in &item.methods {
+        visitor.visit_function(exprs, method);
+    }
+}
+
+pub fn walk_trait<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, item: &TraitStatement) {
+    for method in &item.methods {
+        visitor.visit_trait_method(exprs, method);
+    }
+}
+
+pub fn walk_trait_method<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, method: &TraitMethod) {
+    for param in &method.arguments {
+        visitor.visit_param(exprs, param);
+    }
+    visitor.visit_type(exprs, &method.return_type);
+    if let Some(block) = &method.block {
+        visitor.visit_block(exprs, block);
+    }
+}
+
+pub fn walk_let<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, item: &LetStatement) {
+    if let Some(var_type) = &item.var_type {
+        visitor.visit_type(exprs, var_type);
+    }
+    visitor.visit_expr(exprs, item.value);
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, expr: ExprId) {
+    match &exprs[expr] {
+        Expression::Closure(closure) => visitor.visit_closure(exprs, closure),
+        Expression::Block(block) => visitor.visit_block(exprs, block),
+        Expression::Call(call) => visitor.visit_call(exprs, call),
+        Expression::MethodCall(call) => visitor.visit_method_call(exprs, call),
+        Expression::Struct(struct_expr) => visitor.visit_struct_expr(exprs, struct_expr),
+        Expression::Field(field) => visitor.visit_field(exprs, field),
+        Expression::If(if_expr) => visitor.visit_if(exprs, if_expr),
+        Expression::Binary(binary) => visitor.visit_binary(exprs, binary),
+        Expression::Unary(unary) => visitor.visit_unary(exprs, unary),
+        Expression::Literal(literal) => visitor.visit_literal(exprs, literal),
+        Expression::Identifier(ident) => visitor.visit_ident(exprs, ident),
+    }
+}
+
+pub fn walk_closure<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, closure: &ClosureExpression) {
+    for param in &closure.arguments {
+        visitor.visit_param(exprs, param);
+    }
+    visitor.visit_type(exprs, &closure.return_type);
+    visitor.visit_block(exprs, &closure.block);
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, block: &BlockExpression) {
+    for statement in &block.statements {
+        visitor.visit_statement(exprs, statement);
+    }
+    if let Some(expr) = block.expression {
+        visitor.visit_expr(exprs, expr);
+    }
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, call: &CallExpression) {
+    visitor.visit_expr(exprs, call.function);
+    for argument in &call.arguments {
+        visitor.visit_expr(exprs, *argument);
+    }
+}
+
+pub fn walk_method_call<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, call: &MethodCallExpression) {
+    visitor.visit_expr(exprs, call.receiver);
+    for argument in &call.arguments {
+        visitor.visit_expr(exprs, *argument);
+    }
+}
+
+pub fn walk_struct_expr<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, struct_expr: &StructExpression) {
+    for field in &struct_expr.fields {
+        visitor.visit_expr(exprs, field.value);
+    }
+}
+
+pub fn walk_field<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, field: &FieldExpression) {
+    visitor.visit_expr(exprs, field.receiver);
+}
+
+pub fn walk_if<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, if_expr: &IfExpression) {
+    visitor.visit_expr(exprs, if_expr.condition);
+    visitor.visit_block(exprs, &if_expr.body);
+    if let Some(else_body) = &if_expr.else_body {
+        visitor.visit_else(exprs, else_body);
+    }
+}
+
+pub fn walk_else<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, else_expr: &ElseExpression) {
+    match else_expr {
+        ElseExpression::Else(block) => visitor.visit_block(exprs, block),
+        ElseExpression::ElseIf(if_expr) => visitor.visit_expr(exprs, *if_expr),
+    }
+}
+
+pub fn walk_binary<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, binary: &BinaryExpression) {
+    visitor.visit_expr(exprs, binary.lhs);
+    visitor.visit_expr(exprs, binary.rhs);
+}
+
+pub fn walk_unary<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, unary: &UnaryExpression) {
+    visitor.visit_expr(exprs, unary.rhs);
+}
+
+pub fn walk_literal<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, literal: &LiteralExpression) {
+    match &literal.kind {
+        LitKind::Int { ty: Some(ty), .. } | LitKind::Float { ty: Some(ty), .. } => visitor.visit_type(exprs, ty),
+        LitKind::Tuple(Tuple(elements)) | LitKind::List(List(elements)) => {
+            for element in elements {
+                visitor.visit_expr(exprs, *element);
+            }
+        }
+        LitKind::Interpolated(parts) => {
+            for part in parts {
+                if let StrPart::Expr(expr) = part {
+                    visitor.visit_expr(exprs, *expr);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+pub fn walk_param<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, param: &Parameter) {
+    visitor.visit_type(exprs, &param.param_type);
+}
+
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, exprs: &Exprs, ty: &Type) {
+    match ty {
+        Type::Tuple(TupleType(types)) => {
+            for ty in types {
+                visitor.visit_type(exprs, ty);
+            }
+        }
+        Type::List(element) => visitor.visit_type(exprs, element),
+        Type::Fn { arguments, return_type } => {
+            for argument in arguments {
+                visitor.visit_type(exprs, argument);
+            }
+            visitor.visit_type(exprs, return_type);
+        }
+        Type::Applied { arguments, .. } => {
+            for argument in arguments.iter() {
+                visitor.visit_type(exprs, argument);
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,296 @@
+// The same as `Visitor`, but for passes that change the tree as they go, like renaming what names
+// refer to. A method can also replace its node outright, an expression by putting another one in
+// its place in `exprs`.
+
+use super::*;
+
+pub trait VisitorMut {
+    fn visit_tree(&mut self, tree: &mut ASTree) {
+        walk_tree(self, tree)
+    }
+
+    fn visit_statement(&mut self, exprs: &mut Exprs, statement: &mut Statement) {
+        walk_statement(self, exprs, statement)
+    }
+
+    fn visit_function(&mut self, exprs: &mut Exprs, item: &mut FunctionStatement) {
+        walk_function(self, exprs, item)
+    }
+
+    fn visit_struct(&mut self, exprs: &mut Exprs, item: &mut StructStatement) {
+        walk_struct(self, exprs, item)
+    }
+
+    fn visit_enum(&mut self, _exprs: &mut Exprs, _item: &mut EnumStatement) {}
+
+    fn visit_impl(&mut self, exprs: &mut Exprs, item: &mut ImplStatement) {
+        walk_impl(self, exprs, item)
+    }
+
+    fn visit_trait(&mut self, exprs: &mut Exprs, item: &mut TraitStatement) {
+        walk_trait(self, exprs, item)
+    }
+
+    fn visit_trait_method(&mut self, exprs: &mut Exprs, method: &mut TraitMethod) {
+        walk_trait_method(self, exprs, method)
+    }
+
+    fn visit_import(&mut self, _exprs: &mut Exprs, _item: &mut ImportStatement) {}
+
+    fn visit_let(&mut self, exprs: &mut Exprs, item: &mut LetStatement) {
+        walk_let(self, exprs, item)
+    }
+
+    fn visit_expr(&mut self, exprs: &mut Exprs, expr: ExprId) {
+        walk_expr(self, exprs, expr)
+    }
+
+    fn visit_closure(&mut self, exprs: &mut Exprs, closure: &mut ClosureExpression) {
+        walk_closure(self, exprs, closure)
+    }
+
+    fn visit_block(&mut self, exprs: &mut Exprs, block: &mut BlockExpression) {
+        walk_block(self, exprs, block)
+    }
+
+    fn visit_call(&mut self, exprs: &mut Exprs, call: &mut CallExpression) {
+        walk_call(self, exprs, call)
+    }
+
+    fn visit_method_call(&mut self, exprs: &mut Exprs, call: &mut MethodCallExpression) {
+        walk_method_call(self, exprs, call)
+    }
+
+    fn visit_struct_expr(&mut self, exprs: &mut Exprs, struct_expr: &mut StructExpression) {
+        walk_struct_expr(self, exprs, struct_expr)
+    }
+
+    fn visit_field(&mut self, exprs: &mut Exprs, field: &mut FieldExpression) {
+        walk_field(self, exprs, field)
+    }
+
+    fn visit_if(&mut self, exprs: &mut Exprs, if_expr: &mut IfExpression) {
+        walk_if(self, exprs, if_expr)
+    }
+
+    fn visit_else(&mut self, exprs: &mut Exprs, else_expr: &mut ElseExpression) {
+        walk_else(self, exprs, else_expr)
+    }
+
+    fn visit_binary(&mut self, exprs: &mut Exprs, binary: &mut BinaryExpression) {
+        walk_binary(self, exprs, binary)
+    }
+
+    fn visit_unary(&mut self, exprs: &mut Exprs, unary: &mut UnaryExpression) {
+        walk_unary(self, exprs, unary)
+    }
+
+    fn visit_literal(&mut self, exprs: &mut Exprs, literal: &mut LiteralExpression) {
+        walk_literal(self, exprs, literal)
+    }
+
+    fn visit_ident(&mut self, _exprs: &mut Exprs, _ident: &mut IdentExpression) {}
+
+    fn visit_param(&mut self, exprs: &mut Exprs, param: &mut Parameter) {
+        walk_param(self, exprs, param)
+    }
+
+    fn visit_type(&mut self, exprs: &mut Exprs, ty: &mut Type) {
+        walk_type(self, exprs, ty)
+    }
+}
+
+pub fn walk_tree<V: VisitorMut + ?Sized>(visitor: &mut V, tree: &mut ASTree) {
+    let ASTree { root, exprs } = tree;
+    for statement in root {
+        visitor.visit_statement(exprs, statement);
+    }
+}
+
+pub fn walk_statement<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, statement: &mut Statement) {
+    match statement {
+        Statement::Function(item) => visitor.visit_function(exprs, item),
+        Statement::Struct(item) => visitor.visit_struct(exprs, item),
+        Statement::Enum(item) => visitor.visit_enum(exprs, item),
+        Statement::Impl(item) => visitor.visit_impl(exprs, item),
+        Statement::Trait(item) => visitor.visit_trait(exprs, item),
+        Statement::Import(item) => visitor.visit_import(exprs, item),
+        Statement::Let(item) => visitor.visit_let(exprs, item),
+        Statement::Expression { expr, .. } => visitor.visit_expr(exprs, *expr),
+        Statement::Error { .. } | Statement::EOF => (),
+    }
+}
+
+pub fn walk_function<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, item: &mut FunctionStatement) {
+    for param in &mut item.arguments {
+        visitor.visit_param(exprs, param);
+    }
+    visitor.visit_type(exprs, &mut item.return_type);
+    visitor.visit_block(exprs, &mut item.block);
+}
+
+pub fn walk_struct<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, item: &mut StructStatement) {
+    for field in &mut item.fields {
+        visitor.visit_param(exprs, field);
+    }
+}
+
+pub fn walk_impl<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, item: &mut ImplStatement) {
+    visitor.visit_type(exprs, &mut item.self_type);
+    for method in &mut item.methods {
+        visitor.visit_function(exprs, method);
+    }
+}
+
+pub fn walk_trait<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, item: &mut TraitStatement) {
+    for method in &mut item.methods {
+        visitor.visit_trait_method(exprs, method);
+    }
+}
+
+pub fn walk_trait_method<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, method: &mut TraitMethod) {
+    for param in &mut method.arguments {
+        visitor.visit_param(exprs, param);
+    }
+    visitor.visit_type(exprs, &mut method.return_type);
+    if let Some(block) = &mut method.block {
+        visitor.visit_block(exprs, block);
+    }
+}
+
+pub fn walk_let<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, item: &mut LetStatement) {
+    if let Some(var_type) = &mut item.var_type {
+        visitor.visit_type(exprs, var_type);
+    }
+    visitor.visit_expr(exprs, item.value);
+}
+
+// The expression is taken out of `exprs` while what's in it gets visited, and put back after.
+pub fn walk_expr<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, expr: ExprId) {
+    let mut node = exprs.take(expr);
+    match &mut node {
+        Expression::Closure(closure) => visitor.visit_closure(exprs, closure),
+        Expression::Block(block) => visitor.visit_block(exprs, block),
+        Expression::Call(call) => visitor.visit_call(exprs, call),
+        Expression::MethodCall(call) => visitor.visit_method_call(exprs, call),
+        Expression::Struct(struct_expr) => visitor.visit_struct_expr(exprs, struct_expr),
+        Expression::Field(field) => visitor.visit_field(exprs, field),
+        Expression::If(if_expr) => visitor.visit_if(exprs, if_expr),
+        Expression::Binary(binary) => visitor.visit_binary(exprs, binary),
+        Expression::Unary(unary) => visitor.visit_unary(exprs, unary),
+        Expression::Literal(literal) => visitor.visit_literal(exprs, literal),
+        Expression::Identifier(ident) => visitor.visit_ident(exprs, ident),
+    }
+    exprs.put(expr, node);
+}
+
+pub fn walk_closure<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, closure: &mut ClosureExpression) {
+    for param in &mut closure.arguments {
+        visitor.visit_param(exprs, param);
+    }
+    visitor.visit_type(exprs, &mut closure.return_type);
+    visitor.visit_block(exprs, &mut closure.block);
+}
+
+pub fn walk_block<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, block: &mut BlockExpression) {
+    for statement in &mut block.statements {
+        visitor.visit_statement(exprs, statement);
+    }
+    if let Some(expr) = block.expression {
+        visitor.visit_expr(exprs, expr);
+    }
+}
+
+pub fn walk_call<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, call: &mut CallExpression) {
+    visitor.visit_expr(exprs, call.function);
+    for argument in &mut call.arguments {
+        visitor.visit_expr(exprs, *argument);
+    }
+}
+
+pub fn walk_method_call<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, call: &mut MethodCallExpression) {
+    visitor.visit_expr(exprs, call.receiver);
+    for argument in &mut call.arguments {
+        visitor.visit_expr(exprs, *argument);
+    }
+}
+
+pub fn walk_struct_expr<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, struct_expr: &mut StructExpression) {
+    for field in &mut struct_expr.fields {
+        visitor.visit_expr(exprs, field.value);
+    }
+}
+
+pub fn walk_field<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, field: &mut FieldExpression) {
+    visitor.visit_expr(exprs, field.receiver);
+}
+
+pub fn walk_if<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, if_expr: &mut IfExpression) {
+    visitor.visit_expr(exprs, if_expr.condition);
+    visitor.visit_block(exprs, &mut if_expr.body);
+    if let Some(else_body) = &mut if_expr.else_body {
+        visitor.visit_else(exprs, else_body);
+    }
+}
+
+pub fn walk_else<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, else_expr: &mut ElseExpression) {
+    match else_expr {
+        ElseExpression::Else(block) => visitor.visit_block(exprs, block),
+        ElseExpression::ElseIf(if_expr) => visitor.visit_expr(exprs, *if_expr),
+    }
+}
+
+pub fn walk_binary<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, binary: &mut BinaryExpression) {
+    visitor.visit_expr(exprs, binary.lhs);
+    visitor.visit_expr(exprs, binary.rhs);
+}
+
+pub fn walk_unary<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, unary: &mut UnaryExpression) {
+    visitor.visit_expr(exprs, unary.rhs);
+}
+
+pub fn walk_literal<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, literal: &mut LiteralExpression) {
+    match &mut literal.kind {
+        LitKind::Int { ty: Some(ty), .. } | LitKind::Float { ty: Some(ty), .. } => visitor.visit_type(exprs, ty),
+        LitKind::Tuple(Tuple(elements)) | LitKind::List(List(elements)) => {
+            for element in elements {
+                visitor.visit_expr(exprs, *element);
+            }
+        }
+        LitKind::Interpolated(parts) => {
+            for part in parts {
+                if let StrPart::Expr(expr) = part {
+                    visitor.visit_expr(exprs, *expr);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+pub fn walk_param<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, param: &mut Parameter) {
+    visitor.visit_type(exprs, &mut param.param_type);
+}
+
+pub fn walk_type<V: VisitorMut + ?Sized>(visitor: &mut V, exprs: &mut Exprs, ty: &mut Type) {
+    match ty {
+        Type::Tuple(TupleType(types)) => {
+            for ty in types {
+                visitor.visit_type(exprs, ty);
+            }
+        }
+        Type::List(element) => visitor.visit_type(exprs, element),
+        Type::Fn { arguments, return_type } => {
+            for argument in arguments {
+                visitor.visit_type(exprs, argument);
+            }
+            visitor.visit_type(exprs, return_type);
+        }
+        Type::Applied { arguments, .. } => {
+            for argument in arguments.iter_mut() {
+                visitor.visit_type(exprs, argument);
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,271 @@
+// How Rust values and functions map onto Alisa ones. Everything here is implemented for the types
+// that have an obvious counterpart, so host functions can be plain closures like
+// `|name: String, times: i64| name.repeat(times as usize)`.
+
+use crate::ast::{Type, IntKind, FloatKind, TupleType};
+use crate::eval::{EvalResult, RuntimeError};
+use crate::eval::value::Value;
+
+// A Rust type that has an Alisa type, and can be turned into and back from an Alisa value.
+pub trait HostValue: Sized {
+    fn ty() -> Type;
+    fn into_value(self) -> Value;
+    // An overflow if the value doesn't fit in `Self`, and invalid if it isn't of `ty()` at all.
+    fn from_value(value: Value) -> EvalResult<Self>;
+}
+
+macro_rules! int {
+    ($($t:ty => $sign:expr, $kind:ident;)*) => {$(
+        impl HostValue for $t {
+            fn ty() -> Type {
+                Type::Int { sign: $sign, kind: IntKind::$kind }
+            }
+
+            fn into_value(self) -> Value {
+                Value::Int(self.into())
+            }
+
+            fn from_value(value: Value) -> EvalResult<Self> {
+                match value {
+                    Value::Int(n) => n.try_into().map_err(|_| RuntimeError::Overflow),
+                    _ => Err(RuntimeError::Invalid),
+                }
+            }
+        }
+    )*};
+}
+
+int! {
+    i8 => true, Bit8;
+    i16 => true, Bit16;
+    i32 => true, Bit32;
+    i64 => true, Bit64;
+    u8 => false, Bit8;
+    u16 => false, Bit16;
+    u32 => false, Bit32;
+    u64 => false, Bit64;
+}
+
+impl HostValue for f64 {
+    fn ty() -> Type {
+        Type::Float { kind: FloatKind::Bit64 }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Float(self)
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::Float(x) => Ok(x),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+impl HostValue for f32 {
+    fn ty() -> Type {
+        Type::Float { kind: FloatKind::Bit32 }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Float(self.into())
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::Float(x) => Ok(x as f32),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+impl HostValue for bool {
+    fn ty() -> Type {
+        Type::Bool
+    }
+
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+impl HostValue for char {
+    fn ty() -> Type {
+        Type::Char
+    }
+
+    fn into_value(self) -> Value {
+        Value::Char(self)
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::Char(c) => Ok(c),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+impl HostValue for String {
+    fn ty() -> Type {
+        Type::Str
+    }
+
+    fn into_value(self) -> Value {
+        Value::Str(self)
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::Str(s) => Ok(s),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+impl HostValue for () {
+    fn ty() -> Type {
+        Type::Void
+    }
+
+    fn into_value(self) -> Value {
+        Value::Void
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::Void => Ok(()),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+impl<T: HostValue> HostValue for Vec<T> {
+    fn ty() -> Type {
+        Type::List(Box::new(T::ty()))
+    }
+
+    fn into_value(self) -> Value {
+        Value::List(self.into_iter().map(T::into_value).collect())
+    }
+
+    fn from_value(value: Value) -> EvalResult<Self> {
+        match value {
+            Value::List(values) => values.into_iter().map(T::from_value).collect(),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+}
+
+// The arguments a Rust caller passes to an Alisa function, as a tuple of `HostValue`s.
+pub trait HostArgs {
+    fn types() -> Vec<Type>;
+    fn into_values(self) -> Vec<Value>;
+}
+
+// What a host function can return. Returning an `Err` stops the program with a runtime error.
+pub trait HostResult {
+    type Ok: HostValue;
+    fn into_result(self) -> Result<Value, String>;
+}
+
+impl<T: HostValue> HostResult for T {
+    type Ok = T;
+
+    fn into_result(self) -> Result<Value, String> {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: HostValue> HostResult for Result<T, String> {
+    type Ok = T;
+
+    fn into_result(self) -> Result<Value, String> {
+        self.map(T::into_value)
+    }
+}
+
+// A Rust closure that can be called from Alisa. `Args` is the tuple of its argument types, which
+// is only there so closures of every arity can implement it.
+pub trait HostFunction<Args>: 'static {
+    fn signature() -> (Vec<Type>, Type);
+    fn call(&self, name: &str, arguments: Vec<Value>) -> EvalResult<Value>;
+}
+
+macro_rules! tuple {
+    ($($arg:ident),*) => {
+        impl<$($arg: HostValue),*> HostArgs for ($($arg,)*) {
+            fn types() -> Vec<Type> {
+                vec![$($arg::ty()),*]
+            }
+
+            #[allow(non_snake_case)]
+            fn into_values(self) -> Vec<Value> {
+                let ($($arg,)*) = self;
+                vec![$($arg.into_value()),*]
+            }
+        }
+
+        impl<F, R, $($arg),*> HostFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: HostResult,
+            $($arg: HostValue,)*
+        {
+            fn signature() -> (Vec<Type>, Type) {
+                (vec![$($arg::ty()),*], R::Ok::ty())
+            }
+
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(&self, name: &str, arguments: Vec<Value>) -> EvalResult<Value> {
+                // The type checker has already made sure the arguments fit.
+                let mut arguments = arguments.into_iter();
+                $(let $arg = $arg::from_value(arguments.next().ok_or(RuntimeError::Invalid)?)?;)*
+                self($($arg),*).into_result().map_err(|message| RuntimeError::Native { function: name.into(), message })
+            }
+        }
+    };
+}
+
+tuple!();
+tuple!(A);
+tuple!(A, B);
+tuple!(A, B, C);
+tuple!(A, B, C, D);
+
+// Tuples of values are values too, except for `()` which is `void`.
+macro_rules! tuple_value {
+    ($($arg:ident),*) => {
+        impl<$($arg: HostValue),*> HostValue for ($($arg,)*) {
+            fn ty() -> Type {
+                Type::Tuple(TupleType(vec![$($arg::ty()),*]))
+            }
+
+            #[allow(non_snake_case)]
+            fn into_value(self) -> Value {
+                let ($($arg,)*) = self;
+                Value::Tuple(vec![$($arg.into_value()),*])
+            }
+
+            #[allow(non_snake_case)]
+            fn from_value(value: Value) -> EvalResult<Self> {
+                let Value::Tuple(values) = value else { return Err(RuntimeError::Invalid) };
+                let mut values = values.into_iter();
+                $(let $arg = $arg::from_value(values.next().ok_or(RuntimeError::Invalid)?)?;)*
+                values.next().is_none().then_some(($($arg,)*)).ok_or(RuntimeError::Invalid)
+            }
+        }
+    };
+}
+
+tuple_value!(A, B);
+tuple_value!(A, B, C);
+tuple_value!(A, B, C, D);
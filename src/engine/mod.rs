@@ -0,0 +1,133 @@
+// Running Alisa from Rust. An `Engine` keeps everything the programs loaded into it declare, so
+// source can be loaded bit by bit, and Rust code can call into it with plain Rust values. Rust
+// functions and values can be handed to the programs the same way, as long as they're registered
+// before the source that uses them is loaded.
+
+mod host;
+
+use std::rc::Rc;
+
+pub use host::{HostValue, HostArgs, HostResult, HostFunction};
+
+use crate::ast::Type;
+use crate::eval::{Interpreter, RuntimeError};
+use crate::parse::{Parser, ParseError};
+use crate::prelude::NativeFunction;
+use crate::typeck::{TypeChecker, TypeError};
+
+pub struct Engine {
+    checker: TypeChecker,
+    interpreter: Interpreter,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self { checker: TypeChecker::new(), interpreter: Interpreter::new() }
+    }
+
+    // Makes `function` callable as `name`, with the Alisa types of its arguments and result.
+    pub fn register<Args, F: HostFunction<Args>>(&mut self, name: &str, function: F) {
+        let (arguments, return_type) = F::signature();
+        let function_name = name.to_string();
+        let native = NativeFunction::new(name, &[], arguments, return_type, move |_, arguments| {
+            function.call(&function_name, arguments)
+        });
+        self.checker.declare_native(&native);
+        self.interpreter.declare_native(Rc::new(native));
+    }
+
+    // Makes `value` available as a top-level variable.
+    pub fn define<T: HostValue>(&mut self, name: &str, value: T) {
+        self.checker.declare_global(name, T::ty());
+        self.interpreter.declare_global(name, value.into_value());
+    }
+
+    // Type checks and runs `source`. Nothing it declares is kept if it doesn't type check, or if it
+    // fails while it runs.
+    pub fn load(&mut self, source: &str) -> Result<(), EngineError> {
+        let (mut tree, errors) = Parser::parse(source);
+        if !errors.is_empty() {
+            return Err(EngineError::Parse(errors));
+        }
+
+        let snapshot = self.checker.clone();
+        if let Err(err) = self.checker.check_tree(&mut tree) {
+            self.checker = snapshot;
+            return Err(err.into());
+        }
+        let declared = self.interpreter.snapshot();
+        if let Err(err) = self.interpreter.eval_tree(&tree) {
+            self.checker = snapshot;
+            self.interpreter.restore(declared);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    // Calls the function called `name`, which has to take exactly the types of `arguments` and
+    // return `R`.
+    pub fn call<R: HostValue, A: HostArgs>(&mut self, name: &str, arguments: A) -> Result<R, EngineError> {
+        let Some(binding) = self.checker.function(name) else {
+            return Err(EngineError::UnknownFunction { name: name.into() });
+        };
+        let expected = Type::Fn { arguments: A::types(), return_type: Box::new(R::ty()) };
+        if !binding.generics.is_empty() || binding.ty != expected {
+            return Err(EngineError::Signature { name: name.into(), expected, found: binding.ty.clone() });
+        }
+
+        let function = self.interpreter.global(name).ok_or(RuntimeError::Invalid)?;
+        let value = self.interpreter.call(&function, arguments.into_values())?;
+        Ok(R::from_value(value)?)
+    }
+}
+
+#[derive(Debug)]
+pub enum EngineError {
+    Parse(Vec<ParseError>),
+    Type(TypeError),
+    Runtime(RuntimeError),
+    UnknownFunction { name: String },
+    // A function called from Rust with a different type than it was declared with.
+    Signature { name: String, expected: Type, found: Type },
+}
+
+impl From<TypeError> for EngineError {
+    fn from(err: TypeError) -> Self {
+        EngineError::Type(err)
+    }
+}
+
+impl From<RuntimeError> for EngineError {
+    fn from(err: RuntimeError) -> Self {
+        EngineError::Runtime(err)
+    }
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::Parse(errors) => {
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 { writeln!(f)?; }
+                    write!(f, "{err}")?;
+                }
+                Ok(())
+            }
+            EngineError::Type(err) => write!(f, "{err}"),
+            EngineError::Runtime(err) => write!(f, "{err}"),
+            EngineError::UnknownFunction { name } => write!(f, "cannot find function `{name}`"),
+            EngineError::Signature { name, expected, found } => {
+                write!(f, "`{name}` has type `{found}`, it cannot be called as `{expected}`")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use super::*;
+use crate::eval::value::Value;
+
+#[test]
+fn calling_alisa_from_rust() {
+    let mut engine = Engine::new();
+    engine.load("
+        fn add(a: i32, b: i32) -> i32 { a + b }
+        fn greet(name: str) -> str { str::concat(name, name) }
+        fn stats(values: {f64}) -> (f64, i64) {
+            (list::fold(values, 0.0, \\(acc: f64, x: f64) -> f64 { acc + x }), list::len(values))
+        }
+    ").unwrap();
+
+    assert_eq!(engine.call::<i32, _>("add", (2, 3)).unwrap(), 5);
+    assert_eq!(engine.call::<String, _>("greet", ("ab".to_string(),)).unwrap(), "abab");
+    assert_eq!(engine.call::<(f64, i64), _>("stats", (vec![1.5, 2.5],)).unwrap(), (4.0, 2));
+
+    // Whatever is loaded later can use what was loaded before.
+    engine.load("fn add3(a: i32) -> i32 { add(a, 3) }").unwrap();
+    assert_eq!(engine.call::<i32, _>("add3", (1,)).unwrap(), 4);
+
+    // A default engine is a new one, prelude and all.
+    let mut engine = Engine::default();
+    engine.define("X", 2i32);
+    engine.load("fn count() -> i64 { list::len([X, X]) }").unwrap();
+    assert_eq!(engine.call::<i64, _>("count", ()).unwrap(), 2);
+}
+
+#[test]
+fn calling_rust_from_alisa() {
+    let mut engine = Engine::new();
+    engine.register("repeat", |s: String, times: i64| s.repeat(times as usize));
+    engine.register("sum", |values: Vec<u8>| values.iter().map(|&n| u32::from(n)).sum::<u32>());
+    engine.register("swap", |(a, b): (bool, char)| (b, a));
+    engine.register("half", |n: i32| match n % 2 {
+        0 => Ok(n / 2),
+        _ => Err(format!("{n} is odd")),
+    });
+    engine.define("LIMIT", 10i64);
+    engine.define("Z", 'z');
+    engine.define("NAMES", vec!["a".to_string(), "b".to_string()]);
+
+    engine.load("
+        fn run(s: str) -> (str, u32, (char, bool), i32) {
+            let l: {u8} = [1, 2, 3];
+            (repeat(s, LIMIT / 5), sum(l), swap((true, Z)), half(4))
+        }
+        fn names() -> i64 { list::len(NAMES) }
+        fn odd() -> i32 { half(3) }
+    ").unwrap_or_else(|err| panic!("{err}"));
+
+    let (repeated, sum, swapped, half) = engine.call::<(String, u32, (char, bool), i32), _>("run", ("x".to_string(),)).unwrap();
+    assert_eq!((repeated.as_str(), sum, swapped, half), ("xx", 6, ('z', true), 2));
+    assert_eq!(engine.call::<i64, _>("names", ()).unwrap(), 2);
+
+    let Err(EngineError::Runtime(RuntimeError::Native { function, message })) = engine.call::<i32, _>("odd", ()) else {
+        panic!()
+    };
+    assert_eq!((function.as_str(), message.as_str()), ("half", "3 is odd"));
+}
+
+#[test]
+fn errors() {
+    let mut engine = Engine::new();
+    engine.load("fn id(b: bool) -> bool { b } fn pop(l: {i32}) -> ({i32}, i32) { list::pop(l) }").unwrap();
+
+    assert!(matches!(engine.load("fn f( -> {}"), Err(EngineError::Parse(_))));
+    assert!(matches!(engine.load("fn g() -> i32 { true }"), Err(EngineError::Type(_))));
+    assert!(matches!(engine.call::<i32, _>("g", ()), Err(EngineError::UnknownFunction { .. })));
+
+    let Err(err) = engine.call::<i32, _>("id", (true,)) else { panic!() };
+    assert_eq!(err.to_string(), "`id` has type `fn(bool) -> bool`, it cannot be called as `fn(bool) -> i32`");
+    assert!(matches!(engine.call::<bool, _>("id", ()), Err(EngineError::Signature { .. })));
+
+    let err = engine.call::<(Vec<i32>, i32), _>("pop", (Vec::<i32>::new(),)).unwrap_err();
+    assert_eq!(err.to_string(), "list::pop: the list is empty");
+
+    engine.load("fn inc(n: u8) -> u8 { n + 1 }").unwrap();
+    assert_eq!(engine.call::<u8, _>("inc", (254u8,)).unwrap(), 255);
+    assert!(matches!(engine.call::<u8, _>("inc", (255u8,)), Err(EngineError::Runtime(RuntimeError::Overflow))));
+    assert!(matches!(u8::from_value(Value::Int(256)), Err(RuntimeError::Overflow)));
+
+    // Nothing from source that fails while it runs is remembered either.
+    assert!(matches!(engine.load("fn h() -> i32 { 1 } let z = 1 / 0;"), Err(EngineError::Runtime(_))));
+    assert!(matches!(engine.call::<i32, _>("h", ()), Err(EngineError::UnknownFunction { .. })));
+    assert!(matches!(engine.load("fn k() -> i32 { z }"), Err(EngineError::Type(_))));
+
+    // Including what it redefines, which goes back to what it was before.
+    engine.load("fn f() -> i32 { 1 }").unwrap();
+    assert!(matches!(engine.load("fn f() -> bool { true } let w = 1 / 0;"), Err(EngineError::Runtime(_))));
+    assert_eq!(engine.call::<i32, _>("f", ()).unwrap(), 1);
+}
@@ -0,0 +1,616 @@
+// A tree-walking interpreter. It expects programs to have gone through the type checker first, so
+// anything that should have been caught there only gets reported as a `RuntimeError::Invalid`.
+
+pub mod value;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use value::{Value, Closure};
+
+use crate::ast::{ASTree, Statement, Expression, ExprId, Exprs, Symbol, Type, FloatKind};
+use crate::ast::{LetStatement, FunctionStatement, ImplStatement, TraitStatement};
+use crate::ast::{BlockExpression, CallExpression, MethodCallExpression, IfExpression, ElseExpression};
+use crate::ast::StructExpression;
+use crate::ast::visit_mut::{VisitorMut, walk_method_call};
+use crate::ast::{BinaryExpression, BinaryOperator, UnaryExpression, UnaryOperator};
+use crate::ast::{LiteralExpression, LitKind, StrPart, Tuple, List};
+use crate::prelude::{self, NativeFunction};
+
+#[derive(Debug)]
+pub struct Interpreter {
+    globals: HashMap<Symbol, Value>,
+    // Scopes of whatever function is being run, innermost scope last. Empty at the top level.
+    locals: Vec<HashMap<Symbol, Value>>,
+    functions: HashMap<Symbol, Rc<FunctionStatement>>,
+    // The names of every struct's fields, in the order they're declared in.
+    structs: HashMap<Symbol, Rc<[Symbol]>>,
+    traits: HashMap<Symbol, TraitStatement>,
+    // Methods by the type of the `impl` they're in, then by their own name.
+    methods: HashMap<Type, HashMap<Symbol, Rc<FunctionStatement>>>,
+    natives: HashMap<Symbol, Rc<NativeFunction>>,
+    // The expressions of every tree run so far, which what's been declared keeps referring to. It's
+    // shared with whatever is being evaluated, so that it can look at them while `self` changes.
+    exprs: Rc<Exprs>,
+    pub output: Output,
+}
+
+// Everything the programs run so far have declared, to go back to when one fails partway through.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    globals: HashMap<Symbol, Value>,
+    functions: HashMap<Symbol, Rc<FunctionStatement>>,
+    structs: HashMap<Symbol, Rc<[Symbol]>>,
+    traits: HashMap<Symbol, TraitStatement>,
+    methods: HashMap<Type, HashMap<Symbol, Rc<FunctionStatement>>>,
+    exprs: usize,
+}
+
+// Where `print` and `println` write to.
+#[derive(Debug, Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    Captured(String),
+}
+
+impl Output {
+    pub fn write(&mut self, text: &str) {
+        match self {
+            Output::Stdout => print!("{text}"),
+            Output::Captured(captured) => captured.push_str(text),
+        }
+    }
+
+    // Everything captured since the last time, which is nothing when writing to stdout.
+    pub fn take(&mut self) -> String {
+        match self {
+            Output::Stdout => String::new(),
+            Output::Captured(captured) => std::mem::take(captured),
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let mut interpreter = Self {
+            globals: HashMap::new(),
+            locals: Vec::new(),
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            traits: HashMap::new(),
+            methods: HashMap::new(),
+            natives: HashMap::new(),
+            exprs: Rc::default(),
+            output: Output::Stdout,
+        };
+        for function in prelude::functions() {
+            interpreter.declare_native(function);
+        }
+        interpreter
+    }
+
+    pub fn declare_native(&mut self, function: Rc<NativeFunction>) {
+        self.natives.insert(function.name, function);
+    }
+
+    pub fn declare_global(&mut self, name: &str, value: Value) {
+        self.globals.insert(Symbol::intern(name), value);
+    }
+
+    // A top-level variable or function, or a native one.
+    pub fn global(&self, name: &str) -> Option<Value> {
+        self.lookup(Symbol::intern(name)).ok()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            globals: self.globals.clone(),
+            functions: self.functions.clone(),
+            structs: self.structs.clone(),
+            traits: self.traits.clone(),
+            methods: self.methods.clone(),
+            exprs: self.exprs.len(),
+        }
+    }
+
+    // Forgets whatever was declared after `snapshot` was taken.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        let Snapshot { globals, functions, structs, traits, methods, exprs } = snapshot;
+        self.globals = globals;
+        self.functions = functions;
+        self.structs = structs;
+        self.traits = traits;
+        self.methods = methods;
+        Rc::make_mut(&mut self.exprs).truncate(exprs);
+    }
+
+    // Runs every statement in the tree and returns the value of the last one.
+    pub fn eval_tree(&mut self, tree: &ASTree) -> EvalResult<Value> {
+        let mut statements = tree.root.clone();
+        Rc::make_mut(&mut self.exprs).append(&tree.exprs, &mut statements);
+        self.eval_statements(&statements)
+    }
+
+    fn eval_statements(&mut self, statements: &[Statement]) -> EvalResult<Value> {
+        // Same as in the type checker, functions can be called before they're declared.
+        for statement in statements {
+            match statement {
+                // Only the top level can have functions, the type checker doesn't allow them in blocks.
+                Statement::Function(_) if !self.locals.is_empty() => return Err(RuntimeError::Invalid),
+                Statement::Function(function) => {
+                    self.functions.insert(function.name, Rc::new(function.clone()));
+                }
+                Statement::Struct(item) => {
+                    self.structs.insert(item.name, item.fields.iter().map(|field| field.name).collect());
+                }
+                Statement::Enum(item) => {
+                    for variant in &item.variants {
+                        let name = Symbol::intern(&format!("{}::{variant}", item.name));
+                        self.globals.insert(name, Value::Variant(name));
+                    }
+                }
+                Statement::Trait(item) => {
+                    self.traits.insert(item.name, item.clone());
+                }
+                _ => (),
+            }
+        }
+        for statement in statements {
+            if let Statement::Impl(item) = statement {
+                self.declare_impl(item)?;
+            }
+        }
+
+        let mut last = Value::Void;
+        for statement in statements {
+            last = self.eval_statement(statement)?;
+        }
+        Ok(last)
+    }
+
+    fn eval_statement(&mut self, statement: &Statement) -> EvalResult<Value> {
+        match statement {
+            Statement::Let(LetStatement { name, value, .. }) => {
+                let value = self.eval_expr(*value)?;
+                self.declare_variable(*name, value);
+                Ok(Value::Void)
+            }
+
+            Statement::Expression { expr, .. } => self.eval_expr(*expr),
+
+            // Declarations were already taken care of in eval_statements().
+            Statement::Function(_)
+            | Statement::Struct(_)
+            | Statement::Enum(_)
+            | Statement::Impl(_)
+            | Statement::Trait(_)
+            | Statement::Import(_)
+            | Statement::Error { .. }
+            | Statement::EOF => Ok(Value::Void),
+        }
+    }
+
+    fn declare_impl(&mut self, item: &ImplStatement) -> EvalResult<()> {
+        let methods = self.methods.entry(item.self_type.clone()).or_default();
+        let mut declare = |method: FunctionStatement| {
+            methods.insert(method.name, Rc::new(method));
+        };
+
+        // The trait's default methods go first so that the ones in the `impl` replace them.
+        if let Some(trait_name) = &item.trait_name {
+            let Some(trait_item) = self.traits.get(trait_name) else { return Err(RuntimeError::Invalid) };
+            let exprs = Rc::make_mut(&mut self.exprs);
+            let mut defaults: Vec<FunctionStatement> = trait_item.methods
+                .iter()
+                .filter_map(|method| Some(FunctionStatement {
+                    public: false,
+                    name: method.name,
+                    generics: method.generics.clone(),
+                    arguments: method.arguments.clone(),
+                    return_type: method.return_type.clone(),
+                    // Each `impl` gets its own copy, since what `Self` is differs between them.
+                    block: exprs.copy_block(method.block.as_ref()?),
+                    name_span: method.name_span,
+                    span: method.span,
+                }))
+                .collect();
+
+            // Methods called on `self` in a default method are from this `impl`.
+            let mut resolve = ResolveSelf(&item.self_type);
+            defaults.iter_mut().for_each(|method| resolve.visit_function(exprs, method));
+            defaults.into_iter().for_each(&mut declare);
+        }
+        item.methods.iter().cloned().for_each(declare);
+        Ok(())
+    }
+
+    fn eval_expr(&mut self, expr: ExprId) -> EvalResult<Value> {
+        let exprs = Rc::clone(&self.exprs);
+        match &exprs[expr] {
+            Expression::Literal(literal) => self.eval_literal(literal),
+
+            Expression::Identifier(ident) => self.lookup(ident.name),
+
+            Expression::Block(block) => self.eval_block(block),
+
+            Expression::If(if_expr) => self.eval_if(if_expr),
+
+            Expression::Closure(closure) => {
+                let mut captured = HashMap::new();
+                for scope in &self.locals {
+                    captured.extend(scope.iter().map(|(&name, value)| (name, value.clone())));
+                }
+
+                let closure = Closure {
+                    arguments: closure.arguments.iter().map(|argument| argument.name).collect(),
+                    block: closure.block.clone(),
+                    captured,
+                };
+                Ok(Value::Closure(Rc::new(closure)))
+            }
+
+            Expression::Call(call) => self.eval_call(call),
+
+            Expression::MethodCall(call) => {
+                let receiver = self.eval_expr(call.receiver)?;
+                let method = call.self_type
+                    .as_ref()
+                    .and_then(|self_type| self.methods.get(self_type)?.get(&call.method).cloned());
+                let Some(method) = method else { return Err(RuntimeError::Invalid) };
+
+                let mut arguments = vec![receiver];
+                arguments.extend(self.eval_all(&call.arguments)?);
+                self.call(&Value::Function(method), arguments)
+            }
+
+            Expression::Struct(struct_expr) => self.eval_struct(struct_expr),
+
+            Expression::Field(field) => match self.eval_expr(field.receiver)? {
+                Value::Struct { fields, .. } => fields
+                    .into_iter()
+                    .find_map(|(name, value)| (name == field.field).then_some(value))
+                    .ok_or(RuntimeError::Invalid),
+                _ => Err(RuntimeError::Invalid),
+            },
+
+            Expression::Unary(unary) => self.eval_unary(unary),
+
+            Expression::Binary(binary) => self.eval_binary(binary),
+        }
+    }
+
+    fn eval_literal(&mut self, literal: &LiteralExpression) -> EvalResult<Value> {
+        let value = match &literal.kind {
+            LitKind::Bool(value) => Value::Bool(*value),
+            LitKind::Int { value, .. } => match i128::try_from(*value) {
+                Ok(value) => Value::Int(value),
+                Err(_) => return Err(RuntimeError::Overflow),
+            },
+            LitKind::Float { value, .. } => Value::Float(*value),
+            LitKind::Str(value) => Value::Str(value.clone()),
+            LitKind::Interpolated(parts) => {
+                let mut value = String::new();
+                for part in parts {
+                    match part {
+                        StrPart::Text(text) => value.push_str(text),
+                        // Embedded values show up the same as they do with `str::from`.
+                        StrPart::Expr(expr) => value.push_str(&prelude::text(&self.eval_expr(*expr)?)),
+                    }
+                }
+                Value::Str(value)
+            }
+            LitKind::Char(value) => Value::Char(*value),
+            LitKind::Tuple(Tuple(expressions)) => Value::Tuple(self.eval_all(expressions)?),
+            LitKind::List(List(expressions)) => Value::List(self.eval_all(expressions)?),
+        };
+        Ok(value)
+    }
+
+    // The fields are evaluated in the order they're written in, then put in the order the struct
+    // declares them in.
+    fn eval_struct(&mut self, struct_expr: &StructExpression) -> EvalResult<Value> {
+        let Some(names) = self.structs.get(&struct_expr.name).cloned() else { return Err(RuntimeError::Invalid) };
+        let mut given = Vec::new();
+        for field in &struct_expr.fields {
+            given.push((field.name, self.eval_expr(field.value)?));
+        }
+
+        let mut fields = Vec::new();
+        for name in names.iter() {
+            let Some(i) = given.iter().position(|(given, _)| given == name) else { return Err(RuntimeError::Invalid) };
+            fields.push(given.swap_remove(i));
+        }
+        Ok(Value::Struct { name: struct_expr.name, fields })
+    }
+
+    fn eval_all(&mut self, expressions: &[ExprId]) -> EvalResult<Vec<Value>> {
+        expressions.iter().map(|&expr| self.eval_expr(expr)).collect()
+    }
+
+    fn eval_block(&mut self, block: &BlockExpression) -> EvalResult<Value> {
+        self.locals.push(HashMap::new());
+        let result = self.eval_block_inner(block);
+        self.locals.pop();
+        result
+    }
+
+    fn eval_block_inner(&mut self, block: &BlockExpression) -> EvalResult<Value> {
+        self.eval_statements(&block.statements)?;
+        match block.expression {
+            Some(expr) => self.eval_expr(expr),
+            None => Ok(Value::Void),
+        }
+    }
+
+    fn eval_if(&mut self, if_expr: &IfExpression) -> EvalResult<Value> {
+        let condition = match self.eval_expr(if_expr.condition)? {
+            Value::Bool(condition) => condition,
+            _ => return Err(RuntimeError::Invalid),
+        };
+
+        if condition {
+            return self.eval_block(&if_expr.body);
+        }
+
+        match &if_expr.else_body {
+            Some(ElseExpression::Else(block)) => self.eval_block(block),
+            Some(ElseExpression::ElseIf(else_if)) => self.eval_expr(*else_if),
+            None => Ok(Value::Void),
+        }
+    }
+
+    fn eval_call(&mut self, call: &CallExpression) -> EvalResult<Value> {
+        let function = self.eval_expr(call.function)?;
+        let arguments = self.eval_all(&call.arguments)?;
+        self.call(&function, arguments)
+    }
+
+    pub fn call(&mut self, function: &Value, arguments: Vec<Value>) -> EvalResult<Value> {
+        let (names, block, mut scope): (Vec<Symbol>, _, _) = match function {
+            Value::Function(function) => {
+                let names = function.arguments.iter().map(|argument| argument.name).collect();
+                (names, &function.block, HashMap::new())
+            }
+            Value::Closure(closure) => (closure.arguments.clone(), &closure.block, closure.captured.clone()),
+            Value::Native(function) => return (function.function)(self, arguments),
+            _ => return Err(RuntimeError::Invalid),
+        };
+
+        if names.len() != arguments.len() {
+            return Err(RuntimeError::Invalid);
+        }
+        for (name, value) in names.into_iter().zip(arguments) {
+            scope.insert(name, value);
+        }
+
+        // The function only gets to see its own scope and the globals.
+        let outer = std::mem::replace(&mut self.locals, vec![scope]);
+        let result = self.eval_block(block);
+        self.locals = outer;
+        result
+    }
+
+    fn eval_unary(&mut self, unary: &UnaryExpression) -> EvalResult<Value> {
+        let value = self.eval_expr(unary.rhs)?;
+        let value = match (unary.op, value) {
+            (UnaryOperator::BoolNot, Value::Bool(value)) => Value::Bool(!value),
+            (UnaryOperator::BitNot, Value::Int(value)) => Value::Int(IntType::of(&unary.ty)?.wrap(!value)),
+            (UnaryOperator::Plus, value @ (Value::Int(_) | Value::Float(_))) => value,
+            (UnaryOperator::Minus, Value::Int(value)) => IntType::of(&unary.ty)?.fit(value.checked_neg())?,
+            (UnaryOperator::Minus, Value::Float(value)) => Value::Float(prelude::narrow(float_kind(&unary.ty)?, -value)),
+            _ => return Err(RuntimeError::Invalid),
+        };
+        Ok(value)
+    }
+
+    fn eval_binary(&mut self, binary: &BinaryExpression) -> EvalResult<Value> {
+        use BinaryOperator as Op;
+
+        if binary.op == Op::Pipe {
+            return self.eval_call(&binary.desugar_pipe(&self.exprs));
+        }
+
+        // These two short-circuit, so the right-hand side can't be evaluated up front.
+        if matches!(binary.op, Op::BoolOr | Op::BoolAnd) {
+            let Value::Bool(lhs) = self.eval_expr(binary.lhs)? else { return Err(RuntimeError::Invalid) };
+            if lhs == (binary.op == Op::BoolOr) {
+                return Ok(Value::Bool(lhs));
+            }
+            return match self.eval_expr(binary.rhs)? {
+                Value::Bool(rhs) => Ok(Value::Bool(rhs)),
+                _ => Err(RuntimeError::Invalid),
+            };
+        }
+
+        let lhs = self.eval_expr(binary.lhs)?;
+        let rhs = self.eval_expr(binary.rhs)?;
+
+        match binary.op {
+            Op::Eq => return Ok(Value::Bool(lhs == rhs)),
+            Op::Ne => return Ok(Value::Bool(lhs != rhs)),
+            Op::Ge | Op::Le | Op::Gt | Op::Lt => return compare(binary.op, &lhs, &rhs),
+            _ => (),
+        }
+
+        match (lhs, rhs) {
+            (Value::Int(lhs), Value::Int(rhs)) => int_operation(binary.op, lhs, rhs, IntType::of(&binary.ty)?),
+            (Value::Float(lhs), Value::Float(rhs)) => float_operation(binary.op, lhs, rhs, float_kind(&binary.ty)?),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+
+    fn lookup(&self, name: Symbol) -> EvalResult<Value> {
+        let variable = self.locals.iter().rev().find_map(|scope| scope.get(&name));
+        if let Some(value) = variable.or_else(|| self.globals.get(&name)) {
+            return Ok(value.clone());
+        }
+
+        if let Some(function) = self.functions.get(&name) {
+            return Ok(Value::Function(function.clone()));
+        }
+        match self.natives.get(&name) {
+            Some(function) => Ok(Value::Native(function.clone())),
+            None => Err(RuntimeError::Invalid),
+        }
+    }
+
+    fn declare_variable(&mut self, name: Symbol, value: Value) {
+        match self.locals.last_mut() {
+            Some(scope) => scope.insert(name, value),
+            None => self.globals.insert(name, value),
+        };
+    }
+}
+
+// Fills in which `impl` the method calls on `Self` in a trait's default method go to.
+struct ResolveSelf<'a>(&'a Type);
+
+impl VisitorMut for ResolveSelf<'_> {
+    fn visit_method_call(&mut self, exprs: &mut Exprs, call: &mut MethodCallExpression) {
+        if let Some(Type::Generic { name }) = &call.self_type {
+            if name == "Self" {
+                call.self_type = Some(self.0.clone());
+            }
+        }
+        walk_method_call(self, exprs, call)
+    }
+}
+
+fn compare(op: BinaryOperator, lhs: &Value, rhs: &Value) -> EvalResult<Value> {
+    let ordering = match (lhs, rhs) {
+        (Value::Int(lhs), Value::Int(rhs)) => lhs.partial_cmp(rhs),
+        (Value::Float(lhs), Value::Float(rhs)) => lhs.partial_cmp(rhs),
+        (Value::Char(lhs), Value::Char(rhs)) => lhs.partial_cmp(rhs),
+        (Value::Str(lhs), Value::Str(rhs)) => lhs.partial_cmp(rhs),
+        _ => return Err(RuntimeError::Invalid),
+    };
+
+    // Comparisons with NaN are always false.
+    let Some(ordering) = ordering else { return Ok(Value::Bool(false)) };
+    let result = match op {
+        BinaryOperator::Ge => ordering.is_ge(),
+        BinaryOperator::Le => ordering.is_le(),
+        BinaryOperator::Gt => ordering.is_gt(),
+        BinaryOperator::Lt => ordering.is_lt(),
+        _ => unreachable!("only called with comparison operators"),
+    };
+    Ok(Value::Bool(result))
+}
+
+fn int_operation(op: BinaryOperator, lhs: i128, rhs: i128, ty: IntType) -> EvalResult<Value> {
+    use BinaryOperator as Op;
+
+    if matches!(op, Op::Div | Op::Mod) && rhs == 0 {
+        return Err(RuntimeError::DivisionByZero);
+    }
+
+    // Bits shifted out of the type are gone, but shifting by more bits than it has is an overflow.
+    let shift = u32::try_from(rhs).ok().filter(|&shift| shift < ty.bits);
+
+    let result = match op {
+        Op::Add => lhs.checked_add(rhs),
+        Op::Sub => lhs.checked_sub(rhs),
+        Op::Mul => lhs.checked_mul(rhs),
+        Op::Div => lhs.checked_div(rhs),
+        Op::Mod => lhs.checked_rem(rhs),
+        Op::BitOr => Some(lhs | rhs),
+        Op::BitAnd => Some(lhs & rhs),
+        Op::BitXor => Some(lhs ^ rhs),
+        Op::BitRight => shift.map(|shift| lhs >> shift),
+        Op::BitLeft => shift.map(|shift| ty.wrap(lhs << shift)),
+        _ => return Err(RuntimeError::Invalid),
+    };
+    ty.fit(result)
+}
+
+// How wide an integer operation is and whether it's signed, going by the type the checker gave
+// its operands. Values are all stored as `i128`, so it's up to the operations to stay in range.
+#[derive(Clone, Copy)]
+struct IntType {
+    sign: bool,
+    bits: u32,
+}
+
+impl IntType {
+    fn of(ty: &Option<Type>) -> EvalResult<Self> {
+        match ty {
+            Some(Type::Int { sign, kind }) => Ok(IntType { sign: *sign, bits: kind.bits() }),
+            _ => Err(RuntimeError::Invalid),
+        }
+    }
+
+    // `value` as long as there is one and it fits in the type.
+    fn fit(self, value: Option<i128>) -> EvalResult<Value> {
+        let (min, max) = match self.sign {
+            true => (-(1 << (self.bits - 1)), (1 << (self.bits - 1)) - 1),
+            false => (0, (1 << self.bits) - 1),
+        };
+        match value {
+            Some(value) if (min..=max).contains(&value) => Ok(Value::Int(value)),
+            _ => Err(RuntimeError::Overflow),
+        }
+    }
+
+    // `value` cut down to the type's bits, the same as casting it would in Rust.
+    fn wrap(self, value: i128) -> i128 {
+        let unused = 128 - self.bits;
+        match self.sign {
+            true => (value << unused) >> unused,
+            false => ((value << unused) as u128 >> unused) as i128,
+        }
+    }
+}
+
+// Which float type an operation is on, going by the type the checker gave its operands.
+fn float_kind(ty: &Option<Type>) -> EvalResult<FloatKind> {
+    match ty {
+        Some(Type::Float { kind }) => Ok(*kind),
+        _ => Err(RuntimeError::Invalid),
+    }
+}
+
+// Worked out as `f64`, then rounded to `kind` the same way the prelude's float functions are.
+fn float_operation(op: BinaryOperator, lhs: f64, rhs: f64, kind: FloatKind) -> EvalResult<Value> {
+    let result = match op {
+        BinaryOperator::Add => lhs + rhs,
+        BinaryOperator::Sub => lhs - rhs,
+        BinaryOperator::Mul => lhs * rhs,
+        BinaryOperator::Div => lhs / rhs,
+        BinaryOperator::Mod => lhs % rhs,
+        _ => return Err(RuntimeError::Invalid),
+    };
+    Ok(Value::Float(prelude::narrow(kind, result)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    DivisionByZero,
+    Overflow,
+    // A native function that couldn't do what it was asked to.
+    Native { function: String, message: String },
+    // Something the type checker should have rejected.
+    Invalid,
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::DivisionByZero => write!(f, "attempted to divide by zero"),
+            RuntimeError::Overflow => write!(f, "arithmetic overflow"),
+            RuntimeError::Native { function, message } => write!(f, "{function}: {message}"),
+            RuntimeError::Invalid => write!(f, "invalid operation, was the program type checked?"),
+        }
+    }
+}
+
+pub type EvalResult<T> = Result<T, RuntimeError>;
+
+#[cfg(test)]
+mod tests;
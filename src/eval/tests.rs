@@ -0,0 +1,210 @@
+use super::*;
+use crate::parse::Parser;
+use crate::typeck::TypeChecker;
+
+fn run(s: &str) -> EvalResult<Value> {
+    let (mut tree, errors) = Parser::parse_interactive(s);
+    assert!(errors.is_empty());
+    TypeChecker::new().check_tree(&mut tree).unwrap();
+    Interpreter::new().eval_tree(&tree)
+}
+
+fn check(s: &str, expected: Value) {
+    assert_eq!(run(s), Ok(expected));
+}
+
+#[test]
+fn arithmetic() {
+    check("1 + 2 * 3", Value::Int(7));
+    check("(1 + 2) * 3", Value::Int(9));
+    check("7 % 4 << 2", Value::Int(7));
+    check("-1.5 * 2.", Value::Float(-3.0));
+    check("2.5e1 * 1_0.0", Value::Float(250.0));
+    check("1 < 2 && 2 != 3", Value::Bool(true));
+    check("0xffu64 + 0o10 + 0b11 + 1_000", Value::Int(1266));
+}
+
+#[test]
+fn functions_and_closures() {
+    check("
+        fn fact(n: u64) -> u64 {
+            if n == 0 { 1 } else { n * fact(n - 1) }
+        }
+        fact(10)
+    ", Value::Int(3628800));
+
+    check("
+        let base = 10;
+        let add = \\(x: i32) -> i32 { x + base };
+        add(5)
+    ", Value::Int(15));
+
+    check("
+        fn sign(n: i32) -> i32 {
+            if n < 0 { -1 } else if n == 0 { 0 } else { 1 }
+        }
+        (sign(-5), sign(0), sign(5))
+    ", Value::Tuple(vec![Value::Int(-1), Value::Int(0), Value::Int(1)]));
+}
+
+#[test]
+fn pipes() {
+    check("
+        fn sub(a: i32, b: i32) -> i32 { a - b }
+        let negate = \\(n: i32) -> i32 { -n };
+        10 |> sub(3) |> negate |> sub(1)
+    ", Value::Int(-8));
+    check("[1, 2, 3] |> list::map(\\(n: i32) -> i32 { n * n }) |> list::len", Value::Int(3));
+}
+
+#[test]
+fn methods() {
+    check("
+        trait Counted {
+            fn count(self) -> i32;
+            fn twice(self) -> i32 { self.count() * 2 }
+        }
+        impl Counted for i32 {
+            fn count(self) -> i32 { self + 1 }
+        }
+        impl Counted for bool {
+            fn count(self) -> i32 { 1 }
+            fn twice(self) -> i32 { 0 }
+        }
+        let n: i32 = 4;
+        (n.twice(), true.twice())
+    ", Value::Tuple(vec![Value::Int(10), Value::Int(0)]));
+
+    check("
+        trait Show {
+            fn show(self) -> i32;
+            fn twice(self) -> i32 { self.show() * 2 }
+        }
+        impl Show for i32 { fn show(self) -> i32 { 1 } }
+        impl Show for u8 { fn show(self) -> i32 { 2 } }
+        impl Show for (i32, i32) { fn show(self) -> i32 { 3 } }
+        impl Show for (bool, bool) { fn show(self) -> i32 { 4 } }
+        impl<T> Show for {T} { fn show(self) -> i32 { 5 } }
+        let a = 1;
+        let b: u8 = 2;
+        [a.show(), b.show(), (1, 2).show(), (true, false).show(), [1].show(), a.twice(), b.twice()]
+    ", Value::List([1, 2, 3, 4, 5, 2, 4].map(Value::Int).to_vec()));
+}
+
+#[test]
+fn structs_and_enums() {
+    check("
+        struct Point { x: i32, y: i32 }
+        trait Shape {
+            fn area(self) -> i32;
+            fn twice(self) -> i32 { self.area() * 2 }
+        }
+        impl Shape for Point {
+            fn area(self) -> i32 { self.x * self.y }
+        }
+        impl Point {
+            fn moved(self, by: i32) -> Point { Point { y: self.y + by, x: self.x + by } }
+        }
+        let p = Point { x: 2, y: 3 };
+        (p.twice(), p.moved(1).area(), p.moved(0) == p)
+    ", Value::Tuple(vec![Value::Int(12), Value::Int(12), Value::Bool(true)]));
+
+    // Fields get evaluated in the order they're written in.
+    let (mut tree, _) = Parser::parse_interactive("
+        struct Pair<A, B> { first: A, second: B }
+        let log = \\(n: i32) -> i32 { print(n); n };
+        Pair { second: log(2), first: log(1) }
+    ");
+    TypeChecker::new().check_tree(&mut tree).unwrap();
+    let mut interpreter = Interpreter { output: Output::Captured(String::new()), ..Interpreter::new() };
+    let pair = Value::Struct {
+        name: "Pair".into(),
+        fields: vec![("first".into(), Value::Int(1)), ("second".into(), Value::Int(2))],
+    };
+    assert_eq!(interpreter.eval_tree(&tree), Ok(pair));
+    assert_eq!(interpreter.output.take(), "21");
+
+    check("
+        enum Light { Red Yellow Green }
+        impl Light {
+            fn next(self) -> Light {
+                if self == Light::Red { Light::Green } else if self == Light::Green { Light::Yellow } else { Light::Red }
+            }
+        }
+        let light = Light::Red;
+        (light.next(), light.next().next() == Light::Yellow)
+    ", Value::Tuple(vec![Value::Variant("Light::Green".into()), Value::Bool(true)]));
+
+    let point = run("struct Point { x: i32, y: i32 } Point { y: 2, x: 1 }").unwrap();
+    assert_eq!(point.to_string(), "Point { x: 1, y: 2 }");
+}
+
+#[test]
+fn interpolation() {
+    check(r#"
+        let name = "alisa";
+        let age = 3;
+        "hello {name}, you are {age + 1} and {(true, ['a'])} \{ok\}"
+    "#, Value::Str("hello alisa, you are 4 and (true, ['a']) {ok}".into()));
+    check(r#""{ "nested {1 + 1}" }""#, Value::Str("nested 2".into()));
+}
+
+#[test]
+fn unicode_names() {
+    // The second `café` is spelled with a combining accent, but it's still the same name.
+    check("let café = 1; let 変数 = 2; cafe\u{301} + 変数", Value::Int(3));
+}
+
+#[test]
+fn float_widths() {
+    // The same as doing it in `f32` to begin with.
+    check("1.1f32 + 2.2f32", Value::Float((1.1f32 + 2.2f32).into()));
+    check("let x: f32 = 1.1; x * 3.0", Value::Float((1.1f32 * 3.0).into()));
+    check("-(0.1f32 + 0.2f32)", Value::Float((-(0.1f32 + 0.2f32)).into()));
+    check("1.1 + 2.2", Value::Float(1.1 + 2.2));
+}
+
+#[test]
+fn runtime_errors() {
+    assert_eq!(run("1 / 0"), Err(RuntimeError::DivisionByZero));
+}
+
+#[test]
+fn block_functions() {
+    // The type checker rejects these, but they still shouldn't end up declared at the top level.
+    let (tree, _) = Parser::parse_interactive("{ fn inner() -> i32 { 1 } }; inner()");
+    let mut interpreter = Interpreter::new();
+    assert_eq!(interpreter.eval_tree(&tree), Err(RuntimeError::Invalid));
+    assert!(interpreter.global("inner").is_none());
+}
+
+#[test]
+fn integer_widths() {
+    check("255u8 - 1u8", Value::Int(254));
+    check("~0u8", Value::Int(255));
+    check("~0i8", Value::Int(-1));
+    check("1u8 << 7", Value::Int(128));
+    check("3u8 << 7", Value::Int(128));
+    check("1i8 << 7", Value::Int(-128));
+    check("-128i8 >> 7", Value::Int(-1));
+    check("2147483646 + 1", Value::Int(2147483647));
+    check("-2147483647 - 1", Value::Int(-2147483648));
+    check("let x: u8 = 200; x + 55", Value::Int(255));
+
+    for overflow in [
+        "0u8 - 1u8",
+        "255u8 + 1",
+        "16u8 * 16u8",
+        "let x: u8 = 200; x + 56",
+        "2147483647 + 1",
+        "-2147483647 - 2",
+        "65536 * 32768",
+        "-(-128i8)",
+        "-128i8 / -1i8",
+        "1u8 << 8",
+        "1 << -1",
+        "fn inc(n: u8) -> u8 { n + 1 } inc(255)",
+    ] {
+        assert_eq!(run(overflow), Err(RuntimeError::Overflow), "{overflow}");
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{FunctionStatement, BlockExpression, Symbol};
+use crate::prelude::NativeFunction;
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Bool(bool),
+    // Every integer width is stored the same way. The type checker makes sure they don't mix, and
+    // tells the operations on them which width to stay in.
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Char(char),
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    // Fields are in the order the struct declares them in, whatever order they were given in.
+    Struct { name: Symbol, fields: Vec<(Symbol, Value)> },
+    // An enum variant by its full name, like `Color::Red`.
+    Variant(Symbol),
+    Function(Rc<FunctionStatement>),
+    Closure(Rc<Closure>),
+    Native(Rc<NativeFunction>),
+    Void,
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub arguments: Vec<Symbol>,
+    pub block: BlockExpression,
+    // Every local the closure could see when it was created.
+    pub captured: HashMap<Symbol, Value>,
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
+            (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Struct { name: a, fields: a_fields }, Value::Struct { name: b, fields: b_fields }) => {
+                a == b && a_fields == b_fields
+            }
+            (Value::Variant(a), Value::Variant(b)) => a == b,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            (Value::Native(a), Value::Native(b)) => Rc::ptr_eq(a, b),
+            (Value::Void, Value::Void) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value:?}"),
+            Value::Str(value) => write!(f, "{value:?}"),
+            Value::Char(value) => write!(f, "{value:?}"),
+            Value::Tuple(values) => {
+                write!(f, "(")?;
+                write_separated(f, values)?;
+                if values.len() == 1 { write!(f, ",")?; }
+                write!(f, ")")
+            }
+            Value::List(values) => {
+                write!(f, "[")?;
+                write_separated(f, values)?;
+                write!(f, "]")
+            }
+            Value::Struct { name, fields } => {
+                write!(f, "{name} {{")?;
+                for (i, (field, value)) in fields.iter().enumerate() {
+                    write!(f, "{}{field}: {value}", if i > 0 { ", " } else { " " })?;
+                }
+                match fields.is_empty() {
+                    true => write!(f, "}}"),
+                    false => write!(f, " }}"),
+                }
+            }
+            Value::Variant(name) => write!(f, "{name}"),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+            Value::Closure(_) => write!(f, "<closure>"),
+            Value::Native(function) => write!(f, "<fn {}>", function.name),
+            Value::Void => write!(f, "()"),
+        }
+    }
+}
+
+fn write_separated(f: &mut fmt::Formatter<'_>, values: &[Value]) -> fmt::Result {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 { write!(f, ", ")?; }
+        write!(f, "{value}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+// Fix-its: changes that errors suggest to fix them, which editors can offer and `alisa --fix`
+// applies on its own.
+
+use crate::ast::Span;
+use crate::parse::Parser;
+use crate::syntax::reparse::Edit;
+use crate::typeck::TypeChecker;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    // What the fix does, like "insert `;`".
+    pub message: String,
+    pub edit: Edit,
+}
+
+impl Fix {
+    pub fn insert(message: impl Into<String>, offset: usize, text: &str) -> Self {
+        Self { message: message.into(), edit: Edit::new(Span::new(offset, offset), text) }
+    }
+
+    pub fn replace(message: impl Into<String>, span: Span, text: &str) -> Self {
+        Self { message: message.into(), edit: Edit::new(span, text) }
+    }
+
+    // The same fix with every offset in it passed through `f`.
+    pub fn map_offsets(&self, f: impl Fn(usize) -> usize) -> Fix {
+        let span = Span::new(f(self.edit.span.start), f(self.edit.span.end));
+        Fix { message: self.message.clone(), edit: Edit::new(span, self.edit.insert.clone()) }
+    }
+}
+
+// The fixes suggested for `src`, in the order their errors appear in it, since later errors are
+// often just caused by earlier ones. Type errors are only looked for once it parses.
+pub fn fixes(src: &str) -> Vec<Fix> {
+    let (mut tree, mut errors) = Parser::parse(src);
+    if !errors.is_empty() {
+        errors.sort_by_key(|err| err.span().start);
+        return errors.iter().filter_map(|err| err.fix().cloned()).collect();
+    }
+    match TypeChecker::new().check_tree(&mut tree) {
+        Err(err) => err.fix.map(|fix| vec![*fix]).unwrap_or_default(),
+        Ok(_) => Vec::new(),
+    }
+}
+
+// Applies every fix to `src`. A fix that overlaps one that comes before it is left out, since
+// the two can't both be right.
+pub fn apply(src: &str, fixes: &[Fix]) -> String {
+    let mut edits: Vec<&Edit> = fixes.iter().map(|fix| &fix.edit).collect();
+    edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        if edit.span.start < pos { continue }
+        out.push_str(&src[pos..edit.span.start]);
+        out.push_str(&edit.insert);
+        pos = edit.span.end;
+    }
+    out.push_str(&src[pos..]);
+    out
+}
+
+// The candidate `name` is most likely a typo of, if any is close enough. Roughly one edit is
+// allowed for every three characters, but never so many that nothing of `name` is left.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let length = name.chars().count();
+    let limit = (length / 3).max(1).min(length.saturating_sub(1));
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+// Levenshtein distance, where swapping two characters next to each other counts as one edit too.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Rows for the last two prefixes of `a` and the current one.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests;
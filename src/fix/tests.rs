@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn distances() {
+    assert_eq!(distance("", ""), 0);
+    assert_eq!(distance("lenght", "length"), 1);
+    assert_eq!(distance("stuct", "struct"), 1);
+    assert_eq!(distance("kitten", "sitting"), 3);
+    assert_eq!(distance("名前", "名"), 1);
+}
+
+#[test]
+fn closest_names() {
+    let names = ["length", "len", "push", "pop"];
+    assert_eq!(closest("lenght", names), Some("length"));
+    assert_eq!(closest("pish", names), Some("push"));
+    // Too far off to be a typo, or not one at all.
+    assert_eq!(closest("size", names), None);
+    assert_eq!(closest("len", names), None);
+    assert_eq!(closest("g", ["f", "go"]), None);
+    // Ties go to whichever comes first alphabetically.
+    assert_eq!(closest("lem", ["len", "leg"]), Some("leg"));
+}
+
+#[test]
+fn applying_fixes() {
+    let fixes = [
+        Fix::insert("insert `;`", 9, ";"),
+        Fix::replace("use `==` to compare", Span::new(15, 16), "=="),
+        Fix::insert("insert `}`", 23, "}"),
+    ];
+    assert_eq!(apply("let a = 1 if a = 1 { 2 ", &fixes), "let a = 1; if a == 1 { 2 }");
+
+    // Of two fixes for the same place, only the first is applied.
+    let fixes = [Fix::replace("", Span::new(0, 3), "abc"), Fix::replace("", Span::new(1, 2), "x")];
+    assert_eq!(apply("123", &fixes), "abc");
+}
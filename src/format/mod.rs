@@ -0,0 +1,580 @@
+// Re-emits source from the parse tree in one canonical style. Literals are copied straight from
+// the source so that things like `1_000` or escapes in strings stay the way they were written, and
+// comments are picked back up from the syntax tree's trivia.
+
+use crate::parse::{Parser, ParseError};
+use crate::syntax::{SyntaxKind, SyntaxNode, lower::lower};
+use crate::parse::infix_binding_power;
+use crate::ast::{Statement, Expression, ExprId, Exprs, Span, Parameter, Symbol, Type, generic_params, join_names};
+use crate::ast::{operand_parens, unary_operand_parens, open_prefix, condition_parens};
+use crate::ast::{FunctionStatement, StructStatement, EnumStatement, LetStatement};
+use crate::ast::{ImplStatement, TraitStatement, TraitMethod};
+use crate::ast::{BlockExpression, IfExpression, ElseExpression, StructExpression};
+use crate::ast::{LitKind, Tuple, List};
+use crate::ast::token::TokenKind;
+
+const INDENT: &str = "    ";
+const MAX_WIDTH: usize = 100;
+
+// Formats a whole file. Files that don't parse are left alone since there's no tree to print.
+pub fn format(src: &str) -> Result<String, Vec<ParseError>> {
+    let (root, errors) = Parser::parse_syntax(src);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let tree = lower(&root);
+    let mut formatter = Formatter::new(src, &tree.exprs, comments(&root));
+    formatter.statements(&tree.root, None);
+    formatter.comments_before(src.len());
+
+    // Everything but the very first item ends up with its own line break before it.
+    let mut out = formatter.out;
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+struct Formatter<'src> {
+    src: &'src str,
+    exprs: &'src Exprs,
+    out: String,
+    indent: usize,
+
+    comments: Vec<Span>,
+    next_comment: usize,
+    // Where whatever was printed last ended in the source, used to keep blank lines around.
+    last_end: Option<usize>,
+}
+
+impl<'src> Formatter<'src> {
+    fn new(src: &'src str, exprs: &'src Exprs, comments: Vec<Span>) -> Self {
+        Self {
+            src,
+            exprs,
+            out: String::new(),
+            indent: 0,
+            comments,
+            next_comment: 0,
+            last_end: None,
+        }
+    }
+
+    // Starts a new line for an item that starts at `start` in the source. A single blank line is
+    // kept if there was at least one there before.
+    fn item_line(&mut self, start: usize) {
+        if let Some(gap) = self.last_end.and_then(|last_end| self.src.get(last_end..start)) {
+            if gap.matches('\n').count() > 1 {
+                self.out.push('\n');
+            }
+        }
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    // Prints every comment that hasn't been printed yet and starts before `offset`.
+    fn comments_before(&mut self, offset: usize) {
+        while let Some(&comment) = self.comments.get(self.next_comment) {
+            if comment.start >= offset { break }
+            self.next_comment += 1;
+
+            self.item_line(comment.start);
+            self.out.push_str(self.src[comment.start..comment.end].trim_end());
+            self.last_end = Some(comment.end);
+        }
+    }
+
+    // Prints a comment that sits on the same line right after `end`.
+    fn trailing_comment(&mut self, end: usize) {
+        let Some(&comment) = self.comments.get(self.next_comment) else { return };
+        if comment.start < end || self.src[end..comment.start].contains('\n') {
+            return;
+        }
+        self.next_comment += 1;
+
+        self.out.push(' ');
+        self.out.push_str(self.src[comment.start..comment.end].trim_end());
+        self.last_end = Some(comment.end);
+    }
+
+    fn statements(&mut self, statements: &[Statement], tail: Option<ExprId>) {
+        for statement in statements {
+            if let Statement::EOF = statement { continue }
+
+            let span = statement.span();
+            self.comments_before(span.start);
+            self.item_line(span.start);
+            self.statement(statement);
+            self.last_end = Some(span.end);
+            self.trailing_comment(span.end);
+        }
+
+        if let Some(expr) = tail {
+            let span = self.exprs[expr].span();
+            self.comments_before(span.start);
+            self.item_line(span.start);
+            self.expr(expr);
+            self.last_end = Some(span.end);
+            self.trailing_comment(span.end);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Function(item) => self.function(item, None),
+            Statement::Struct(item) => self.structure(item),
+            Statement::Enum(item) => self.enumeration(item),
+            Statement::Impl(item) => self.implementation(item),
+            Statement::Trait(item) => self.trait_statement(item),
+            Statement::Import(item) => self.out.push_str(&format!("{} {};", item.keyword(), join_names(&item.path, "::"))),
+            Statement::Let(item) => self.let_statement(item),
+            Statement::Expression { expr, end_token, .. } => {
+                self.expr(*expr);
+                if end_token.kind == TokenKind::Semi {
+                    self.out.push(';');
+                }
+            }
+            // Only trees without parse errors get formatted, but the text is kept as is anyway.
+            Statement::Error { span } => self.out.push_str(&self.src[span.start..span.end]),
+            Statement::EOF => (),
+        }
+    }
+
+    fn function(&mut self, item: &FunctionStatement, self_type: Option<&Type>) {
+        let FunctionStatement { name, generics, arguments, return_type, .. } = item;
+        self.visibility(item.public);
+        self.signature(&name.as_str(), generics, arguments, return_type, self_type, item.block.span.start);
+        self.out.push(' ');
+        self.block(&item.block);
+    }
+
+    // `fn name(arguments) -> return_type`, with the arguments on their own lines if it doesn't fit
+    // on one. `body` is where whatever comes after it starts in the source.
+    fn signature(
+        &mut self,
+        name: &str,
+        generics: &[Symbol],
+        arguments: &[Parameter],
+        return_type: &Type,
+        self_type: Option<&Type>,
+        body: usize,
+    ) {
+        let name = format!("{}{}", name, generic_params(generics));
+        let single_line = format!("fn {}({}) -> {}", name, params(arguments, self_type), return_type);
+
+        // Room is left for the ` {` that comes after it. Comments among the arguments need them
+        // to be on their own lines too.
+        let comments = arguments.first().is_some_and(|first| self.has_comments_within(first.name_span.start, body));
+        if !comments && self.line_width() + single_line.len() + 2 <= MAX_WIDTH {
+            self.out.push_str(&single_line);
+        } else {
+            self.out.push_str(&format!("fn {name}("));
+            self.indent += 1;
+            self.last_end = None;
+            for param in arguments {
+                self.comments_before(param.name_span.start);
+                self.item_line(param.name_span.start);
+                self.out.push_str(&params(std::slice::from_ref(param), self_type));
+                self.out.push(',');
+                self.last_end = Some(param.name_span.end);
+                self.trailing_comment(param.name_span.end);
+            }
+            self.comments_before(body);
+            self.indent -= 1;
+            self.last_end = None;
+            self.item_line(body);
+            self.out.push_str(&format!(") -> {return_type}"));
+        }
+    }
+
+    fn implementation(&mut self, item: &ImplStatement) {
+        self.out.push_str(&format!("impl{} ", generic_params(&item.generics)));
+        if let Some(trait_name) = &item.trait_name {
+            self.out.push_str(&format!("{trait_name} for "));
+        }
+        self.out.push_str(&format!("{} {{", item.self_type));
+
+        self.members(&item.methods, item.span.end, |method| method.span, |f, method| {
+            f.function(method, Some(&item.self_type));
+        });
+    }
+
+    fn trait_statement(&mut self, item: &TraitStatement) {
+        self.visibility(item.public);
+        self.out.push_str(&format!("trait {} {{", item.name));
+
+        let self_type = Type::Generic { name: "Self".into() };
+        self.members(&item.methods, item.span.end, |method| method.span, |f, method| {
+            let TraitMethod { name, generics, arguments, return_type, .. } = method;
+            let body = method.block.as_ref().map_or(method.span.end, |block| block.span.start);
+            f.signature(&name.as_str(), generics, arguments, return_type, Some(&self_type), body);
+            match &method.block {
+                Some(block) => {
+                    f.out.push(' ');
+                    f.block(block);
+                }
+                None => f.out.push(';'),
+            }
+        });
+    }
+
+    // Prints what's inside the braces of an `impl` or a `trait`, each on its own line. `end` is
+    // where the closing brace ends in the source.
+    fn members<T>(&mut self, members: &[T], end: usize, span: impl Fn(&T) -> Span, mut print: impl FnMut(&mut Self, &T)) {
+        if members.is_empty() && !self.has_comments_before(end) {
+            self.out.push('}');
+            return;
+        }
+
+        self.indent += 1;
+        self.last_end = None;
+        for member in members {
+            let span = span(member);
+            self.comments_before(span.start);
+            self.item_line(span.start);
+            print(self, member);
+            self.last_end = Some(span.end);
+            self.trailing_comment(span.end);
+        }
+        self.comments_before(end);
+        self.indent -= 1;
+
+        self.close_brace(end);
+    }
+
+    fn visibility(&mut self, public: bool) {
+        if public {
+            self.out.push_str("pub ");
+        }
+    }
+
+    fn structure(&mut self, item: &StructStatement) {
+        self.visibility(item.public);
+        self.out.push_str(&format!("struct {}{} {{", item.name, generic_params(&item.generics)));
+        if item.fields.is_empty() && !self.has_comments_before(item.span.end) {
+            self.out.push('}');
+            return;
+        }
+
+        self.indent += 1;
+        self.last_end = None;
+        for field in &item.fields {
+            self.comments_before(field.name_span.start);
+            self.item_line(field.name_span.start);
+            self.out.push_str(&format!("{}: {},", field.name, field.param_type));
+            self.last_end = Some(field.name_span.end);
+            self.trailing_comment(field.name_span.end);
+        }
+        self.comments_before(item.span.end);
+        self.indent -= 1;
+
+        self.close_brace(item.span.end);
+    }
+
+    fn enumeration(&mut self, item: &EnumStatement) {
+        self.visibility(item.public);
+        self.out.push_str(&format!("enum {}{} {{", item.name, generic_params(&item.generics)));
+        if item.variants.is_empty() && !self.has_comments_before(item.span.end) {
+            self.out.push('}');
+            return;
+        }
+
+        self.indent += 1;
+        self.last_end = None;
+        for (variant, &span) in item.variants.iter().zip(&item.variant_spans) {
+            self.comments_before(span.start);
+            self.item_line(span.start);
+            self.out.push_str(&variant.as_str());
+            self.last_end = Some(span.end);
+            self.trailing_comment(span.end);
+        }
+        self.comments_before(item.span.end);
+        self.indent -= 1;
+
+        self.close_brace(item.span.end);
+    }
+
+    fn let_statement(&mut self, item: &LetStatement) {
+        self.out.push_str(&format!("let {}", item.name));
+        if let Some(var_type) = &item.var_type {
+            self.out.push_str(&format!(": {var_type}"));
+        }
+        self.out.push_str(" = ");
+        self.expr(item.value);
+        self.out.push(';');
+    }
+
+    fn block(&mut self, block: &BlockExpression) {
+        let empty = block.statements.is_empty() && block.expression.is_none();
+        if empty && !self.has_comments_before(block.span.end) {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push('{');
+        self.indent += 1;
+        self.last_end = None;
+        self.statements(&block.statements, block.expression);
+        self.comments_before(block.span.end);
+        self.indent -= 1;
+
+        self.close_brace(block.span.end);
+    }
+
+    // `end` is where the closing brace ends in the source.
+    fn close_brace(&mut self, end: usize) {
+        self.last_end = None;
+        self.item_line(end);
+        self.out.push('}');
+    }
+
+    fn has_comments_before(&self, offset: usize) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.start < offset)
+    }
+
+    fn has_comments_within(&self, start: usize, end: usize) -> bool {
+        self.comments[self.next_comment..]
+            .iter()
+            .take_while(|comment| comment.start < end)
+            .any(|comment| comment.start >= start)
+    }
+
+    fn line_width(&self) -> usize {
+        let line_start = self.out.rfind('\n').map_or(0, |i| i + 1);
+        self.out[line_start..].chars().count()
+    }
+
+    fn expr(&mut self, expr: ExprId) {
+        let continued = self.continue_after_comments(self.exprs[expr].span().start);
+        self.expr_kind(expr);
+        if continued {
+            self.indent -= 1;
+        }
+    }
+
+    // Comments in the middle of an expression, before `start`, stay where they were in it. One that
+    // was at the end of a line stays at the end of that line, and the rest get lines of their own.
+    // Whatever's left of the expression carries on on the next line, indented once more until it
+    // ends, so returns whether that happened.
+    fn continue_after_comments(&mut self, start: usize) -> bool {
+        let Some(&comment) = self.comments.get(self.next_comment) else { return false };
+        if comment.start >= start {
+            return false;
+        }
+
+        // Like the space after the operator in `a + // comment`.
+        self.out.truncate(self.out.trim_end().len());
+        self.indent += 1;
+        let before = self.src[..comment.start].trim_end().len();
+        self.last_end = None;
+        if !self.src[before..comment.start].contains('\n') {
+            self.trailing_comment(before);
+        }
+        self.comments_before(start);
+        self.last_end = None;
+        self.item_line(start);
+        true
+    }
+
+    fn expr_kind(&mut self, expr: ExprId) {
+        let exprs = self.exprs;
+        match &exprs[expr] {
+            Expression::Literal(literal) => match &literal.kind {
+                LitKind::Tuple(Tuple(expressions)) => {
+                    // A single element needs the comma to not just be a parenthesized expression.
+                    let close = if expressions.len() == 1 { ",)" } else { ")" };
+                    self.group("(", expressions, close, literal.span);
+                }
+                LitKind::List(List(expressions)) => self.group("[", expressions, "]", literal.span),
+                _ => self.out.push_str(&self.src[literal.span.start..literal.span.end]),
+            },
+
+            Expression::Identifier(ident) => self.out.push_str(&ident.name.as_str()),
+
+            Expression::Block(block) => self.block(block),
+
+            Expression::If(if_expr) => self.if_expr(if_expr),
+
+            Expression::Closure(closure) => {
+                self.out.push_str(&format!("\\({}) -> {} ", params(&closure.arguments, None), closure.return_type));
+                self.block(&closure.block);
+            }
+
+            Expression::Call(call) => {
+                let parens = matches!(exprs[call.function], Expression::Binary(_) | Expression::Unary(_));
+                self.operand(call.function, parens);
+                self.group("(", &call.arguments, ")", call.span);
+            }
+
+            Expression::MethodCall(call) => {
+                let parens = matches!(exprs[call.receiver], Expression::Binary(_) | Expression::Unary(_));
+                self.operand(call.receiver, parens);
+                self.out.push_str(&format!(".{}", call.method));
+                self.group("(", &call.arguments, ")", call.span);
+            }
+
+            Expression::Struct(struct_expr) => self.struct_expr(struct_expr),
+
+            Expression::Field(field) => {
+                let parens = matches!(exprs[field.receiver], Expression::Binary(_) | Expression::Unary(_));
+                self.operand(field.receiver, parens);
+                self.out.push_str(&format!(".{}", field.field));
+            }
+
+            Expression::Unary(unary) => {
+                self.out.push_str(&unary.op.to_string());
+                self.operand(unary.rhs, unary_operand_parens(exprs, unary));
+            }
+
+            Expression::Binary(binary) => {
+                let (l_bp, r_bp) = infix_binding_power(binary.op);
+
+                // Whatever's on the left can't have an operator that binds looser than this one
+                // sticking out at its end, since it would swallow everything to the right.
+                let lhs_parens = match &exprs[binary.lhs] {
+                    Expression::Binary(lhs) => infix_binding_power(lhs.op).1 <= l_bp,
+                    _ => false,
+                } || open_prefix(exprs, binary.lhs).is_some_and(|bp| bp <= l_bp);
+
+                self.operand(binary.lhs, lhs_parens);
+                self.out.push_str(&format!(" {} ", binary.op));
+                self.operand(binary.rhs, operand_parens(exprs, binary.rhs, r_bp));
+            }
+        }
+    }
+
+    fn operand(&mut self, expr: ExprId, parens: bool) {
+        // The parentheses go after any comments before the operand, not before them.
+        let continued = parens && self.continue_after_comments(self.exprs[expr].span().start);
+        if parens { self.out.push('('); }
+        self.expr(expr);
+        if parens { self.out.push(')'); }
+        if continued {
+            self.indent -= 1;
+        }
+    }
+
+    // Prints `expressions` between `open` and `close`, all on one line unless there are comments
+    // among them, which each need a line of their own to end. `span` ends right after `close`.
+    fn group(&mut self, open: &str, expressions: &[ExprId], close: &str, span: Span) {
+        self.out.push_str(open);
+        if !self.has_comments_within(span.start, span.end) {
+            for (i, &expr) in expressions.iter().enumerate() {
+                if i > 0 { self.out.push_str(", "); }
+                self.expr(expr);
+            }
+            self.out.push_str(close);
+            return;
+        }
+
+        self.indent += 1;
+        self.last_end = None;
+        for (i, &expr) in expressions.iter().enumerate() {
+            let span = self.exprs[expr].span();
+            self.comments_before(span.start);
+            self.item_line(span.start);
+            self.expr(expr);
+            // Lists and tuples don't take a comma after their last element, except for the one a
+            // single element tuple needs.
+            if i + 1 < expressions.len() || close.starts_with(',') {
+                self.out.push(',');
+            }
+            self.last_end = Some(span.end);
+            self.trailing_comment(span.end);
+        }
+        self.comments_before(span.end);
+        self.indent -= 1;
+
+        self.last_end = None;
+        self.item_line(span.end);
+        self.out.push_str(close.trim_start_matches(','));
+    }
+
+    // Same as `group()`, but for the fields of a struct literal, which take a comma after the last
+    // one when they're on lines of their own.
+    fn struct_expr(&mut self, struct_expr: &StructExpression) {
+        let span = struct_expr.span;
+        self.out.push_str(&format!("{} {{", struct_expr.name));
+        if struct_expr.fields.is_empty() && !self.has_comments_within(span.start, span.end) {
+            self.out.push('}');
+            return;
+        }
+        if !self.has_comments_within(span.start, span.end) {
+            for (i, field) in struct_expr.fields.iter().enumerate() {
+                self.out.push_str(if i > 0 { ", " } else { " " });
+                self.out.push_str(&format!("{}: ", field.name));
+                self.expr(field.value);
+            }
+            self.out.push_str(" }");
+            return;
+        }
+
+        self.indent += 1;
+        self.last_end = None;
+        for field in &struct_expr.fields {
+            self.comments_before(field.name_span.start);
+            self.item_line(field.name_span.start);
+            self.out.push_str(&format!("{}: ", field.name));
+            self.expr(field.value);
+            self.out.push(',');
+            let end = self.exprs[field.value].span().end;
+            self.last_end = Some(end);
+            self.trailing_comment(end);
+        }
+        self.comments_before(span.end);
+        self.indent -= 1;
+
+        self.last_end = None;
+        self.item_line(span.end);
+        self.out.push('}');
+    }
+
+    fn if_expr(&mut self, if_expr: &IfExpression) {
+        self.out.push_str("if ");
+        self.operand(if_expr.condition, condition_parens(self.exprs, if_expr.condition));
+        self.out.push(' ');
+        self.block(&if_expr.body);
+
+        match &if_expr.else_body {
+            Some(ElseExpression::Else(block)) => {
+                self.out.push_str(" else ");
+                self.block(block);
+            }
+            Some(ElseExpression::ElseIf(else_if)) => {
+                self.out.push_str(" else ");
+                self.expr_kind(*else_if);
+            }
+            None => (),
+        }
+    }
+}
+
+// A method's `self` is left without its type if it's just `Self`.
+fn params(params: &[Parameter], self_type: Option<&Type>) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| match param.name == "self" && Some(&param.param_type) == self_type {
+            true => param.name.to_string(),
+            false => format!("{}: {}", param.name, param.param_type),
+        })
+        .collect();
+    params.join(", ")
+}
+
+fn comments(root: &SyntaxNode) -> Vec<Span> {
+    root.tokens()
+        .iter()
+        .filter(|token| token.kind() == SyntaxKind::Comment)
+        .map(|token| token.span())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,239 @@
+use super::*;
+
+fn check(s: &str, expected: &str) {
+    let formatted = format(s).unwrap();
+    assert_eq!(formatted, expected);
+    // Formatting something that's already formatted shouldn't change anything.
+    assert_eq!(format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn items() {
+    check(
+        "struct Point{x:i32,y:i32}\nenum Color{Red Green}\nfn add(a:i32,b:i32)->i32{a+b}",
+        "\
+struct Point {
+    x: i32,
+    y: i32,
+}
+enum Color {
+    Red
+    Green
+}
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+",
+    );
+}
+
+#[test]
+fn generics() {
+    check(
+        "struct Pair<A,B>{a:A,b:B}\nenum Option<T>{Some None}\nfn id<T>(x:Pair<T,{Option<T>}>)->Pair<T,Option<T>>{x}",
+        "\
+struct Pair<A, B> {
+    a: A,
+    b: B,
+}
+enum Option<T> {
+    Some
+    None
+}
+fn id<T>(x: Pair<T, {Option<T>}>) -> Pair<T, Option<T>> {
+    x
+}
+",
+    );
+}
+
+#[test]
+fn impls_and_traits() {
+    check(
+        "trait Shape{fn area(self)->i32;fn double(self)->i32{self.area()*2}}\nimpl<T> Shape for {T}{fn area(self:{T})->i32{self.len()}}",
+        "\
+trait Shape {
+    fn area(self) -> i32;
+    fn double(self) -> i32 {
+        self.area() * 2
+    }
+}
+impl<T> Shape for {T} {
+    fn area(self) -> i32 {
+        self.len()
+    }
+}
+",
+    );
+}
+
+#[test]
+fn modules() {
+    check(
+        "import  geom :: shapes ;\nmod   lines;\npub   struct P{x:shapes::Circle}\npub fn f()->void{shapes :: draw()}",
+        "\
+import geom::shapes;
+mod lines;
+pub struct P {
+    x: shapes::Circle,
+}
+pub fn f() -> void {
+    shapes::draw()
+}
+",
+    );
+}
+
+#[test]
+fn pipes() {
+    check("fn f()->void{x|>g(1)|>h}", "fn f() -> void {\n    x |> g(1) |> h\n}\n");
+}
+
+#[test]
+fn long_parameter_lists() {
+    check(
+        "fn f(first_argument: i32, second_argument: i32, third_argument: i32, fourth_argument_with_a_long_name: i32) -> void {}",
+        "\
+fn f(
+    first_argument: i32,
+    second_argument: i32,
+    third_argument: i32,
+    fourth_argument_with_a_long_name: i32,
+) -> void {}
+",
+    );
+}
+
+#[test]
+fn parentheses() {
+    check("let a = ((1 + 2)) * 3;", "let a = (1 + 2) * 3;\n");
+    check("let a = (1 * 2) + 3;", "let a = 1 * 2 + 3;\n");
+    check("let a = 1 - (2 - 3);", "let a = 1 - (2 - 3);\n");
+    check("let a = (1 - 2) - 3;", "let a = 1 - 2 - 3;\n");
+    check("let a = -(1 + 2);", "let a = -(1 + 2);\n");
+    check("let a = (!a) == b;", "let a = (!a) == b;\n");
+    check("let a = !a && b;", "let a = !a && b;\n");
+    check("let a = (a + !b) == c;", "let a = (a + !b) == c;\n");
+    check("let a = (-f)(1);", "let a = (-f)(1);\n");
+    check("let a = -(-1);", "let a = -(-1);\n");
+    check("let a = --1.5;", "let a = -(-1.5);\n");
+    check("let a = -(!a);", "let a = -!a;\n");
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    check(
+        "// header\n\n\n\nfn f() -> i32 { // trailing\n// inside\n\n1\n// before brace\n}\n// footer",
+        "\
+// header
+
+fn f() -> i32 {
+    // trailing
+    // inside
+
+    1
+    // before brace
+}
+// footer
+",
+    );
+}
+
+#[test]
+fn comments_in_expressions() {
+    check(
+        "fn f() -> i32 {\nlet x = a + // after plus\nb;\n// next\nlet y = a *\n// why\n\n(b - c);\nx\n}",
+        "\
+fn f() -> i32 {
+    let x = a + // after plus
+        b;
+    // next
+    let y = a *
+        // why
+        (b - c);
+    x
+}
+",
+    );
+}
+
+#[test]
+fn expressions() {
+    check(
+        "let x = if a {1} else if b {2} else {3};\nlet f = \\(x: i32)->i32{x};\nlet t = (1,);",
+        "\
+let x = if a {
+    1
+} else if b {
+    2
+} else {
+    3
+};
+let f = \\(x: i32) -> i32 {
+    x
+};
+let t = (1,);
+",
+    );
+}
+
+#[test]
+fn comments_in_lists() {
+    check(
+        "let a = f(\n1, // one\n2);\nlet b = [ // start\n1 // one\n];",
+        "\
+let a = f(
+    1, // one
+    2
+);
+let b = [
+    // start
+    1 // one
+];
+",
+    );
+    check("let t = (1, // one\n);", "let t = (\n    1, // one\n);\n");
+    check(
+        "enum E {\nA // a\nB\n// last\n}",
+        "\
+enum E {
+    A // a
+    B
+    // last
+}
+",
+    );
+    check(
+        "fn f(a: i32, // about a\nb: i32) -> i32 { a }",
+        "\
+fn f(
+    a: i32, // about a
+    b: i32,
+) -> i32 {
+    a
+}
+",
+    );
+}
+
+#[test]
+fn struct_literals() {
+    check(
+        "let p=Point{x:1,y:a.b};\nlet e=Empty{};\nlet q = geom::Point { x: (a+b).x, y: 2, };\nif e==(Empty{}) {};",
+        "\
+let p = Point { x: 1, y: a.b };
+let e = Empty {};
+let q = geom::Point { x: (a + b).x, y: 2 };
+if (e == Empty {}) {};
+",
+    );
+    check(
+        "let p = Point { x: 1, // one\ny: 2 };",
+        "\
+let p = Point {
+    x: 1, // one
+    y: 2,
+};
+",
+    );
+}
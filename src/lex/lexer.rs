@@ -26,6 +26,13 @@ impl<'a> Lexer<'a> {
         iter.next().unwrap_or(EOF_CHAR)
     }
 
+    pub fn peek_third(&mut self) -> char {
+        let mut iter = self.chars.clone();
+        iter.next();
+        iter.next();
+        iter.next().unwrap_or(EOF_CHAR)
+    }
+
     pub fn is_eof(&mut self) -> bool {
         self.chars.as_str().is_empty()
     }
@@ -4,7 +4,14 @@ pub mod lexer;
 
 use lexer::Lexer;
 
+use std::borrow::Cow;
+use std::ops::Range;
+
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_xid::UnicodeXID;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum TokenKind {
     Identifier,
 
@@ -13,6 +20,9 @@ pub enum TokenKind {
     // see is_whitespace() in mod.rs
     Whitespace,
 
+    // `// ...` up until the end of the line
+    LineComment,
+
     // `;`
     Semi,
     // `:`
@@ -75,11 +85,24 @@ pub enum LiteralKind {
 
     Float,
 
+    // `"..."`, which can have expressions embedded in it like `"{a + b}"`.
     Str { terminated: bool },
 
+    // `r"..."` or `r#"..."#`, where nothing is escaped or embedded.
+    RawStr { terminated: bool },
+
     Char { terminated: bool },
 }
 
+// A piece of what's between the quotes of a string literal, as a range into it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StrPiece {
+    // Text with its escapes still in it.
+    Text(Range<usize>),
+    // The source of an embedded expression, without its braces.
+    Expr(Range<usize>),
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Token {
     pub kind: TokenKind,
@@ -117,8 +140,23 @@ pub fn is_whitespace(c: char) -> bool {
     )
 }
 
+// Identifiers follow UAX #31, so they start with an XID_Start character or `_` and go on with
+// XID_Continue characters.
 pub fn is_identifier_start(c: char) -> bool {
-    c == '_' || c.is_ascii_alphabetic()
+    c == '_' || c.is_xid_start()
+}
+
+pub fn is_identifier_continue(c: char) -> bool {
+    c.is_xid_continue()
+}
+
+// Names are compared in NFC, so that ones that look the same are the same name no matter how
+// they were typed.
+pub fn normalize_identifier(name: &str) -> Cow<'_, str> {
+    match is_nfc_quick(name.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(name),
+        _ => Cow::Owned(name.nfc().collect()),
+    }
 }
 
 pub fn is_digit(c: char) -> bool {
@@ -135,6 +173,13 @@ impl<'a> Lexer<'a> {
             // Skips through whitespace.
             c if is_whitespace(c) => self.whitespace(),
 
+            // Raw String Literal
+            'r' if self.is_raw_string() => {
+                let terminated = self.raw_string();
+                let litkind = LiteralKind::RawStr { terminated };
+                TokenKind::Literal { kind: litkind }
+            }
+
             // Identifiers can't start with a digit.
             c if is_identifier_start(c) => self.identifier(),
 
@@ -144,6 +189,8 @@ impl<'a> Lexer<'a> {
                 if self.peek_first().is_ascii_digit() {
                     // Keep taking until end of float.
                     self.take_while(is_digit);
+                    self.exponent();
+                    self.suffix();
                     TokenKind::Literal {
                         kind: LiteralKind::Float,
                     }
@@ -153,25 +200,7 @@ impl<'a> Lexer<'a> {
             }
 
             // Integer or Float Literal
-            '0'..='9' => {
-                // Skip through the rest of the digits.
-                self.take_while(is_digit);
-
-                // If the next is a dot, it might be a float or trying to access a field.
-                if self.peek_first() == '.' && !is_identifier_start(self.peek_second()) {
-                    // Skip the dot.
-                    self.take();
-                    self.take_while(is_digit);
-                    TokenKind::Literal {
-                        kind: LiteralKind::Float,
-                    }
-                // If there's no dot at all, it's just an int for sure.
-                } else {
-                    TokenKind::Literal {
-                        kind: LiteralKind::Int,
-                    }
-                }
-            }
+            '0'..='9' => self.number(c),
 
             // String Literal
             '"' => {
@@ -206,7 +235,17 @@ impl<'a> Lexer<'a> {
             '+' => TokenKind::Plus,
             '-' => TokenKind::Minus,
             '*' => TokenKind::Star,
-            '/' => TokenKind::FSlash,
+
+            // Slash or Line Comment
+            '/' => {
+                if self.peek_first() == '/' {
+                    self.take_while(|c| c != '\n');
+                    TokenKind::LineComment
+                } else {
+                    TokenKind::FSlash
+                }
+            }
+
             '\\' => TokenKind::BSlash,
             '%' => TokenKind::Percent,
             '!' => TokenKind::Bang,
@@ -218,13 +257,77 @@ impl<'a> Lexer<'a> {
         res
     }
 
+    pub fn number(&mut self, first: char) -> TokenKind {
+        // `0x`, `0o` and `0b` can only start integers. Digits that don't fit the base are still
+        // taken so the parser can point them out.
+        if first == '0' && matches!(self.peek_first(), 'x' | 'o' | 'b') {
+            match self.take() {
+                Some('x') => self.take_while(|c| c == '_' || c.is_ascii_hexdigit()),
+                _ => self.take_while(is_digit),
+            }
+            self.suffix();
+            return TokenKind::Literal {
+                kind: LiteralKind::Int,
+            };
+        }
+
+        // Skip through the rest of the digits.
+        self.take_while(is_digit);
+
+        // If the next is a dot, it might be a float or trying to access a field.
+        let mut kind = if self.peek_first() == '.' && !is_identifier_start(self.peek_second()) {
+            // Skip the dot.
+            self.take();
+            self.take_while(is_digit);
+            LiteralKind::Float
+        // If there's no dot at all, it's just an int for sure.
+        } else {
+            LiteralKind::Int
+        };
+        if self.exponent() {
+            kind = LiteralKind::Float;
+        }
+        self.suffix();
+        TokenKind::Literal { kind }
+    }
+
+    // `e10`, `E-3` and so on. It's only an exponent if there's a digit in it, otherwise it's left
+    // for `suffix()`.
+    pub fn exponent(&mut self) -> bool {
+        if !matches!(self.peek_first(), 'e' | 'E') {
+            return false;
+        }
+        let digit = match self.peek_second() {
+            '+' | '-' => self.peek_third(),
+            c => c,
+        };
+        if !digit.is_ascii_digit() {
+            return false;
+        }
+
+        self.take();
+        if matches!(self.peek_first(), '+' | '-') {
+            self.take();
+        }
+        self.take_while(is_digit);
+        true
+    }
+
+    // A type suffix like the `u8` in `10u8`. Anything that looks like an identifier is taken, and
+    // the parser decides whether it's a type.
+    pub fn suffix(&mut self) {
+        if is_identifier_start(self.peek_first()) {
+            self.identifier();
+        }
+    }
+
     pub fn whitespace(&mut self) -> TokenKind {
         self.take_while(is_whitespace);
         TokenKind::Whitespace
     }
 
     pub fn identifier(&mut self) -> TokenKind {
-        self.take_while(|c: char| c == '_' || c.is_alphanumeric());
+        self.take_while(is_identifier_continue);
         TokenKind::Identifier
     }
 
@@ -233,37 +336,156 @@ impl<'a> Lexer<'a> {
         while let Some(c) = self.take() {
             match c {
                 '"' => return true,
-                '\\' if self.peek_first() == '\\' || self.peek_first() == '"' => {
-                    // skips the escape character
-                    self.take();
-                }
+                '\\' => self.escape(),
+                '{' if !self.embedded() => return false,
                 _ => (),
             }
         }
         false
     }
 
+    // Whatever comes after a `\` is part of the escape, even a `"`. The braces of a `\u{...}` are
+    // too, so they don't start an embedded expression. The parser works out what the escape means.
+    pub fn escape(&mut self) {
+        if self.take() == Some('u') && self.peek_first() == '{' {
+            self.take_while(|c| c != '}' && c != '"');
+            if self.peek_first() == '}' {
+                self.take();
+            }
+        }
+    }
+
+    // Goes through an expression embedded in a string, up to and including the `}` that closes
+    // it, and returns if there is one. Strings and braces inside of it are skipped as a whole.
+    pub fn embedded(&mut self) -> bool {
+        let mut depth = 0;
+        while let Some(c) = self.take() {
+            // Whether whatever `c` starts is closed.
+            let closed = match c {
+                '}' if depth == 0 => return true,
+                '}' => {
+                    depth -= 1;
+                    true
+                }
+                '{' => {
+                    depth += 1;
+                    true
+                }
+                '"' => self.literal_string(),
+                'r' if self.is_raw_string() => self.raw_string(),
+                '\'' => {
+                    self.literal_char();
+                    true
+                }
+                c if is_identifier_start(c) => {
+                    self.identifier();
+                    true
+                }
+                _ => true,
+            };
+            if !closed {
+                return false;
+            }
+        }
+        false
+    }
+
+    // Whether the `r` that was just taken starts a raw string, which is when it's followed by
+    // some `#`s and a `"`.
+    pub fn is_raw_string(&mut self) -> bool {
+        let mut ahead = self.chars.clone();
+        loop {
+            match ahead.next() {
+                Some('#') => (),
+                Some('"') => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    // Goes through a raw string after its `r` and returns if it's terminated. It only ends at a
+    // `"` followed by as many `#`s as it started with.
+    pub fn raw_string(&mut self) -> bool {
+        let mut hashes = 0;
+        while self.take() == Some('#') {
+            hashes += 1;
+        }
+
+        while let Some(c) = self.take() {
+            if c != '"' {
+                continue;
+            }
+            let mut ahead = self.chars.clone();
+            if (0..hashes).all(|_| ahead.next() == Some('#')) {
+                self.chars = ahead;
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn literal_char(&mut self) -> bool {
-        match self.take() {
-            // Empty character literals are illegal.
-            // If self.take() is None, it doesn't terminate.
-            Some('\'') | None => return false,
-
-            Some(c) => {
-                // Skip char after escape character if it's an escape sequence.
-                if c == '\\' {
-                    self.take();
+        // Empty character literals are illegal.
+        if self.peek_first() == '\'' {
+            self.take();
+            return false;
+        }
+
+        // Everything up to the closing quote is part of the literal, even if it's more than one
+        // character, so the parser can point that out. It has to be on the same line though.
+        let mut ahead = self.chars.clone();
+        loop {
+            match ahead.next() {
+                Some('\\') => {
+                    ahead.next();
                 }
-                if self.peek_first() == '\'' {
-                    // skip "'"
-                    self.take();
+                Some('\'') => {
+                    self.chars = ahead;
                     return true;
                 }
+                Some('\n') | None => break,
+                Some(_) => (),
             }
         }
+
+        // Without a closing quote, only the first character (or escape) is taken.
+        if self.take() == Some('\\') {
+            self.take();
+        }
         false
     }
 }
 
+// Splits what's between the quotes of a string literal into its text and the expressions embedded
+// in it.
+pub fn str_pieces(contents: &str) -> Vec<StrPiece> {
+    let mut lex = Lexer::new(contents);
+    let offset = |lex: &Lexer| contents.len() - lex.chars.as_str().len();
+
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while let Some(c) = lex.take() {
+        match c {
+            '\\' => lex.escape(),
+            '{' => {
+                let open = offset(&lex);
+                if start < open - 1 {
+                    pieces.push(StrPiece::Text(start..open - 1));
+                }
+                // Leave out the `}`, unless the literal ended before it.
+                let closed = lex.embedded();
+                let close = offset(&lex) - usize::from(closed);
+                pieces.push(StrPiece::Expr(open..close));
+                start = offset(&lex);
+            }
+            _ => (),
+        }
+    }
+    if start < contents.len() {
+        pieces.push(StrPiece::Text(start..contents.len()));
+    }
+    pieces
+}
+
 #[cfg(test)]
 mod tests;
@@ -63,6 +63,16 @@ fn character_literal_tokens() {
             kind: LiteralKind::Char { terminated: false },
         },
     );
+    // Escapes and over-long literals are still single tokens; the parser
+    // decides whether their contents are valid.
+    for s in ["'\\''", "'\\u{1F600}'", "'ab'"] {
+        check(
+            s,
+            TokenKind::Literal {
+                kind: LiteralKind::Char { terminated: true },
+            },
+        );
+    }
 }
 
 #[test]
@@ -92,6 +102,19 @@ fn string_literal_tokens() {
             kind: LiteralKind::Str { terminated: false },
         },
     );
+    // An escaped quote doesn't end the string.
+    check(
+        "\"a\\\"b\"",
+        TokenKind::Literal {
+            kind: LiteralKind::Str { terminated: true },
+        },
+    );
+    check(
+        "\"a\\\"",
+        TokenKind::Literal {
+            kind: LiteralKind::Str { terminated: false },
+        },
+    );
 }
 
 #[test]
@@ -122,3 +145,94 @@ fn number_literal_tokens() {
         },
     );
 }
+
+#[test]
+fn number_prefixes_and_suffixes() {
+    let int = TokenKind::Literal { kind: LiteralKind::Int };
+    let float = TokenKind::Literal { kind: LiteralKind::Float };
+    for (s, kind) in [
+        ("0xff_FFu8", int),
+        ("0o17", int),
+        ("0b1010_0101", int),
+        ("0b102", int),
+        ("1_000i64", int),
+        ("1.5f32", float),
+        (".5f64", float),
+        ("1f32", int),
+        ("1e10", float),
+        ("2.5E-3", float),
+        ("1_000.0e+1_0f64", float),
+        (".5e3", float),
+    ] {
+        let mut lex = Lexer::new(s);
+        assert_eq!(lex.next_token(), Token::new(kind, s.len()), "{s}");
+    }
+
+    // Without digits, an `e` is a suffix and a sign is its own token.
+    let mut lex = Lexer::new("1e+x");
+    assert_eq!(lex.next_token(), Token::new(int, 2));
+    assert_eq!(lex.next_token(), Token::new(TokenKind::Plus, 1));
+
+    // A method call on an int isn't a float with a suffix.
+    let mut lex = Lexer::new("1.abs()");
+    assert_eq!(lex.next_token(), Token::new(int, 1));
+    assert_eq!(lex.next_token(), Token::new(TokenKind::Dot, 1));
+}
+
+#[test]
+fn comment_tokens() {
+    check("// comment", TokenKind::LineComment);
+    check("//", TokenKind::LineComment);
+    check("/ /", TokenKind::FSlash);
+}
+
+#[test]
+fn interpolated_and_raw_strings() {
+    let str = |terminated| TokenKind::Literal { kind: LiteralKind::Str { terminated } };
+    let raw = |terminated| TokenKind::Literal { kind: LiteralKind::RawStr { terminated } };
+    for (s, kind) in [
+        (r#""a {b} c""#, str(true)),
+        // Quotes and braces inside of an embedded expression don't end it.
+        (r#""{ f("}") + {1} }""#, str(true)),
+        (r#""\u{41} \{ {'"'}""#, str(true)),
+        (r#""{a""#, str(false)),
+        (r#"r"a\b""#, raw(true)),
+        (r##"r#"a"b"#"##, raw(true)),
+        (r###"r##"a"#"##"###, raw(true)),
+        (r##"r#"a""##, raw(false)),
+    ] {
+        let mut lex = Lexer::new(s);
+        assert_eq!(lex.next_token(), Token::new(kind, s.len()), "{s}");
+    }
+    // Without a quote, it's just an identifier.
+    check("r#a", TokenKind::Identifier);
+
+    let contents = r#"a\{{b + "}"} {{}}!"#;
+    assert_eq!(str_pieces(contents), vec![
+        StrPiece::Text(0..3),
+        StrPiece::Expr(4..11),
+        StrPiece::Text(12..13),
+        StrPiece::Expr(14..16),
+        StrPiece::Text(17..18),
+    ]);
+    assert_eq!(str_pieces("{a"), vec![StrPiece::Expr(1..2)]);
+}
+
+#[test]
+fn unicode_identifiers() {
+    for s in ["écrire", "café", "変数", "παράδειγμα", "_x1", "cafe\u{301}"] {
+        let mut lex = Lexer::new(s);
+        assert_eq!(lex.next_token(), Token::new(TokenKind::Identifier, s.len()), "{s}");
+    }
+    // Neither can start an identifier.
+    check("1x", TokenKind::Literal { kind: LiteralKind::Int });
+    check("\u{301}a", TokenKind::Unknown);
+    // `·` only continues one.
+    let mut lex = Lexer::new("a·b ·");
+    assert_eq!(lex.next_token(), Token::new(TokenKind::Identifier, "a·b".len()));
+    lex.next_token();
+    assert_eq!(lex.next_token().kind, TokenKind::Unknown);
+
+    assert_eq!(normalize_identifier("cafe\u{301}"), "café");
+    assert_eq!(normalize_identifier("écrire"), "écrire");
+}
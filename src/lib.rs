@@ -0,0 +1,18 @@
+// The language as a library, for embedding it in other programs. `engine` is the place to start,
+// everything else is what it's built out of.
+
+pub mod lex;
+pub mod parse;
+pub mod ast;
+pub mod typeck;
+pub mod eval;
+pub mod repl;
+pub mod lsp;
+pub mod format;
+pub mod syntax;
+pub mod module;
+pub mod prelude;
+pub mod engine;
+pub mod lint;
+pub mod fix;
+pub mod outline;
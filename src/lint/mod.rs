@@ -0,0 +1,67 @@
+// Warnings about code that's allowed but probably isn't what was meant. Unlike errors they never
+// stop a program from being checked or run.
+
+use std::collections::{HashMap, HashSet};
+
+use unicode_security::{skeleton, MixedScript};
+
+use crate::ast::Span;
+use crate::ast::token::TokenKind;
+use crate::lex;
+use crate::parse::stream::TokenStream;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LintKind {
+    // An identifier with letters from scripts that aren't written together, like a Cyrillic `а`
+    // in an otherwise Latin name.
+    MixedScript { name: String },
+    // An identifier that looks like a different one from earlier in the file.
+    Confusable { name: String, other: String },
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            LintKind::MixedScript { name } => write!(f, "identifier `{name}` mixes scripts"),
+            LintKind::Confusable { name, other } => write!(f, "identifier `{name}` looks like `{other}`"),
+        }
+    }
+}
+
+// Looks for identifiers in `src` that could be mistaken for something they aren't.
+pub fn identifiers(src: &str) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    // The first name seen for each skeleton, which is what a name looks like with confusable
+    // characters replaced by a prototype, so `раураl` and `paypal` have the same one.
+    let mut skeletons: HashMap<String, String> = HashMap::new();
+
+    // Each name only gets linted where it first shows up.
+    let mut seen = HashSet::new();
+
+    for tok in TokenStream::new(src).tokens {
+        if tok.kind != TokenKind::Identifier { continue }
+        let name = lex::normalize_identifier(&src[tok.start..tok.end]).into_owned();
+        if !seen.insert(name.clone()) { continue }
+
+        if !name.as_str().is_single_script() {
+            lints.push(Lint { kind: LintKind::MixedScript { name: name.clone() }, span: tok.span() });
+        }
+
+        // Two ASCII names are left alone, otherwise `l` and `I` would be confusable too.
+        let other = skeletons.entry(skeleton(&name).collect()).or_insert_with(|| name.clone());
+        if *other != name && !(name.is_ascii() && other.is_ascii()) {
+            let kind = LintKind::Confusable { name, other: other.clone() };
+            lints.push(Lint { kind, span: tok.span() });
+        }
+    }
+    lints
+}
+
+#[cfg(test)]
+mod tests;
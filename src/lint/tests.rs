@@ -0,0 +1,27 @@
+use super::*;
+
+fn lints(src: &str) -> Vec<String> {
+    identifiers(src).iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn mixed_scripts() {
+    // `а` is Cyrillic, the rest is Latin.
+    assert_eq!(lints("let nаme = 1; nаme"), ["identifier `nаme` mixes scripts"]);
+    assert_eq!(identifiers("let nаme = 1;")[0].span, Span::new(4, 9));
+    // Single scripts and digits are fine, however many of them there are.
+    assert!(lints("let écrire = 1; let 変数2 = 2; let παράδειγμα_3 = 3;").is_empty());
+}
+
+#[test]
+fn confusables() {
+    assert_eq!(lints("let paypal = 1; let раураl = 2;"), [
+        "identifier `раураl` mixes scripts",
+        "identifier `раураl` looks like `paypal`",
+    ]);
+    // `ο` is a Greek omicron.
+    assert_eq!(lints("let ο = 1; let o = 2;"), ["identifier `o` looks like `ο`"]);
+    // Names that are the same after NFC are the same name.
+    assert!(lints("let cafe\u{301} = 1; café").is_empty());
+    assert!(lints("let l = 1; let I = 2; let rn = 3; let m = 4;").is_empty());
+}
@@ -0,0 +1,257 @@
+// Everything the server knows about a single document. When the document changes only the part of
+// the syntax tree the edit touched is reparsed, but everything after that is redone from scratch.
+
+use crate::ast::{self, ASTree, Span, Type, generic_params, join_names};
+use crate::ast::token::{Token, TokenKind, KEYWORDS};
+use crate::fix::Fix;
+use crate::parse::{Parser, ParseError};
+use crate::syntax::SyntaxNode;
+use crate::syntax::lower::lower;
+use crate::syntax::reparse::{reparse, Edit};
+use crate::prelude;
+use crate::lex;
+use crate::lint;
+use crate::outline::{self, Symbol};
+use crate::typeck::{TypeChecker, TypeError};
+
+pub const PRIMITIVES: [&str; 13] = [
+    "u8", "u16", "u32", "u64",
+    "i8", "i16", "i32", "i64",
+    "f32", "f64",
+    "bool", "char", "str",
+];
+
+pub struct Analysis {
+    pub text: String,
+    pub lines: LineIndex,
+    pub syntax: SyntaxNode,
+    pub tokens: Vec<Token>,
+    pub tree: ASTree,
+    pub parse_errors: Vec<ParseError>,
+    pub type_errors: Vec<TypeError>,
+    pub checker: TypeChecker,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub message: String,
+    pub severity: Severity,
+    pub fix: Option<Fix>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CompletionKind {
+    Keyword,
+    Type,
+    Function,
+    Variable,
+}
+
+impl Analysis {
+    pub fn new(text: String) -> Self {
+        let (syntax, parse_errors) = Parser::parse_syntax(&text);
+        Self::from_syntax(text, syntax, parse_errors)
+    }
+
+    // Only reparses whatever the edit touched, everything else is checked again from scratch.
+    pub fn edit(&self, edit: &Edit) -> Self {
+        let (syntax, parse_errors) = reparse(&self.syntax, &self.parse_errors, edit);
+        Self::from_syntax(syntax.to_string(), syntax, parse_errors)
+    }
+
+    fn from_syntax(text: String, syntax: SyntaxNode, parse_errors: Vec<ParseError>) -> Self {
+        let tokens = syntax
+            .tokens()
+            .iter()
+            .filter_map(|token| {
+                let span = token.span();
+                Some(Token::new(token.token_kind()?, span.start, span.end))
+            })
+            .collect();
+
+        // Statements that didn't parse are left out, but everything around them still gets
+        // checked so that a half-written file keeps working.
+        let mut tree = lower(&syntax);
+
+        let mut checker = TypeChecker::recording();
+        // While recording, errors are collected rather than returned.
+        let _ = checker.check_tree(&mut tree);
+        let type_errors = std::mem::take(&mut checker.errors);
+
+        Self {
+            lines: LineIndex::new(&text),
+            text,
+            syntax,
+            tokens,
+            tree,
+            parse_errors,
+            type_errors,
+            checker,
+        }
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self.parse_errors
+            .iter()
+            .map(|err| Diagnostic {
+                span: err.span(),
+                message: err.to_string(),
+                severity: Severity::Error,
+                fix: err.fix().cloned(),
+            })
+            .collect();
+
+        for err in &self.type_errors {
+            diagnostics.push(Diagnostic {
+                span: err.span.unwrap_or_default(),
+                message: err.to_string(),
+                severity: Severity::Error,
+                fix: err.fix.as_deref().cloned(),
+            });
+        }
+        for lint in lint::identifiers(&self.text) {
+            diagnostics.push(Diagnostic {
+                span: lint.span,
+                message: lint.to_string(),
+                severity: Severity::Warning,
+                fix: None,
+            });
+        }
+        diagnostics
+    }
+
+    pub fn hover(&self, offset: usize) -> Option<String> {
+        if let Some(reference) = self.reference_at(offset) {
+            return Some(format!("{}: {}", reference.name, reference.ty));
+        }
+
+        let name = self.identifier_at(offset)?;
+        if let Some(item) = self.checker.structs.get(&name) {
+            let fields: Vec<String> = item.fields
+                .iter()
+                .map(|field| format!("{}: {}", field.name, field.param_type))
+                .collect();
+            let generics = generic_params(&item.generics);
+            return Some(format!("struct {}{} {{ {} }}", item.name, generics, fields.join(", ")));
+        }
+        if let Some(item) = self.checker.enums.get(&name) {
+            let generics = generic_params(&item.generics);
+            return Some(format!("enum {}{} {{ {} }}", item.name, generics, join_names(&item.variants, " ")));
+        }
+        None
+    }
+
+    pub fn definition(&self, offset: usize) -> Option<Span> {
+        if let Some(reference) = self.reference_at(offset) {
+            // Native functions aren't declared anywhere there'd be something to go to.
+            let native = reference.definition == Span::default();
+            return (!native).then_some(reference.definition);
+        }
+
+        // Types aren't expressions so the type checker doesn't record them, but they're all
+        // declared at the top level so looking them up by name is enough.
+        let name = self.identifier_at(offset)?;
+        let structs = self.checker.structs.get(&name).map(|item| item.name_span);
+        let enums = || self.checker.enums.get(&name).map(|item| item.name_span);
+        let traits = || self.checker.traits.get(&name).map(|item| item.name_span);
+        structs.or_else(enums).or_else(traits)
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        outline::symbols(&self.tree)
+    }
+
+    // Keywords, types, and every name declared before `offset`.
+    pub fn completions(&self, offset: usize) -> Vec<(String, CompletionKind)> {
+        let mut completions: Vec<(String, CompletionKind)> = Vec::new();
+        let mut push = |name: &str, kind| {
+            if !completions.iter().any(|(existing, _)| existing == name) {
+                completions.push((name.into(), kind));
+            }
+        };
+
+        for keyword in KEYWORDS {
+            push(keyword, CompletionKind::Keyword);
+        }
+        for primitive in PRIMITIVES {
+            push(primitive, CompletionKind::Type);
+        }
+        for name in self.checker.structs.keys().chain(self.checker.enums.keys()) {
+            push(&name.as_str(), CompletionKind::Type);
+        }
+        for function in prelude::functions() {
+            push(&function.name.as_str(), CompletionKind::Function);
+        }
+
+        let definitions = self.checker.references
+            .iter()
+            .filter(|reference| reference.span == reference.definition);
+        for reference in definitions {
+            let kind = match reference.ty {
+                Type::Fn { .. } => CompletionKind::Function,
+                _ => CompletionKind::Variable,
+            };
+            if kind == CompletionKind::Function || reference.definition.start < offset {
+                push(&reference.name.as_str(), kind);
+            }
+        }
+        completions
+    }
+
+    // The smallest recorded reference around `offset`.
+    fn reference_at(&self, offset: usize) -> Option<&crate::typeck::Reference> {
+        self.checker.references
+            .iter()
+            .filter(|reference| reference.span.contains(offset))
+            .min_by_key(|reference| reference.span.end - reference.span.start)
+    }
+
+    fn identifier_at(&self, offset: usize) -> Option<ast::Symbol> {
+        let tok = self.tokens
+            .iter()
+            .find(|tok| tok.kind == TokenKind::Identifier && tok.span().contains(offset))?;
+        Some(ast::Symbol::intern(&lex::normalize_identifier(&self.text[tok.start..tok.end])))
+    }
+}
+
+// Converts between byte offsets and the line/column positions editors use. Columns are counted
+// in UTF-16 code units since that's what the protocol asks for.
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { line_starts }
+    }
+
+    pub fn position(&self, text: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let end = offset.min(text.len());
+        let column = text.get(start..end).map_or(0, |s| s.encode_utf16().count());
+        (line, column)
+    }
+
+    pub fn offset(&self, text: &str, line: usize, column: usize) -> usize {
+        let Some(&start) = self.line_starts.get(line) else { return text.len() };
+
+        let mut units = 0;
+        for (i, c) in text[start..].char_indices() {
+            if units >= column || c == '\n' {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        text.len()
+    }
+}
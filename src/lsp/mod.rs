@@ -0,0 +1,428 @@
+// A language server that talks JSON-RPC over stdio.
+
+pub mod json;
+pub mod analysis;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use json::Json;
+use analysis::{Analysis, CompletionKind, Severity};
+
+use crate::ast::{self, Span};
+use crate::outline::{self, Symbol, SymbolKind};
+use crate::syntax::reparse::Edit;
+
+// See https://microsoft.github.io/language-server-protocol/specification
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_REQUEST: i64 = -32600;
+
+// How many names can be interned before they're all forgotten and every document is analysed again.
+const MAX_SYMBOLS: usize = 100_000;
+
+pub fn run() -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    serve(&mut stdin.lock(), &mut stdout.lock())
+}
+
+// Handles messages from `reader` until the client sends `exit` or closes the stream.
+pub fn serve(reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<()> {
+    let mut server = Server::default();
+    while let Some(message) = read_message(reader)? {
+        let message = match Json::parse(&message) {
+            Ok(message) => message,
+            // There's no id to respond to if the message can't even be read.
+            Err(_) => continue,
+        };
+
+        for response in server.handle(&message) {
+            write_message(writer, &response)?;
+        }
+        if server.exit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// The body of the next message, or `None` once the stream ends. A message that can't be read is
+// logged and skipped, since one bad message shouldn't take the whole server down with it.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            // Whatever was left of a message that got skipped can come right before the header.
+            if let Some((_, value)) = header.split_once("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+
+        let Some(length) = length else {
+            eprintln!("skipping a message without a valid Content-Length header");
+            continue;
+        };
+
+        let mut body = vec![0; length];
+        match reader.read_exact(&mut body) {
+            Ok(()) => (),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        match String::from_utf8(body) {
+            Ok(body) => return Ok(Some(body)),
+            Err(err) => eprintln!("skipping a message that isn't UTF-8: {err}"),
+        }
+    }
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+pub struct Server {
+    documents: HashMap<String, Analysis>,
+    // Every name in the documents is interned here rather than with everyone else's.
+    names: ast::Names,
+    // How many of them there can be before they're all forgotten.
+    max_names: usize,
+    shutdown: bool,
+    pub exit: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new(MAX_SYMBOLS)
+    }
+}
+
+impl Server {
+    pub fn new(max_names: usize) -> Self {
+        Self { documents: HashMap::new(), names: ast::Names::new(), max_names, shutdown: false, exit: false }
+    }
+
+    // Returns every message that should be sent back, responses and notifications alike.
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let _names = self.names.enter();
+        self.handle_message(message)
+    }
+
+    fn handle_message(&mut self, message: &Json) -> Vec<Json> {
+        let id = message.get("id");
+        let params = message.get("params");
+        let Some(method) = message.get("method").as_str() else { return Vec::new() };
+
+        // Requests have an id and need a response, notifications don't.
+        let is_request = !id.is_null();
+
+        if self.shutdown && method != "exit" && is_request {
+            return vec![error_response(id, INVALID_REQUEST, "the server is shutting down")];
+        }
+
+        let result = match method {
+            "initialize" => capabilities(),
+
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            }
+
+            "exit" => {
+                self.exit = true;
+                return Vec::new();
+            }
+
+            "textDocument/didOpen" => {
+                let document = params.get("textDocument");
+                let text = document.get("text").as_str().unwrap_or_default();
+                return self.update(document.get("uri"), Analysis::new(text.into()));
+            }
+
+            "textDocument/didChange" => {
+                let uri = params.get("textDocument").get("uri");
+                let Some(mut analysis) = uri.as_str().and_then(|uri| self.documents.remove(uri)) else {
+                    return Vec::new();
+                };
+
+                // Changes come one after the other, each one relative to the text the previous one
+                // left behind. Ones without a range replace the whole document.
+                let changes = params.get("contentChanges").as_array().unwrap_or_default();
+                for change in changes {
+                    let text = change.get("text").as_str().unwrap_or_default();
+                    analysis = match change.get("range") {
+                        Json::Null => Analysis::new(text.into()),
+                        range => {
+                            let start = position_offset(&analysis, range.get("start"));
+                            let end = position_offset(&analysis, range.get("end"));
+                            analysis.edit(&Edit::new(Span::new(start, end.max(start)), text))
+                        }
+                    };
+                }
+                return self.update(uri, analysis);
+            }
+
+            "textDocument/didClose" => {
+                let uri = params.get("textDocument").get("uri");
+                if let Some(uri) = uri.as_str() {
+                    self.documents.remove(uri);
+                }
+                return vec![publish_diagnostics(uri, Vec::new())];
+            }
+
+            "textDocument/hover" => self.with_position(params, |analysis, offset| {
+                let Some(contents) = analysis.hover(offset) else { return Json::Null };
+                Json::object([
+                    ("contents", Json::object([
+                        ("kind", "markdown".into()),
+                        ("value", format!("```alisa\n{contents}\n```").into()),
+                    ])),
+                ])
+            }),
+
+            "textDocument/definition" => {
+                let uri = params.get("textDocument").get("uri").clone();
+                self.with_position(params, |analysis, offset| {
+                    let Some(span) = analysis.definition(offset) else { return Json::Null };
+                    Json::object([
+                        ("uri", uri),
+                        ("range", range(analysis, span)),
+                    ])
+                })
+            }
+
+            "textDocument/documentSymbol" => {
+                let uri = params.get("textDocument").get("uri").as_str().unwrap_or_default();
+                match self.documents.get(uri) {
+                    Some(analysis) => {
+                        let symbols = analysis.symbols().iter().map(|symbol| document_symbol(analysis, symbol)).collect();
+                        Json::Array(symbols)
+                    }
+                    None => Json::Null,
+                }
+            }
+
+            // Symbols from every open document, searched by name.
+            "workspace/symbol" => {
+                let query = params.get("query").as_str().unwrap_or_default();
+                let mut found = Vec::new();
+                for (uri, analysis) in &self.documents {
+                    let symbols = analysis.symbols();
+                    for (symbol, container) in outline::search(&symbols, query) {
+                        found.push(symbol_information(analysis, uri, symbol, container));
+                    }
+                }
+                Json::Array(found)
+            }
+
+            "textDocument/completion" => self.with_position(params, |analysis, offset| {
+                let items = analysis.completions(offset).into_iter().map(|(label, kind)| {
+                    // See CompletionItemKind in the specification.
+                    let kind: usize = match kind {
+                        CompletionKind::Function => 3,
+                        CompletionKind::Variable => 6,
+                        CompletionKind::Keyword => 14,
+                        CompletionKind::Type => 22,
+                    };
+                    Json::object([("label", label.into()), ("kind", kind.into())])
+                }).collect();
+                Json::Array(items)
+            }),
+
+            "textDocument/codeAction" => {
+                let uri = params.get("textDocument").get("uri").as_str().unwrap_or_default();
+                match self.documents.get(uri) {
+                    Some(analysis) => code_actions(analysis, uri, params.get("range")),
+                    None => Json::Null,
+                }
+            }
+
+            _ if is_request => {
+                return vec![error_response(id, METHOD_NOT_FOUND, &format!("unknown method `{method}`"))];
+            }
+
+            // Notifications we don't care about are just ignored.
+            _ => return Vec::new(),
+        };
+
+        vec![Json::object([
+            ("jsonrpc", "2.0".into()),
+            ("id", id.clone()),
+            ("result", result),
+        ])]
+    }
+
+    fn update(&mut self, uri: &Json, analysis: Analysis) -> Vec<Json> {
+        let Some(key) = uri.as_str() else { return Vec::new() };
+
+        let diagnostics = analysis.diagnostics().into_iter().map(|diagnostic| {
+            Json::object([
+                ("range", range(&analysis, diagnostic.span)),
+                ("severity", match diagnostic.severity {
+                    Severity::Error => 1.into(),
+                    Severity::Warning => 2.into(),
+                }),
+                ("source", "alisa".into()),
+                ("message", diagnostic.message.into()),
+            ])
+        }).collect();
+
+        self.documents.insert(key.into(), analysis);
+        if self.names.count() > self.max_names {
+            self.forget_names();
+        }
+        vec![publish_diagnostics(uri, diagnostics)]
+    }
+
+    // Every name that's been typed so far stays interned, even the half-written ones, so over a
+    // long session they'd pile up. They're dropped by starting over with new names, which means
+    // analysing every document again, since they're all that's kept between messages.
+    fn forget_names(&mut self) {
+        let texts: Vec<(String, String)> = self.documents
+            .drain()
+            .map(|(uri, analysis)| (uri, analysis.text))
+            .collect();
+        self.names = ast::Names::new();
+        let _names = self.names.enter();
+        for (uri, text) in texts {
+            self.documents.insert(uri, Analysis::new(text));
+        }
+    }
+
+    // Finds the document and offset a `TextDocumentPositionParams` points to.
+    fn with_position(&self, params: &Json, f: impl FnOnce(&Analysis, usize) -> Json) -> Json {
+        let uri = params.get("textDocument").get("uri").as_str().unwrap_or_default();
+        let Some(analysis) = self.documents.get(uri) else { return Json::Null };
+        f(analysis, position_offset(analysis, params.get("position")))
+    }
+}
+
+// The byte offset a `Position` points to.
+fn position_offset(analysis: &Analysis, position: &Json) -> usize {
+    let line = position.get("line").as_u64().unwrap_or_default() as usize;
+    let column = position.get("character").as_u64().unwrap_or_default() as usize;
+    analysis.lines.offset(&analysis.text, line, column)
+}
+
+fn capabilities() -> Json {
+    Json::object([
+        ("capabilities", Json::object([
+            // Incremental document sync.
+            ("textDocumentSync", 2.into()),
+            ("hoverProvider", true.into()),
+            ("definitionProvider", true.into()),
+            ("documentSymbolProvider", true.into()),
+            ("completionProvider", Json::object([])),
+            ("codeActionProvider", true.into()),
+            ("workspaceSymbolProvider", true.into()),
+        ])),
+        ("serverInfo", Json::object([("name", "alisa".into())])),
+    ])
+}
+
+fn publish_diagnostics(uri: &Json, diagnostics: Vec<Json>) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/publishDiagnostics".into()),
+        ("params", Json::object([
+            ("uri", uri.clone()),
+            ("diagnostics", Json::Array(diagnostics)),
+        ])),
+    ])
+}
+
+fn error_response(id: &Json, code: i64, message: &str) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("id", id.clone()),
+        ("error", Json::object([
+            ("code", Json::Number(code as f64)),
+            ("message", message.into()),
+        ])),
+    ])
+}
+
+fn range(analysis: &Analysis, span: Span) -> Json {
+    let position = |offset| {
+        let (line, character) = analysis.lines.position(&analysis.text, offset);
+        Json::object([("line", line.into()), ("character", character.into())])
+    };
+    Json::object([("start", position(span.start)), ("end", position(span.end))])
+}
+
+// Quick fixes for the diagnostics in `range` that have one.
+fn code_actions(analysis: &Analysis, uri: &str, range_json: &Json) -> Json {
+    let start = position_offset(analysis, range_json.get("start"));
+    let end = position_offset(analysis, range_json.get("end"));
+
+    let actions = analysis.diagnostics().into_iter().filter_map(|diagnostic| {
+        if diagnostic.span.end < start || end < diagnostic.span.start { return None }
+        let fix = diagnostic.fix?;
+        let edit = Json::object([
+            ("range", range(analysis, fix.edit.span)),
+            ("newText", fix.edit.insert.into()),
+        ]);
+        Some(Json::object([
+            ("title", fix.message.into()),
+            ("kind", "quickfix".into()),
+            ("edit", Json::object([
+                ("changes", Json::Object(vec![(uri.into(), Json::Array(vec![edit]))])),
+            ])),
+        ]))
+    }).collect();
+    Json::Array(actions)
+}
+
+// See SymbolKind in the specification.
+fn symbol_kind(kind: &SymbolKind) -> usize {
+    match kind {
+        SymbolKind::Method => 6,
+        SymbolKind::Field => 8,
+        SymbolKind::Enum => 10,
+        SymbolKind::Trait => 11,
+        SymbolKind::Function => 12,
+        SymbolKind::Impl => 19,
+        SymbolKind::Struct => 23,
+    }
+}
+
+fn symbol_information(analysis: &Analysis, uri: &str, symbol: &Symbol, container: Option<&str>) -> Json {
+    let mut information = Json::object([
+        ("name", symbol.name.as_str().into()),
+        ("kind", symbol_kind(&symbol.kind).into()),
+        ("location", Json::object([
+            ("uri", uri.into()),
+            ("range", range(analysis, symbol.span)),
+        ])),
+    ]);
+    if let (Json::Object(fields), Some(container)) = (&mut information, container) {
+        fields.push(("containerName".into(), container.into()));
+    }
+    information
+}
+
+fn document_symbol(analysis: &Analysis, symbol: &Symbol) -> Json {
+    let kind = symbol_kind(&symbol.kind);
+    let children = symbol.children.iter().map(|child| document_symbol(analysis, child)).collect();
+    Json::object([
+        ("name", symbol.name.clone().into()),
+        ("detail", symbol.detail.clone().into()),
+        ("kind", kind.into()),
+        ("range", range(analysis, symbol.span)),
+        ("selectionRange", range(analysis, symbol.name_span)),
+        ("children", Json::Array(children)),
+    ])
+}
+
+#[cfg(test)]
+mod tests;
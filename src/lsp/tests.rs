@@ -0,0 +1,307 @@
+use std::io::Cursor;
+
+use super::*;
+
+const URI: &str = "file:///test.alisa";
+
+const SOURCE: &str = "\
+struct Point { x: i32, y: i32 }
+fn double(n: i32) -> i32 { n * 2 }
+fn main(p: Point) -> i32 {
+    let a = double(4);
+    a + 1
+}
+";
+
+fn request(id: usize, method: &str, params: Json) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("id", id.into()),
+        ("method", method.into()),
+        ("params", params),
+    ])
+}
+
+fn notification(method: &str, params: Json) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("method", method.into()),
+        ("params", params),
+    ])
+}
+
+fn open(text: &str) -> Json {
+    notification("textDocument/didOpen", Json::object([
+        ("textDocument", Json::object([
+            ("uri", URI.into()),
+            ("languageId", "alisa".into()),
+            ("version", 1.into()),
+            ("text", text.into()),
+        ])),
+    ]))
+}
+
+fn position(line: usize, character: usize) -> Json {
+    Json::object([
+        ("textDocument", Json::object([("uri", URI.into())])),
+        ("position", Json::object([("line", line.into()), ("character", character.into())])),
+    ])
+}
+
+// Runs the server over a script of messages and returns everything it sent back.
+fn run_script(messages: &[Json]) -> Vec<Json> {
+    let mut input = Vec::new();
+    for message in messages {
+        write_message(&mut input, message).unwrap();
+    }
+
+    let mut output = Vec::new();
+    serve(&mut Cursor::new(input), &mut output).unwrap();
+
+    let mut output = Cursor::new(output);
+    let mut responses = Vec::new();
+    while let Some(message) = read_message(&mut output).unwrap() {
+        responses.push(Json::parse(&message).unwrap());
+    }
+    responses
+}
+
+fn response(responses: &[Json], id: usize) -> &Json {
+    let id = Json::from(id);
+    let response = responses.iter().find(|response| *response.get("id") == id);
+    response.unwrap().get("result")
+}
+
+#[test]
+fn json_round_trip() {
+    let text = r#"{"a":[1,2.5,-3],"b":"\"esc\"\né😀","c":null,"d":true}"#;
+    let value = Json::parse(text).unwrap();
+    assert_eq!(value.get("b").as_str(), Some("\"esc\"\n\u{e9}\u{1F600}"));
+    assert_eq!(Json::parse(&value.to_string()).unwrap(), value);
+    assert!(Json::parse("{\"a\":}").is_err());
+}
+
+#[test]
+fn line_index() {
+    let text = "ab\n\u{1F600}c\n";
+    let lines = analysis::LineIndex::new(text);
+    assert_eq!(lines.position(text, 4), (1, 0));
+    assert_eq!(lines.position(text, 7), (1, 2));
+    assert_eq!(lines.offset(text, 1, 2), 7);
+    assert_eq!(lines.offset(text, 0, 10), 2);
+}
+
+#[test]
+fn lifecycle() {
+    let responses = run_script(&[
+        request(1, "initialize", Json::object([])),
+        request(2, "shutdown", Json::Null),
+        notification("exit", Json::Null),
+        // Never gets read since the server already exited.
+        request(3, "initialize", Json::object([])),
+    ]);
+    assert_eq!(responses.len(), 2);
+    assert_eq!(response(&responses, 1).get("capabilities").get("hoverProvider"), &Json::Bool(true));
+    assert!(response(&responses, 2).is_null());
+}
+
+#[test]
+fn bad_messages() {
+    // Neither of the first two can be read, but the request after them still gets a response.
+    let mut input = b"Content-Length: many\r\n\r\n{}Content-Type: json\r\n\r\n".to_vec();
+    write_message(&mut input, &request(1, "initialize", Json::object([]))).unwrap();
+
+    let mut output = Vec::new();
+    serve(&mut Cursor::new(input), &mut output).unwrap();
+    let message = read_message(&mut Cursor::new(output)).unwrap().unwrap();
+    assert_eq!(Json::parse(&message).unwrap().get("id"), &Json::from(1));
+}
+
+#[test]
+fn diagnostics() {
+    let responses = run_script(&[open("let a: bool = 1;\nlet b = `;")]);
+    let diagnostics = responses[0].get("params").get("diagnostics").as_array().unwrap();
+    assert_eq!(diagnostics.len(), 2);
+
+    let starts: Vec<&Json> = diagnostics.iter().map(|d| d.get("range").get("start")).collect();
+    assert_eq!(starts[0].get("line").as_u64(), Some(1));
+    assert_eq!(starts[0].get("character").as_u64(), Some(8));
+    assert_eq!(starts[1].get("line").as_u64(), Some(0));
+    assert_eq!(starts[1].get("character").as_u64(), Some(14));
+
+    // Lints are only warnings.
+    let responses = run_script(&[open("let nаme = 1;")]);
+    let diagnostics = responses[0].get("params").get("diagnostics").as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].get("severity").as_u64(), Some(2));
+    assert_eq!(diagnostics[0].get("message").as_str(), Some("identifier `nаme` mixes scripts"));
+}
+
+#[test]
+fn code_actions() {
+    let at = |line: usize, character: usize| position(line, character).get("position").clone();
+    let responses = run_script(&[
+        open("let count = 1;\nlet b = coutn"),
+        request(1, "textDocument/codeAction", Json::object([
+            ("textDocument", Json::object([("uri", URI.into())])),
+            ("range", Json::object([("start", at(1, 0)), ("end", at(1, 13))])),
+        ])),
+    ]);
+    let actions = response(&responses, 1).as_array().unwrap();
+    let titles: Vec<&str> = actions.iter().filter_map(|action| action.get("title").as_str()).collect();
+    assert_eq!(titles, ["insert `;`"]);
+
+    let edits = actions[0].get("edit").get("changes").get(URI).as_array().unwrap();
+    assert_eq!(edits[0].get("newText").as_str(), Some(";"));
+    assert_eq!(edits[0].get("range").get("start").get("character").as_u64(), Some(13));
+}
+
+#[test]
+fn hover_and_definition() {
+    let responses = run_script(&[
+        open(SOURCE),
+        // `a` in `a + 1`
+        request(1, "textDocument/hover", position(4, 4)),
+        request(2, "textDocument/definition", position(4, 4)),
+        // `double` in `double(4)`
+        request(3, "textDocument/hover", position(3, 13)),
+        // `Point` in `p: Point`
+        request(4, "textDocument/definition", position(2, 13)),
+    ]);
+
+    let hover = response(&responses, 1).get("contents").get("value").as_str();
+    assert_eq!(hover, Some("```alisa\na: i32\n```"));
+
+    let definition = response(&responses, 2).get("range").get("start");
+    assert_eq!((definition.get("line").as_u64(), definition.get("character").as_u64()), (Some(3), Some(8)));
+
+    let hover = response(&responses, 3).get("contents").get("value").as_str();
+    assert_eq!(hover, Some("```alisa\ndouble: fn(i32) -> i32\n```"));
+
+    let definition = response(&responses, 4).get("range").get("start");
+    assert_eq!((definition.get("line").as_u64(), definition.get("character").as_u64()), (Some(0), Some(7)));
+}
+
+#[test]
+fn symbols_and_completion() {
+    let responses = run_script(&[
+        open(SOURCE),
+        request(1, "textDocument/documentSymbol", Json::object([
+            ("textDocument", Json::object([("uri", URI.into())])),
+        ])),
+        request(2, "textDocument/completion", position(4, 4)),
+    ]);
+
+    let symbols = response(&responses, 1).as_array().unwrap();
+    let names: Vec<_> = symbols.iter().map(|symbol| symbol.get("name").as_str().unwrap()).collect();
+    assert_eq!(names, ["Point", "double", "main"]);
+    assert_eq!(symbols[0].get("children").as_array().unwrap().len(), 2);
+
+    let items = response(&responses, 2).as_array().unwrap();
+    let labels: Vec<_> = items.iter().map(|item| item.get("label").as_str().unwrap()).collect();
+    for expected in ["fn", "i32", "Point", "double", "p", "a", "println", "str::len"] {
+        assert!(labels.contains(&expected), "missing {expected}");
+    }
+}
+
+#[test]
+fn workspace_symbols() {
+    let responses = run_script(&[
+        open(SOURCE),
+        request(1, "workspace/symbol", Json::object([("query", "o".into())])),
+    ]);
+    let symbols = response(&responses, 1).as_array().unwrap();
+    let names: Vec<_> = symbols.iter().map(|symbol| symbol.get("name").as_str().unwrap()).collect();
+    assert_eq!(names, ["Point", "double"]);
+    assert_eq!(symbols[0].get("location").get("uri").as_str(), Some(URI));
+    assert_eq!(symbols[0].get("kind").as_u64(), Some(23));
+}
+
+#[test]
+fn partial_documents() {
+    // The broken `fn` at the end shouldn't stop the rest from being analyzed.
+    let responses = run_script(&[
+        open("fn one() -> i32 { 1 }\nlet x = one();\nfn broken("),
+        request(1, "textDocument/hover", position(1, 4)),
+    ]);
+    let diagnostics = responses[0].get("params").get("diagnostics").as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+
+    let hover = response(&responses, 1).get("contents").get("value").as_str();
+    assert_eq!(hover, Some("```alisa\nx: i32\n```"));
+
+    // Neither do type errors, and each of them gets reported.
+    let responses = run_script(&[
+        open("fn a() -> i32 { true }\nfn b() -> bool { 1 }\nlet x = a();"),
+        request(1, "textDocument/hover", position(2, 4)),
+        request(2, "textDocument/definition", position(2, 8)),
+    ]);
+    let diagnostics = responses[0].get("params").get("diagnostics").as_array().unwrap();
+    assert_eq!(diagnostics.len(), 2);
+
+    let hover = response(&responses, 1).get("contents").get("value").as_str();
+    assert_eq!(hover, Some("```alisa\nx: i32\n```"));
+    let definition = response(&responses, 2).get("range").get("start");
+    assert_eq!((definition.get("line").as_u64(), definition.get("character").as_u64()), (Some(0), Some(3)));
+}
+
+#[test]
+fn incremental_changes() {
+    let change = |start: (usize, usize), end: (usize, usize), text: &str| {
+        let position = |(line, character): (usize, usize)| {
+            Json::object([("line", line.into()), ("character", character.into())])
+        };
+        Json::object([
+            ("range", Json::object([("start", position(start)), ("end", position(end))])),
+            ("text", text.into()),
+        ])
+    };
+
+    let responses = run_script(&[
+        open(SOURCE),
+        notification("textDocument/didChange", Json::object([
+            ("textDocument", Json::object([("uri", URI.into()), ("version", 2.into())])),
+            ("contentChanges", Json::Array(vec![
+                // `a + 1` to `a + true`, then `double` to `triple` everywhere.
+                change((4, 8), (4, 9), "true"),
+                change((3, 12), (3, 18), "triple"),
+                change((1, 3), (1, 9), "triple"),
+            ])),
+        ])),
+        request(1, "textDocument/hover", position(3, 13)),
+    ]);
+
+    let diagnostics = responses[1].get("params").get("diagnostics").as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    let start = diagnostics[0].get("range").get("start");
+    assert_eq!((start.get("line").as_u64(), start.get("character").as_u64()), (Some(4), Some(8)));
+
+    let hover = response(&responses, 1).get("contents").get("value").as_str();
+    assert_eq!(hover, Some("```alisa\ntriple: fn(i32) -> i32\n```"));
+}
+
+#[test]
+fn forgetting_names() {
+    let mut server = Server::new(1_000);
+    let stale = {
+        let _names = server.names.enter();
+        ast::Symbol::intern("stale")
+    };
+
+    // Under the limit, nothing is forgotten.
+    server.handle(&open(SOURCE));
+    assert_eq!(&*stale.as_str(), "stale");
+
+    // Over it, every name goes and the documents are analysed again from their text.
+    server.max_names = server.names.count();
+    server.handle(&open(&SOURCE.replace("double", "triple")));
+    assert!(std::panic::catch_unwind(|| stale.as_str()).is_err());
+    assert!(server.names.count() <= server.max_names);
+
+    let hover = server.handle(&request(1, "textDocument/hover", position(3, 13)));
+    let hover = response(&hover, 1).get("contents").get("value").as_str();
+    assert_eq!(hover, Some("```alisa\ntriple: fn(i32) -> i32\n```"));
+    let definition = server.handle(&request(2, "textDocument/definition", position(4, 4)));
+    let start = response(&definition, 2).get("range").get("start");
+    assert_eq!((start.get("line").as_u64(), start.get("character").as_u64()), (Some(3), Some(8)));
+}
@@ -1,37 +1,119 @@
-mod lex;
 #[allow(unused_imports)]
-use lex::*;
+use alisalang::lex::*;
 
-
-mod parse;
-use parse::Parser;
 #[allow(unused_imports)]
-use parse::stream::TokenStream;
+use alisalang::parse::stream::TokenStream;
 
-mod ast;
+use alisalang::{repl, lsp, format, module, fix};
 
-use std::fs::File;
 use std::io::prelude::*;
+use std::path::Path;
 use std::time::Instant;
 
 fn main() -> std::io::Result<()> {
     use std::env;
     env::set_var("RUST_BACKTRACE", "1");
     env::set_var("RUSTFLAGS", "-Awarnings");
-    let mut file = File::open("foo.rs")?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    /*
-    let mut lex = lexer::Lexer::new(&contents);
-    println!("{:#?}", lex);
-    */
-
-    // println!("{:#?}", TokenStream::new(&contents));
-    // println!("{}", contents);
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("repl") => return repl::Repl::new().run(),
+        Some("lsp") => return lsp::run(),
+        Some("fmt") => return fmt(&args[1..]),
+        Some("--fix") => return fix(&args[1..]),
+        _ => (),
+    }
+
+    // The file the program starts in, along with every module it imports.
+    let path = args.first().map(String::as_str).unwrap_or("foo.alisa");
     let t = Instant::now();
-    let tree = Parser::parse(&contents);
+    let linked = module::ModuleGraph::load(Path::new(path)).and_then(|graph| graph.link());
     let time = Instant::now() - t;
+
+    // TODO: add actual error reporting.
+    let tree = match linked {
+        Ok(tree) => tree,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
     println!("{:?}", time);
-    println!("{:#?}", tree);
+    print!("{tree}");
+    Ok(())
+}
+
+// `alisa fmt [--check] [files...]`. Formats stdin to stdout if no files are given.
+fn fmt(args: &[String]) -> std::io::Result<()> {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+
+    if paths.is_empty() {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents)?;
+        match format::format(&contents) {
+            Ok(formatted) if check && formatted != contents => std::process::exit(1),
+            Ok(formatted) if !check => print!("{formatted}"),
+            Ok(_) => (),
+            Err(errors) => {
+                for err in &errors {
+                    eprintln!("error: {err}");
+                }
+                std::process::exit(2);
+            }
+        }
+        return Ok(());
+    }
+
+    let mut unformatted = false;
+    for path in paths {
+        let contents = std::fs::read_to_string(path)?;
+        let formatted = match format::format(&contents) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                for err in &errors {
+                    eprintln!("error: {path}: {err}");
+                }
+                std::process::exit(2);
+            }
+        };
+
+        if formatted == contents { continue }
+        match check {
+            true => {
+                println!("{path} is not formatted");
+                unformatted = true;
+            }
+            false => std::fs::write(path, formatted)?,
+        }
+    }
+
+    if unformatted {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// `alisa --fix <files...>`. Applies suggested fixes one at a time, since fixing one error can
+// change what the rest of the file parses as.
+fn fix(paths: &[String]) -> std::io::Result<()> {
+    for path in paths {
+        let mut contents = std::fs::read_to_string(path)?;
+        let original = contents.clone();
+
+        // Enough for any reasonable file, without looping forever on fixes that don't help.
+        for _ in 0..100 {
+            let Some(fix) = fix::fixes(&contents).into_iter().next() else { break };
+            let fixed = fix::apply(&contents, std::slice::from_ref(&fix));
+            if fixed == contents { break }
+            println!("{path}: {}", fix.message);
+            contents = fixed;
+        }
+
+        if contents != original {
+            std::fs::write(path, contents)?;
+        }
+    }
     Ok(())
 }
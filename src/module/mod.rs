@@ -0,0 +1,202 @@
+// Programs split across files. Every file is a module, and `import geom::shapes;` loads the file
+// `geom/shapes.alisa` from next to the importing one. `mod shapes;` in `geom.alisa` loads that same
+// file, from the directory named after the declaring module, like Rust does. The file the program
+// starts in declares its modules next to it instead. The files are loaded into a `ModuleGraph`,
+// which `link()` then turns into a single `ASTree` for the type checker and the interpreter.
+
+mod resolve;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::ast::{ASTree, Span, Statement, Symbol, join_names};
+use crate::parse::{Parser, ParseError};
+
+pub const EXTENSION: &str = "alisa";
+
+pub type ModuleId = usize;
+
+#[derive(Debug)]
+pub struct Module {
+    // The qualified name of the module, like `["geom", "shapes"]`, which is also where it is
+    // relative to the file the program starts in. Empty for that file.
+    pub name: Vec<Symbol>,
+    pub path: PathBuf,
+    pub tree: ASTree,
+    // The modules it imports or declares, by every name they can be referred to with. That's the
+    // last part of the import's path, as well as the whole path.
+    pub imports: HashMap<String, ModuleId>,
+}
+
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    // Every module comes after the modules it imports, so the file the program starts in is last.
+    pub modules: Vec<Module>,
+}
+
+impl ModuleGraph {
+    pub fn load(entry: &Path) -> Result<Self, ModuleError> {
+        Self::load_with(entry, |path| std::fs::read_to_string(path))
+    }
+
+    // Same as `load()`, but with the files read by `read` instead of from the file system.
+    pub fn load_with(entry: &Path, read: impl FnMut(&Path) -> io::Result<String>) -> Result<Self, ModuleError> {
+        let mut loader = Loader { read, graph: ModuleGraph::default(), ids: HashMap::new(), loading: Vec::new() };
+        loader.load(entry.to_path_buf(), Vec::new(), None)?;
+        Ok(loader.graph)
+    }
+}
+
+struct Loader<F> {
+    read: F,
+    graph: ModuleGraph,
+    // Keyed by `file_key()`, so that a file reached through two different paths is one module.
+    ids: HashMap<PathBuf, ModuleId>,
+    // The modules that are still loading their imports, in the order they were imported in.
+    loading: Vec<(PathBuf, Vec<Symbol>)>,
+}
+
+impl<F: FnMut(&Path) -> io::Result<String>> Loader<F> {
+    // `import` is where the module was imported from, which is where errors about it go.
+    fn load(&mut self, path: PathBuf, name: Vec<Symbol>, import: Option<(&Path, Span)>) -> Result<ModuleId, ModuleError> {
+        let path = normalize(&path);
+        let key = file_key(&path);
+        if let Some(&id) = self.ids.get(&key) {
+            return Ok(id);
+        }
+
+        let error = |kind| match import {
+            Some((file, span)) => ModuleError { kind, path: file.to_path_buf(), span: Some(span) },
+            None => ModuleError { kind, path: path.clone(), span: None },
+        };
+
+        if let Some(start) = self.loading.iter().position(|(loading, _)| *loading == key) {
+            let mut cycle: Vec<String> = self.loading[start..].iter().map(|(_, name)| display_name(name)).collect();
+            cycle.push(display_name(&name));
+            return Err(error(ModuleErrorKind::Cycle { cycle }));
+        }
+
+        let source = (self.read)(&path).map_err(|err| {
+            error(ModuleErrorKind::Io { file: path.clone(), message: err.to_string() })
+        })?;
+        let (tree, errors) = Parser::parse(&source);
+        if !errors.is_empty() {
+            return Err(ModuleError { kind: ModuleErrorKind::Parse { errors }, path, span: None });
+        }
+
+        self.loading.push((key.clone(), name.clone()));
+        let mut imports = HashMap::new();
+        for statement in &tree.root {
+            let Statement::Import(import) = statement else { continue };
+
+            // Imports are relative to the importing file, which is in the module's parent. Declared
+            // modules are in the module itself, unless it's the main one.
+            let (mut import_name, mut import_path) = match import.child && !name.is_empty() {
+                true => (name.clone(), path.with_extension("").join(join_names(&import.path, "/"))),
+                false => {
+                    let parent = name[..name.len().saturating_sub(1)].to_vec();
+                    (parent, path.with_file_name(join_names(&import.path, "/")))
+                }
+            };
+            import_name.extend(import.path.iter().cloned());
+            import_path.set_extension(EXTENSION);
+
+            let id = self.load(import_path, import_name, Some((&path, import.span)))?;
+            imports.insert(import.name().to_string(), id);
+            imports.insert(join_names(&import.path, "::"), id);
+        }
+        self.loading.pop();
+
+        let id = self.graph.modules.len();
+        self.graph.modules.push(Module { name, path, tree, imports });
+        self.ids.insert(key, id);
+        Ok(id)
+    }
+}
+
+// Where the file really is, following symlinks. Files that can't be found that way, like the ones
+// `load_with()` is given, are only told apart by their normalized path.
+fn file_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// `path` without any `.`s, and with every `..` taking out the directory before it if there is one.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn display_name(name: &[Symbol]) -> String {
+    match name.is_empty() {
+        true => "the main module".into(),
+        false => join_names(name, "::"),
+    }
+}
+
+#[derive(Debug)]
+pub struct ModuleError {
+    pub kind: ModuleErrorKind,
+    // The file the error is in.
+    pub path: PathBuf,
+    pub span: Option<Span>,
+}
+
+#[derive(Debug)]
+pub enum ModuleErrorKind {
+    Io { file: PathBuf, message: String },
+    Parse { errors: Vec<ParseError> },
+    // The modules that end up importing themselves, starting and ending with the same one.
+    Cycle { cycle: Vec<String> },
+    NestedImport,
+    UnknownModule { name: String },
+    UnknownItem { module: String, name: String },
+    PrivateItem { module: String, name: String },
+}
+
+impl std::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.display();
+        match &self.kind {
+            ModuleErrorKind::Parse { errors } => {
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 { writeln!(f)?; }
+                    write!(f, "{path}: {err}")?;
+                }
+                Ok(())
+            }
+            kind => match self.span {
+                Some(span) => write!(f, "{path}: {kind} at {}..{}", span.start, span.end),
+                None => write!(f, "{path}: {kind}"),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ModuleErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleErrorKind::Io { file, message } => write!(f, "cannot read `{}`: {message}", file.display()),
+            ModuleErrorKind::Parse { errors } => write!(f, "{} parse error(s)", errors.len()),
+            ModuleErrorKind::Cycle { cycle } => write!(f, "modules import each other: {}", cycle.join(" -> ")),
+            ModuleErrorKind::NestedImport => write!(f, "`import` and `mod` can only be used outside of functions and blocks"),
+            ModuleErrorKind::UnknownModule { name } => write!(f, "cannot find module `{name}`, is it imported?"),
+            ModuleErrorKind::UnknownItem { module, name } => write!(f, "cannot find `{name}` in `{module}`"),
+            ModuleErrorKind::PrivateItem { module, name } => {
+                write!(f, "`{name}` is private to `{module}`, it needs to be `pub` to be used here")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,347 @@
+// Links the modules of a `ModuleGraph` into one `ASTree`. Every item is renamed to its qualified
+// name, like `geom::Point`, and so is everything that refers to one, so the passes after this
+// don't need to know about modules at all. Items of the main module keep their names.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{ASTree, Statement, Expression, ExprId, Exprs, Parameter, Symbol, Type, TupleType, join_names};
+use crate::ast::{BlockExpression, IfExpression, ElseExpression, LitKind, StrPart, Tuple, List, Span};
+
+use crate::prelude;
+
+use super::{Module, ModuleError, ModuleErrorKind, ModuleGraph, ModuleId};
+
+impl ModuleGraph {
+    pub fn link(&self) -> Result<ASTree, ModuleError> {
+        let exports: Vec<HashMap<Symbol, bool>> = self.modules.iter().map(|module| items(&module.tree)).collect();
+
+        let mut statements = Vec::new();
+        let mut exprs = Exprs::new();
+        for (id, module) in self.modules.iter().enumerate() {
+            let mut items: Vec<Statement> = module.tree.root
+                .iter()
+                .filter(|statement| !matches!(statement, Statement::Import(_) | Statement::EOF))
+                .cloned()
+                .collect();
+            exprs.append(&module.tree.exprs, &mut items);
+
+            let mut resolver = Resolver { graph: self, id, module, exports: &exports, exprs: &mut exprs, scopes: Vec::new() };
+            for mut statement in items {
+                resolver.item(&mut statement).map_err(|err| ModuleError {
+                    kind: err.kind,
+                    path: module.path.clone(),
+                    span: err.span.or(Some(statement.span())),
+                })?;
+                statements.push(statement);
+            }
+        }
+        statements.push(Statement::EOF);
+        Ok(ASTree::new(statements, exprs))
+    }
+}
+
+// The names a module declares at the top level, and whether they're public. Top-level `let`s are
+// never public.
+fn items(tree: &ASTree) -> HashMap<Symbol, bool> {
+    let mut items = HashMap::new();
+    for statement in &tree.root {
+        let (name, public) = match statement {
+            Statement::Function(item) => (item.name, item.public),
+            Statement::Struct(item) => (item.name, item.public),
+            Statement::Enum(item) => (item.name, item.public),
+            Statement::Trait(item) => (item.name, item.public),
+            Statement::Let(item) => (item.name, false),
+            _ => continue,
+        };
+        items.insert(name, public);
+    }
+    items
+}
+
+// What went wrong and, if it's known at that point, the span of the name it went wrong at. It's
+// up to whatever the name was in to fill in the span otherwise.
+struct ResolveError {
+    kind: ModuleErrorKind,
+    span: Option<Span>,
+}
+
+impl ResolveError {
+    fn or_span(self, span: Span) -> Self {
+        Self { span: self.span.or(Some(span)), ..self }
+    }
+}
+
+impl From<ModuleErrorKind> for ResolveError {
+    fn from(kind: ModuleErrorKind) -> Self {
+        Self { kind, span: None }
+    }
+}
+
+type ResolveResult<T> = Result<T, ResolveError>;
+
+struct Resolver<'a> {
+    graph: &'a ModuleGraph,
+    id: ModuleId,
+    module: &'a Module,
+    exports: &'a [HashMap<Symbol, bool>],
+    // The expressions of every module linked so far, this one's included.
+    exprs: &'a mut Exprs,
+    // Locals, innermost scope last. These shadow the module's items.
+    scopes: Vec<HashSet<Symbol>>,
+}
+
+impl Resolver<'_> {
+    // A statement at the top level of the module, which declares one of its items.
+    fn item(&mut self, statement: &mut Statement) -> ResolveResult<()> {
+        match statement {
+            Statement::Function(item) => item.name = qualify(self.module, item.name),
+            Statement::Struct(item) => item.name = qualify(self.module, item.name),
+            Statement::Enum(item) => item.name = qualify(self.module, item.name),
+            Statement::Trait(item) => item.name = qualify(self.module, item.name),
+            Statement::Let(item) => {
+                self.expr(item.value)?;
+                if let Some(var_type) = &mut item.var_type {
+                    self.ty(var_type)?;
+                }
+                item.name = qualify(self.module, item.name);
+                return Ok(());
+            }
+            _ => (),
+        }
+        self.statement(statement)
+    }
+
+    fn statement(&mut self, statement: &mut Statement) -> ResolveResult<()> {
+        match statement {
+            Statement::Function(item) => {
+                self.declare(item.name);
+                self.params(&mut item.arguments)?;
+                self.ty(&mut item.return_type)?;
+                self.body(&item.arguments, &mut item.block)
+            }
+
+            Statement::Struct(item) => self.params(&mut item.fields),
+
+            Statement::Enum(_) | Statement::Error { .. } | Statement::EOF => Ok(()),
+
+            Statement::Impl(item) => {
+                if let Some(trait_name) = &mut item.trait_name {
+                    *trait_name = self.name(*trait_name)?;
+                }
+                self.ty(&mut item.self_type)?;
+                for method in &mut item.methods {
+                    self.params(&mut method.arguments)?;
+                    self.ty(&mut method.return_type)?;
+                    self.body(&method.arguments, &mut method.block)?;
+                }
+                Ok(())
+            }
+
+            Statement::Trait(item) => {
+                for method in &mut item.methods {
+                    self.params(&mut method.arguments)?;
+                    self.ty(&mut method.return_type)?;
+                    if let Some(block) = &mut method.block {
+                        self.body(&method.arguments, block)?;
+                    }
+                }
+                Ok(())
+            }
+
+            // Only the loader looks at imports, and it only looks at the top level.
+            Statement::Import(item) => Err(ResolveError::from(ModuleErrorKind::NestedImport).or_span(item.span)),
+
+            Statement::Let(item) => {
+                self.expr(item.value)?;
+                if let Some(var_type) = &mut item.var_type {
+                    self.ty(var_type)?;
+                }
+                self.declare(item.name);
+                Ok(())
+            }
+
+            Statement::Expression { expr, .. } => self.expr(*expr),
+        }
+    }
+
+    fn declare(&mut self, name: Symbol) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name);
+        }
+    }
+
+    // The block of a function or closure, with its arguments in scope.
+    fn body(&mut self, arguments: &[Parameter], block: &mut BlockExpression) -> ResolveResult<()> {
+        self.scopes.push(arguments.iter().map(|argument| argument.name).collect());
+        let result = self.block(block);
+        self.scopes.pop();
+        result
+    }
+
+    fn block(&mut self, block: &mut BlockExpression) -> ResolveResult<()> {
+        self.scopes.push(HashSet::new());
+        let result = self.block_inner(block);
+        self.scopes.pop();
+        result
+    }
+
+    fn block_inner(&mut self, block: &mut BlockExpression) -> ResolveResult<()> {
+        for statement in &mut block.statements {
+            self.statement(statement)?;
+        }
+        match block.expression {
+            Some(expr) => self.expr(expr),
+            None => Ok(()),
+        }
+    }
+
+    // The expression is taken out while it's resolved, and put back even if it doesn't resolve.
+    fn expr(&mut self, expr: ExprId) -> ResolveResult<()> {
+        let mut node = self.exprs.take(expr);
+        let result = self.expr_kind(&mut node);
+        self.exprs.put(expr, node);
+        result
+    }
+
+    fn expr_kind(&mut self, expr: &mut Expression) -> ResolveResult<()> {
+        match expr {
+            Expression::Identifier(ident) => {
+                let is_local = self.scopes.iter().any(|scope| scope.contains(&ident.name));
+                if !is_local {
+                    ident.name = self.name(ident.name).map_err(|err| err.or_span(ident.span))?;
+                }
+                Ok(())
+            }
+
+            Expression::Closure(closure) => {
+                self.params(&mut closure.arguments)?;
+                self.ty(&mut closure.return_type)?;
+                self.body(&closure.arguments, &mut closure.block)
+            }
+
+            Expression::Block(block) => self.block(block),
+
+            Expression::Call(call) => {
+                self.expr(call.function)?;
+                self.each_expr(&call.arguments)
+            }
+
+            Expression::MethodCall(call) => {
+                self.expr(call.receiver)?;
+                self.each_expr(&call.arguments)
+            }
+
+            Expression::Struct(struct_expr) => {
+                let span = struct_expr.name_span;
+                struct_expr.name = self.name(struct_expr.name).map_err(|err| err.or_span(span))?;
+                struct_expr.fields.iter().try_for_each(|field| self.expr(field.value))
+            }
+
+            Expression::Field(field) => self.expr(field.receiver),
+
+            Expression::If(if_expr) => self.if_expr(if_expr),
+
+            Expression::Binary(binary) => {
+                self.expr(binary.lhs)?;
+                self.expr(binary.rhs)
+            }
+
+            Expression::Unary(unary) => self.expr(unary.rhs),
+
+            Expression::Literal(literal) => match &literal.kind {
+                LitKind::Tuple(Tuple(elements)) | LitKind::List(List(elements)) => self.each_expr(elements),
+                LitKind::Interpolated(parts) => parts.iter().try_for_each(|part| match part {
+                    StrPart::Expr(expr) => self.expr(*expr),
+                    StrPart::Text(_) => Ok(()),
+                }),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn each_expr(&mut self, exprs: &[ExprId]) -> ResolveResult<()> {
+        exprs.iter().try_for_each(|&expr| self.expr(expr))
+    }
+
+    fn if_expr(&mut self, if_expr: &mut IfExpression) -> ResolveResult<()> {
+        self.expr(if_expr.condition)?;
+        self.block(&mut if_expr.body)?;
+        match &mut if_expr.else_body {
+            Some(ElseExpression::Else(block)) => self.block(block),
+            Some(ElseExpression::ElseIf(else_if)) => self.expr(*else_if),
+            None => Ok(()),
+        }
+    }
+
+    fn params(&mut self, params: &mut [Parameter]) -> ResolveResult<()> {
+        params.iter_mut().try_for_each(|param| {
+            self.ty(&mut param.param_type).map_err(|err| err.or_span(param.name_span))
+        })
+    }
+
+    // Types aren't shadowed by locals, so the names in them are always items.
+    fn ty(&mut self, ty: &mut Type) -> ResolveResult<()> {
+        match ty {
+            Type::UserDefined { name } => *name = self.name(*name)?,
+            Type::Applied { name, arguments } => {
+                *name = self.name(*name)?;
+                arguments.iter_mut().try_for_each(|argument| self.ty(argument))?;
+            }
+            Type::Tuple(TupleType(types)) => types.iter_mut().try_for_each(|ty| self.ty(ty))?,
+            Type::List(inner) => self.ty(inner)?,
+            Type::Fn { arguments, return_type } => {
+                arguments.iter_mut().try_for_each(|argument| self.ty(argument))?;
+                self.ty(return_type)?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    // What a name that isn't a local refers to, qualified by the module it's declared in.
+    fn name(&self, name: Symbol) -> ResolveResult<Symbol> {
+        let text = name.as_str();
+        let Some((module_name, item)) = text.rsplit_once("::") else {
+            return match self.exports[self.id].contains_key(&name) {
+                true => Ok(qualify(self.module, name)),
+                // Whatever it is, the type checker will report it if it doesn't exist.
+                false => Ok(name),
+            };
+        };
+
+        let Some(&id) = self.module.imports.get(module_name) else {
+            // Native functions like `str::len` look like they're from a module but aren't.
+            if prelude::contains(&text) {
+                return Ok(name);
+            }
+            // Neither do enum variants, like `Color::Red` or `shapes::Color::Red`.
+            if self.is_item(module_name) {
+                let owner = self.name(Symbol::intern(module_name))?;
+                return Ok(Symbol::intern(&format!("{owner}::{item}")));
+            }
+            return Err(ModuleErrorKind::UnknownModule { name: module_name.into() }.into());
+        };
+        let (module, name) = (module_name.to_string(), item.to_string());
+        let item = Symbol::intern(item);
+        match self.exports[id].get(&item) {
+            Some(true) => Ok(qualify(&self.graph.modules[id], item)),
+            Some(false) => Err(ModuleErrorKind::PrivateItem { module, name }.into()),
+            None => Err(ModuleErrorKind::UnknownItem { module, name }.into()),
+        }
+    }
+
+    // Whether `name` is an item of this module, or of a module it imports if it's a path.
+    fn is_item(&self, name: &str) -> bool {
+        match name.rsplit_once("::") {
+            Some((module_name, _)) => self.module.imports.contains_key(module_name),
+            None => self.exports[self.id].contains_key(&Symbol::intern(name)),
+        }
+    }
+}
+
+fn qualify(module: &Module, name: Symbol) -> Symbol {
+    match module.name.is_empty() {
+        true => name,
+        false => Symbol::intern(&format!("{}::{}", join_names(&module.name, "::"), name)),
+    }
+}
@@ -0,0 +1,176 @@
+use super::*;
+use crate::eval::{Interpreter, value::Value};
+use crate::typeck::TypeChecker;
+
+fn load(files: &[(&str, &str)]) -> Result<ModuleGraph, ModuleError> {
+    let files: HashMap<PathBuf, String> = files
+        .iter()
+        .map(|(path, source)| (PathBuf::from(path), source.to_string()))
+        .collect();
+    ModuleGraph::load_with(Path::new("main.alisa"), |path| {
+        files.get(path).cloned().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    })
+}
+
+fn check_err(files: &[(&str, &str)], expected: impl Fn(&ModuleErrorKind) -> bool) {
+    let err = load(files).and_then(|graph| graph.link()).unwrap_err();
+    assert!(expected(&err.kind), "{err}");
+}
+
+#[test]
+fn multiple_files() {
+    let graph = load(&[
+        ("main.alisa", "
+            import geom;
+            import util::math;
+            fn area(p: geom::Point) -> i32 { 0 }
+            let total = math::double(geom::square(5)) + geom::scaled(1);
+        "),
+        ("geom.alisa", "
+            pub struct Point { x: i32, y: i32, }
+            let scale = 3;
+            pub fn square(n: i32) -> i32 { n * two() }
+            pub fn scaled(n: i32) -> i32 { let two = scale; n * two }
+            fn two() -> i32 { 2 }
+        "),
+        ("util/math.alisa", "import twice; pub fn double(n: i32) -> i32 { twice::apply(n) }"),
+        ("util/twice.alisa", "pub fn apply(n: i32) -> i32 { n + n }"),
+    ]).unwrap();
+
+    // Imports come before whatever imports them.
+    let names: Vec<String> = graph.modules.iter().map(|module| join_names(&module.name, "::")).collect();
+    assert_eq!(names, ["geom", "util::twice", "util::math", ""]);
+
+    let mut tree = graph.link().unwrap();
+    TypeChecker::new().check_tree(&mut tree).unwrap();
+
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_tree(&tree).unwrap();
+    assert_eq!(interpreter.global("total"), Some(Value::Int(23)));
+}
+
+#[test]
+fn structs_and_enums() {
+    let graph = load(&[
+        ("main.alisa", "
+            import geom;
+            let p = geom::Point { x: 1, y: geom::Light::Green.brightness() };
+            let total = p.y + geom::origin().x;
+        "),
+        ("geom.alisa", "
+            pub struct Point { x: i32, y: i32 }
+            pub enum Light { Red Green }
+            impl Light {
+                fn brightness(self) -> i32 { if self == Light::Red { 1 } else { 2 } }
+            }
+            pub fn origin() -> Point { Point { x: 10, y: 0 } }
+        "),
+    ]).unwrap();
+
+    let mut tree = graph.link().unwrap();
+    TypeChecker::new().check_tree(&mut tree).unwrap();
+
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_tree(&tree).unwrap();
+    assert_eq!(interpreter.global("total"), Some(Value::Int(12)));
+
+    let hidden = ("geom.alisa", "struct Point { x: i32 } enum Light { Red }");
+    check_err(&[("main.alisa", "import geom; geom::Point { x: 1 };"), hidden], |kind| {
+        matches!(kind, ModuleErrorKind::PrivateItem { name, .. } if name == "Point")
+    });
+    check_err(&[("main.alisa", "import geom; geom::Light::Red;"), hidden], |kind| {
+        matches!(kind, ModuleErrorKind::PrivateItem { name, .. } if name == "Light")
+    });
+}
+
+#[test]
+fn module_errors() {
+    let geom = ("geom.alisa", "pub fn area() -> i32 { secret() } fn secret() -> i32 { 1 }");
+
+    check_err(&[("main.alisa", "import geom; geom::secret();"), geom], |kind| {
+        matches!(kind, ModuleErrorKind::PrivateItem { name, .. } if name == "secret")
+    });
+    check_err(&[("main.alisa", "import geom; geom::volume();"), geom], |kind| {
+        matches!(kind, ModuleErrorKind::UnknownItem { name, .. } if name == "volume")
+    });
+    check_err(&[("main.alisa", "fn f(p: geom::Point) -> void {}")], |kind| {
+        matches!(kind, ModuleErrorKind::UnknownModule { name } if name == "geom")
+    });
+    check_err(&[("main.alisa", "import shapes;")], |kind| matches!(kind, ModuleErrorKind::Io { .. }));
+    check_err(&[("main.alisa", "import geom;"), ("geom.alisa", "fn f( -> {}")], |kind| {
+        matches!(kind, ModuleErrorKind::Parse { .. })
+    });
+    check_err(&[("main.alisa", "fn f() -> void { import geom; }"), geom], |kind| {
+        matches!(kind, ModuleErrorKind::NestedImport)
+    });
+}
+
+#[test]
+fn import_cycles() {
+    let err = load(&[
+        ("main.alisa", "import a;"),
+        ("a.alisa", "import b;"),
+        ("b.alisa", "import a;"),
+    ]).unwrap_err();
+    assert_eq!(err.path, PathBuf::from("b.alisa"));
+    let ModuleErrorKind::Cycle { cycle } = err.kind else { panic!("{err}") };
+    assert_eq!(cycle, ["a", "b", "a"]);
+
+    // Importing the same module twice isn't a cycle.
+    let graph = load(&[
+        ("main.alisa", "import a; import b;"),
+        ("a.alisa", "import b;"),
+        ("b.alisa", ""),
+    ]).unwrap();
+    assert_eq!(graph.modules.len(), 3);
+}
+
+#[test]
+fn module_declarations() {
+    let graph = load(&[
+        ("main.alisa", "mod geom; import geom::shapes; let total = geom::area(2) + shapes::unit();"),
+        ("geom.alisa", "mod shapes; pub fn area(n: i32) -> i32 { n * shapes::unit() }"),
+        ("geom/shapes.alisa", "pub fn unit() -> i32 { 3 }"),
+    ]).unwrap();
+
+    // Declaring a module and importing it loads the same file.
+    let names: Vec<String> = graph.modules.iter().map(|module| join_names(&module.name, "::")).collect();
+    assert_eq!(names, ["geom::shapes", "geom", ""]);
+
+    let mut tree = graph.link().unwrap();
+    TypeChecker::new().check_tree(&mut tree).unwrap();
+
+    let mut interpreter = Interpreter::new();
+    interpreter.eval_tree(&tree).unwrap();
+    assert_eq!(interpreter.global("total"), Some(Value::Int(9)));
+
+    check_err(&[("main.alisa", "fn f() -> void { mod geom; }")], |kind| matches!(kind, ModuleErrorKind::NestedImport));
+}
+
+#[test]
+fn same_file_through_different_paths() {
+    let files = HashMap::from([
+        (PathBuf::from("main.alisa"), "import geom;".to_string()),
+        (PathBuf::from("geom.alisa"), String::new()),
+    ]);
+    let graph = ModuleGraph::load_with(Path::new("./lib/../main.alisa"), |path| {
+        files.get(path).cloned().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }).unwrap();
+    let paths: Vec<&Path> = graph.modules.iter().map(|module| module.path.as_path()).collect();
+    assert_eq!(paths, [Path::new("geom.alisa"), Path::new("main.alisa")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_files() {
+    let dir = std::env::temp_dir().join(format!("alisa-modules-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.alisa"), "import geom; import shapes;").unwrap();
+    std::fs::write(dir.join("geom.alisa"), "pub fn unit() -> i32 { 1 }").unwrap();
+    let _ = std::fs::remove_file(dir.join("shapes.alisa"));
+    std::os::unix::fs::symlink(dir.join("geom.alisa"), dir.join("shapes.alisa")).unwrap();
+
+    let graph = ModuleGraph::load(&dir.join("main.alisa"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(graph.unwrap().modules.len(), 2);
+}
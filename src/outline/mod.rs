@@ -0,0 +1,136 @@
+// Item signatures without their bodies: every function, type, trait and `impl` in a file, with
+// where it is. Bodies are skipped without being parsed, so this stays quick for whole workspaces.
+
+use crate::ast::{ASTree, Statement, Span};
+use crate::parse::{Parser, ParseError};
+use crate::typeck::function_type;
+
+#[derive(Debug, PartialEq)]
+pub enum SymbolKind {
+    Function,
+    Struct,
+    Enum,
+    Field,
+    Trait,
+    Impl,
+    Method,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub detail: String,
+    pub span: Span,
+    pub name_span: Span,
+    pub children: Vec<Symbol>,
+}
+
+// The symbols declared in `src`, found by only parsing its outline.
+pub fn index(src: &str) -> (Vec<Symbol>, Vec<ParseError>) {
+    let (tree, errors) = Parser::parse_outline(src);
+    (symbols(&tree), errors)
+}
+
+// The items declared at the top level of `tree`, along with their fields and methods.
+pub fn symbols(tree: &ASTree) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    for statement in &tree.root {
+        let symbol = match statement {
+            Statement::Function(item) => Symbol {
+                name: item.name.to_string(),
+                kind: SymbolKind::Function,
+                detail: function_type(&item.arguments, &item.return_type).to_string(),
+                span: item.span,
+                name_span: item.name_span,
+                children: Vec::new(),
+            },
+
+            Statement::Struct(item) => Symbol {
+                name: item.name.to_string(),
+                kind: SymbolKind::Struct,
+                detail: String::new(),
+                span: item.span,
+                name_span: item.name_span,
+                children: item.fields.iter().map(|field| Symbol {
+                    name: field.name.to_string(),
+                    kind: SymbolKind::Field,
+                    detail: field.param_type.to_string(),
+                    span: field.name_span,
+                    name_span: field.name_span,
+                    children: Vec::new(),
+                }).collect(),
+            },
+
+            Statement::Enum(item) => Symbol {
+                name: item.name.to_string(),
+                kind: SymbolKind::Enum,
+                detail: String::new(),
+                span: item.span,
+                name_span: item.name_span,
+                children: Vec::new(),
+            },
+
+            Statement::Trait(item) => Symbol {
+                name: item.name.to_string(),
+                kind: SymbolKind::Trait,
+                detail: String::new(),
+                span: item.span,
+                name_span: item.name_span,
+                children: item.methods.iter().map(|method| Symbol {
+                    name: method.name.to_string(),
+                    kind: SymbolKind::Method,
+                    detail: function_type(&method.arguments, &method.return_type).to_string(),
+                    span: method.span,
+                    name_span: method.name_span,
+                    children: Vec::new(),
+                }).collect(),
+            },
+
+            // `impl`s don't have a name of their own, so they're named after what they're for.
+            Statement::Impl(item) => Symbol {
+                name: match &item.trait_name {
+                    Some(trait_name) => format!("impl {trait_name} for {}", item.self_type),
+                    None => format!("impl {}", item.self_type),
+                },
+                kind: SymbolKind::Impl,
+                detail: String::new(),
+                span: item.span,
+                name_span: item.span,
+                children: item.methods.iter().map(|method| Symbol {
+                    name: method.name.to_string(),
+                    kind: SymbolKind::Method,
+                    detail: function_type(&method.arguments, &method.return_type).to_string(),
+                    span: method.span,
+                    name_span: method.name_span,
+                    children: Vec::new(),
+                }).collect(),
+            },
+
+            _ => continue,
+        };
+        symbols.push(symbol);
+    }
+    symbols
+}
+
+// Every symbol in `symbols`, nested ones included, whose name contains `query` regardless of case,
+// along with the name of the symbol it's in.
+pub fn search<'a>(symbols: &'a [Symbol], query: &str) -> Vec<(&'a Symbol, Option<&'a str>)> {
+    let query = query.to_lowercase();
+    let mut found = Vec::new();
+    for symbol in symbols {
+        if symbol.name.to_lowercase().contains(&query) {
+            found.push((symbol, None));
+        }
+        for child in &symbol.children {
+            if child.name.to_lowercase().contains(&query) {
+                found.push((child, Some(symbol.name.as_str())));
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests;
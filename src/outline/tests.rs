@@ -0,0 +1,57 @@
+use super::*;
+
+const SRC: &str = "
+struct Point { x: i32, y: i32 }
+enum Shape { Circle Square }
+fn area(s: Shape) -> f64 {
+    let broken = ;
+    1.5
+}
+trait Sized { fn size(self) -> i32; }
+impl Sized for Point { fn size(self) -> i32 { self.x + } }
+";
+
+#[test]
+fn outline_only() {
+    // Mistakes in bodies don't matter, since they never get parsed.
+    let (symbols, errors) = index(SRC);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let names: Vec<(&str, &SymbolKind)> = symbols.iter().map(|symbol| (symbol.name.as_str(), &symbol.kind)).collect();
+    assert_eq!(names, [
+        ("Point", &SymbolKind::Struct),
+        ("Shape", &SymbolKind::Enum),
+        ("area", &SymbolKind::Function),
+        ("Sized", &SymbolKind::Trait),
+        ("impl Sized for Point", &SymbolKind::Impl),
+    ]);
+    assert_eq!(symbols[2].detail, "fn(Shape) -> f64");
+    assert_eq!(symbols[2].span, Span::new(62, 117));
+    assert_eq!(&SRC[symbols[2].name_span.start..symbols[2].name_span.end], "area");
+    assert_eq!(symbols[4].children[0].name, "size");
+
+    // The full parse has the same items, just with their bodies.
+    let (tree, errors) = Parser::parse(SRC);
+    assert_eq!(errors.len(), 2);
+    let (outline, _) = Parser::parse_outline(SRC);
+    assert_eq!(super::symbols(&tree), super::symbols(&outline));
+}
+
+#[test]
+fn unbalanced_bodies() {
+    // A body that doesn't close can't be skipped, so it's parsed to find where it ends.
+    let (symbols, errors) = index("fn f() -> i32 { (1 }\nfn g() -> i32 { 2 }");
+    assert!(!errors.is_empty());
+    assert_eq!(symbols.iter().map(|symbol| symbol.name.as_str()).collect::<Vec<_>>(), ["f", "g"]);
+}
+
+#[test]
+fn searching() {
+    let (symbols, _) = index(SRC);
+    let found: Vec<(&str, Option<&str>)> = search(&symbols, "SI")
+        .into_iter()
+        .map(|(symbol, container)| (symbol.name.as_str(), container))
+        .collect();
+    assert_eq!(found, [("Sized", None), ("size", Some("Sized")), ("impl Sized for Point", None), ("size", Some("impl Sized for Point"))]);
+    assert!(search(&symbols, "nothing").is_empty());
+}
@@ -0,0 +1,134 @@
+// Matches up `(`, `[` and `{` with their closers, so that one that's missing can be reported where
+// it was opened instead of wherever the parser happened to give up.
+
+use crate::ast::Span;
+use crate::ast::token::{T, Token, TokenKind};
+use crate::fix::Fix;
+use crate::parse::ParseError;
+use crate::parse::tree::{self, Group, TokenTree};
+
+// Checks the delimiters in `tokens`, which were lexed from `src`. Unclosed ones are reported
+// innermost first, the order they need closing in. Along with the errors, returns the parts of
+// the source in which anything else that went wrong is probably because of them: from each
+// opener up to the token its closer should have come before, any stray closers, and the end of the file,
+// which is where the parser ends up looking for closers that never came.
+pub(super) fn check(src: &str, tokens: &[Token]) -> (Vec<ParseError>, Vec<Span>) {
+    if balanced(tokens) {
+        return (Vec::new(), Vec::new());
+    }
+    let mut errors = Vec::new();
+    let mut regions = Vec::new();
+    check_trees(src, tokens, &tree::build(tokens), &mut errors, &mut regions);
+    if !errors.is_empty() {
+        regions.push(Span::new(src.len(), src.len()));
+    }
+    (errors, regions)
+}
+
+// Whether every closer in `tokens` closes the innermost group, and every group gets closed. That's
+// most files, which don't need the trees built to tell that nothing's wrong with them.
+fn balanced(tokens: &[Token]) -> bool {
+    let mut open = Vec::new();
+    for tok in tokens {
+        match tok.kind {
+            T!("(") | T!("[") | T!("{") => open.push(tree::closer(tok.kind)),
+            T!(")") | T!("]") | T!("}") if open.pop() != Some(tok.kind) => return false,
+            _ => (),
+        }
+    }
+    open.is_empty()
+}
+
+fn check_trees(src: &str, tokens: &[Token], trees: &[TokenTree], errors: &mut Vec<ParseError>, regions: &mut Vec<Span>) {
+    for tree in trees {
+        let group = match tree {
+            TokenTree::Group(group) => group,
+            TokenTree::Token(tok) if matches!(tok.kind, T!(")") | T!("]") | T!("}")) => {
+                let fix = Fix::replace(format!("remove `{}`", text(tok.kind)), tok.span(), "");
+                errors.push(ParseError::UnexpectedDelimiter { found: *tok }.with_fix(fix));
+                regions.push(tok.span());
+                continue;
+            }
+            TokenTree::Token(_) => continue,
+        };
+        check_trees(src, tokens, &group.trees, errors, regions);
+
+        let text = text(tree::closer(group.open.kind));
+        match group.close {
+            Some(close) if close.kind != tree::closer(group.open.kind) => {
+                let fix = Fix::replace(format!("replace with `{text}`"), close.span(), text);
+                errors.push(ParseError::MismatchedDelimiter { delimiter: group.open, found: close }.with_fix(fix));
+                regions.push(Span::new(group.open.start, close.end));
+            }
+            Some(_) => (),
+            None => {
+                let fix = guess(src, group);
+                let next = tokens.iter().find(|tok| tok.start >= fix.edit.span.start);
+                regions.push(Span::new(group.open.start, next.map_or(src.len(), |tok| tok.start)));
+                errors.push(ParseError::UnclosedDelimiter { delimiter: group.open }.with_fix(fix));
+            }
+        }
+    }
+}
+
+// Where the closer for an unclosed group most likely belongs, going by indentation: after the
+// last token in it that's either on the opener's line or indented further than it. A `(` or `[`
+// doesn't take a block in with it, as in `fn f(a: i32 -> i32 { a }`, since one that's closed
+// properly is much more likely to come after it. Nor does it take a `->` in, unless it's the
+// return type of a function type or a closure, as in `fn(i32) -> i32`.
+fn guess(src: &str, group: &Group) -> Fix {
+    let opener = group.open;
+    let (line, indent) = line_of(src, opener.start);
+    let text = text(tree::closer(opener.kind));
+
+    let mut tokens = Vec::new();
+    let mut after_parens = false;
+    for tree in &group.trees {
+        match tree {
+            TokenTree::Group(inner) if opener.kind != T!("{") && inner.open.kind == T!("{") && inner.is_balanced() => break,
+            TokenTree::Token(tok) if opener.kind != T!("{") && tok.kind == T!("->") && !after_parens => break,
+            TokenTree::Group(inner) => tokens.extend(inner.tokens()),
+            TokenTree::Token(tok) => tokens.push(*tok),
+        }
+        after_parens = matches!(tree, TokenTree::Group(inner) if inner.open.kind == T!("(") && inner.is_balanced());
+    }
+
+    let mut last = opener;
+    let mut current_line = line;
+    for tok in tokens {
+        if tok.kind == T!("EOF") { break }
+        let (tok_line, tok_indent) = line_of(src, tok.start);
+        if tok_line != current_line && tok_indent <= indent { break }
+        current_line = tok_line;
+        last = tok;
+    }
+
+    // A statement's `;` stays outside of a `(` or `[` that's left open in it.
+    if last.kind == T!(";") && opener.kind != T!("{") {
+        return Fix::insert(format!("insert `{text}`"), last.start, text);
+    }
+    let insert = match (opener.kind, line_of(src, last.start).0 == line) {
+        (T!("{"), false) => format!("\n{}{text}", &src[line..line + indent]),
+        (T!("{"), true) => format!(" {text}"),
+        _ => text.to_string(),
+    };
+    Fix::insert(format!("insert `{text}`"), last.end, &insert)
+}
+
+// The offset the line `offset` is on starts at, and how far it's indented.
+fn line_of(src: &str, offset: usize) -> (usize, usize) {
+    let start = src[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let indent = src[start..].chars().take_while(|c| *c == ' ' || *c == '\t').count();
+    (start, indent)
+}
+
+pub(super) fn text(delimiter: TokenKind) -> &'static str {
+    match delimiter {
+        T!("(") => "(",
+        T!(")") => ")",
+        T!("[") => "[",
+        T!("]") => "]",
+        T!("{") => "{",
+        _ => "}",
+    }
+}
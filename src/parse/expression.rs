@@ -1,17 +1,20 @@
-use crate::parse::{Parser, ParseError, ParseResult};
+use crate::parse::{Parser, ParseError, ParseResult, parse_literal};
 
 
-use crate::ast::token::{T, TokenKind, LiteralKind, OpKind};
-use crate::ast::{Statement, Expression};
+use crate::ast::token::{T, TokenKind, OpKind};
+use crate::ast::{Statement, Expression, ExprId, Symbol};
 use crate::ast::{ClosureExpression, IdentExpression, BlockExpression, CallExpression};
+use crate::ast::{MethodCallExpression, StructExpression, FieldInit, FieldExpression};
 use crate::ast::{IfExpression, ElseExpression};
 use crate::ast::{LitKind, LiteralExpression};
 use crate::ast::{BinaryExpression, BinaryOperator};
 use crate::ast::{UnaryExpression, UnaryOperator};
 use crate::ast::{Tuple, List};
+use crate::syntax::SyntaxKind;
+use crate::fix::Fix;
 
 
-fn binop_tok_to_ast(op_kind: TokenKind) -> Option<BinaryOperator> {
+pub(crate) fn binop_tok_to_ast(op_kind: TokenKind) -> Option<BinaryOperator> {
     let op = match op_kind {
         T!("|") => BinaryOperator::BitOr,
         T!("&") => BinaryOperator::BitAnd,
@@ -41,7 +44,7 @@ fn binop_tok_to_ast(op_kind: TokenKind) -> Option<BinaryOperator> {
     Some(op)
 }
 
-fn unop_tok_to_ast(op_kind: TokenKind) -> Option<UnaryOperator> {
+pub(crate) fn unop_tok_to_ast(op_kind: TokenKind) -> Option<UnaryOperator> {
     let op = match op_kind {
         T!("+") => UnaryOperator::Plus,
         T!("-") => UnaryOperator::Minus,
@@ -54,7 +57,7 @@ fn unop_tok_to_ast(op_kind: TokenKind) -> Option<UnaryOperator> {
     Some(op)
 }
 
-fn prefix_binding_power(op: UnaryOperator) -> ((), u8) {
+pub(crate) fn prefix_binding_power(op: UnaryOperator) -> ((), u8) {
     match op {
         UnaryOperator::BoolNot => ((), 5),
 
@@ -64,7 +67,7 @@ fn prefix_binding_power(op: UnaryOperator) -> ((), u8) {
     }
 }
 
-fn infix_binding_power(op: BinaryOperator) -> (u8, u8) {
+pub(crate) fn infix_binding_power(op: BinaryOperator) -> (u8, u8) {
     match op {
         BinaryOperator::BoolOr  => (1, 2),
         BinaryOperator::BoolAnd => (3, 4),
@@ -94,78 +97,87 @@ fn infix_binding_power(op: BinaryOperator) -> (u8, u8) {
 }
 
 impl<'src> Parser<'src> {
-    pub(super) fn parse_expr(&mut self, min_bp: u8) -> ParseResult<Expression> {
+    pub(super) fn parse_expr(&mut self, min_bp: u8) -> ParseResult<ExprId> {
         let tok = self.peek(0);
+        let start = tok.start;
+        let checkpoint = self.checkpoint();
         let mut lhs = match tok.kind {
-            TokenKind::Literal { kind } => {
-                self.bump();
-                let lexeme = self.get_lexeme(tok);
-                let literal = self.parse_literal(kind, lexeme);
-                Expression::Literal(literal)
-            }
+            TokenKind::Literal { kind } => self.node(SyntaxKind::Literal, |p| {
+                p.bump();
+                let lexeme = p.get_lexeme(tok);
+                let literal = parse_literal(kind, lexeme, tok.span(), &mut p.exprs)?;
+                Ok(p.exprs.alloc(Expression::Literal(literal)))
+            })?,
 
             T!("\\") => {
-                let closure = self.parse_closure()?;
-                Expression::Closure(Box::new(closure))
+                let closure = self.node(SyntaxKind::Closure, Self::parse_closure)?;
+                self.exprs.alloc(Expression::Closure(closure))
             }
 
             T!("if") => {
-                let if_expr = self.parse_if()?;
-                Expression::If(Box::new(if_expr))
+                let if_expr = self.node(SyntaxKind::If, Self::parse_if)?;
+                self.exprs.alloc(Expression::If(if_expr))
             }
 
             T!("(") => {
                 self.bump();
-                let lhs = self.parse_expr(0)?;
+                let lhs = self.with_condition(false, |p| p.parse_expr(0))?;
 
                 const CLOSE: TokenKind = T!(")");
 
-                if self.bump_check(T!(",")) {
-                    let mut first_expr = true;
-                    let mut expressions = Vec::new();
-                    expressions.push(lhs);
-
-                    loop {
-                        let (peek_0, peek_1) = (self.peek(0).kind, self.peek(1).kind);
-                        if peek_0 == CLOSE || (peek_1 == CLOSE && !first_expr) { break }
-
-                        if !first_expr { 
-                            self.bump_recover(T!(","));
-                        } else { first_expr = false; }
-
-                        let expression = match self.parse_expr(0) {
-                            Ok(expression) => expression,
-                            Err(err) => {
-                                /*
-                                self.recover_error(err);
-                                break
-                                */
-                                return Err(err);
-                            }
-                        };
-
-                        expressions.push(expression);
-                    }
+                // Whether it's a tuple or just parentheses is only known after the first
+                // expression, hence the checkpoint.
+                if self.check(T!(",")) {
+                    self.node_at(checkpoint, SyntaxKind::Tuple, |p| p.with_condition(false, |p| {
+                        p.bump();
+
+                        let mut first_expr = true;
+                        let mut expressions = Vec::new();
+                        expressions.push(lhs);
+
+                        loop {
+                            let (peek_0, peek_1) = (p.peek(0).kind, p.peek(1).kind);
+                            if peek_0 == CLOSE || (peek_1 == CLOSE && !first_expr) { break }
+
+                            if !first_expr { 
+                                p.bump_recover(T!(","));
+                            } else { first_expr = false; }
+
+                            let expression = match p.parse_expr(0) {
+                                Ok(expression) => expression,
+                                Err(err) => {
+                                    /*
+                                    p.recover_error(err);
+                                    break
+                                    */
+                                    return Err(err);
+                                }
+                            };
+
+                            expressions.push(expression);
+                        }
 
-                    self.bump_expect(CLOSE)?;
+                        p.bump_expect(CLOSE)?;
 
-                    let tuple_expr = LiteralExpression {
-                        kind: LitKind::Tuple(Tuple(expressions))
-                    };
-                    Expression::Literal(tuple_expr)
+                        let tuple_expr = LiteralExpression {
+                            kind: LitKind::Tuple(Tuple(expressions)),
+                            span: p.span_from(start),
+                        };
+                        Ok(p.exprs.alloc(Expression::Literal(tuple_expr)))
+                    }))?
                 } else {
-                    self.bump_expect(CLOSE)?;
+                    self.node_at(checkpoint, SyntaxKind::Paren, |p| p.bump_expect(CLOSE))?;
                     lhs
                 }
             }
 
             T!("{") => {
-                let block_expr = Box::new(self.parse_block()?);
-                Expression::Block(block_expr)
+                let block_expr = self.parse_block()?;
+                self.exprs.alloc(Expression::Block(block_expr))
             }
 
-            T!("[") => {
-                self.bump();
+            T!("[") => self.node(SyntaxKind::List, |p| p.with_condition(false, |p| {
+                p.bump();
 
                 let mut first_expr = true;
                 let mut expressions = Vec::new();
@@ -173,17 +185,19 @@ impl<'src> Parser<'src> {
                 const CLOSE: TokenKind = T!("]");
 
                 loop {
-                    let (peek_0, peek_1) = (self.peek(0).kind, self.peek(1).kind);
+                    let (peek_0, peek_1) = (p.peek(0).kind, p.peek(1).kind);
                     if peek_0 == CLOSE || (peek_1 == CLOSE && !first_expr) { break }
 
                     if !first_expr { 
-                        self.bump_recover(T!(","));
+                        p.bump_recover(T!(","));
                     } else { first_expr = false; }
 
-                    let expression = match self.parse_expr(0) {
+                    let exprs = p.exprs.len();
+                    let expression = match p.parse_expr(0) {
                         Ok(expression) => expression,
                         Err(err) => {
-                            self.recover_error(err);
+                            p.recover_error(err);
+                            p.exprs.truncate(exprs);
                             break
                         }
                     };
@@ -191,20 +205,21 @@ impl<'src> Parser<'src> {
                     expressions.push(expression);
                 }
 
-                self.bump_expect(CLOSE)?;
+                p.bump_expect(CLOSE)?;
 
                 let list_expr = LiteralExpression {
-                    kind: LitKind::List(List(expressions))
+                    kind: LitKind::List(List(expressions)),
+                    span: p.span_from(start),
                 };
-                Expression::Literal(list_expr)
-            }
+                Ok(p.exprs.alloc(Expression::Literal(list_expr)))
+            }))?,
 
             kind @
             ( TokenKind::Op { .. }
             | T!("!") 
             | T!("~")
-            ) => {
-                self.bump();
+            ) => self.node(SyntaxKind::Unary, |p| {
+                p.bump();
                 let op = match unop_tok_to_ast(kind) {
                     Some(op) => op,
                     None => return Err(ParseError::ExpectedAlternatives {
@@ -218,17 +233,21 @@ impl<'src> Parser<'src> {
                 };
 
                 let ((), r_bp) = prefix_binding_power(op);
-                let rhs = self.parse_expr(r_bp)?;
-                let un_expr = UnaryExpression { rhs, op };
-                Expression::Unary(Box::new(un_expr))
+                let rhs = p.parse_expr(r_bp)?;
+                let un_expr = UnaryExpression { rhs, op, ty: None, span: p.span_from(start) };
+                Ok(p.exprs.alloc(Expression::Unary(un_expr)))
+            })?,
+
+            T!("ID") if self.struct_literal_ahead() => {
+                let struct_expr = self.node(SyntaxKind::StructLit, Self::parse_struct_literal)?;
+                self.exprs.alloc(Expression::Struct(struct_expr))
             }
 
-            T!("ID") => {
-                self.bump();
-                let name = self.get_lexeme(tok);
-                let ident = IdentExpression { name: name.into() };
-                Expression::Identifier(ident)
-            }
+            T!("ID") => self.node(SyntaxKind::Name, |p| {
+                let (path, span) = p.parse_path()?;
+                let ident = IdentExpression { name: Symbol::join(&path, "::"), span };
+                Ok(p.exprs.alloc(Expression::Identifier(ident)))
+            })?,
             
             _ => return Err(ParseError::ExpectedNode {
                 expected: "expression".into(),
@@ -238,6 +257,52 @@ impl<'src> Parser<'src> {
 
         loop {
             let tok = self.peek(0);
+
+            // Calls bind tighter than any operator, so they get handled before looking for one.
+            if tok.kind == T!("(") {
+                lhs = self.node_at(checkpoint, SyntaxKind::Call, |p| {
+                    let arguments = p.parse_call_args()?;
+                    let call_expr = CallExpression { function: lhs, arguments, span: p.span_from(start) };
+                    Ok(p.exprs.alloc(Expression::Call(call_expr)))
+                })?;
+                continue
+            }
+
+            // Field accesses and method calls bind the same as calls. It's only a method call if
+            // there's a `(` after the name.
+            if tok.kind == T!(".") && self.peek(2).kind != T!("(") {
+                lhs = self.node_at(checkpoint, SyntaxKind::Field, |p| {
+                    p.bump();
+                    let field = p.take_expect(T!("ID"))?;
+                    let field_expr = FieldExpression {
+                        receiver: lhs,
+                        field: p.get_name(field),
+                        field_span: field.span(),
+                        span: p.span_from(start),
+                    };
+                    Ok(p.exprs.alloc(Expression::Field(field_expr)))
+                })?;
+                continue
+            }
+
+            if tok.kind == T!(".") {
+                lhs = self.node_at(checkpoint, SyntaxKind::MethodCall, |p| {
+                    p.bump();
+                    let method = p.take_expect(T!("ID"))?;
+                    let arguments = p.parse_call_args()?;
+                    let method_call = MethodCallExpression {
+                        receiver: lhs,
+                        method: p.get_name(method),
+                        method_span: method.span(),
+                        arguments,
+                        self_type: None,
+                        span: p.span_from(start),
+                    };
+                    Ok(p.exprs.alloc(Expression::MethodCall(method_call)))
+                })?;
+                continue
+            }
+
             let op = match binop_tok_to_ast(tok.kind) {
                 Some(op) => op,
                 None => break,
@@ -249,64 +314,89 @@ impl<'src> Parser<'src> {
                 break
             }
 
-            let mark = self.stream.mark();
-            self.bump();
-            let rhs = match self.parse_expr(r_bp) {
-                Ok(rhs) => rhs,
-                Err(err) => { 
-                    self.stream.reset(mark);
-                    return Err(err);
-                }
-            };
-
-            let bin_expr = BinaryExpression { lhs, rhs, op };
-            lhs = Expression::Binary(Box::new(bin_expr));
+            lhs = self.node_at(checkpoint, SyntaxKind::Binary, |p| {
+                p.bump();
+                let rhs = p.parse_expr(r_bp)?;
+                let bin_expr = BinaryExpression { lhs, rhs, op, ty: None, span: p.span_from(start) };
+                Ok(p.exprs.alloc(Expression::Binary(bin_expr)))
+            })?;
         }
 
         Ok(lhs)
     }
 
-    pub(super) fn parse_literal(&self, kind: LiteralKind, lexeme: &str) -> LiteralExpression {
-        let kind = match kind {
-            LiteralKind::Bool => {
-                let Ok(value) = lexeme.parse::<bool>() else {
-                    unreachable!("could not parse boolean"); 
-                };
-                LitKind::Bool(value)
-            }
+    // See parse_params()
+    pub(super) fn parse_call_args(&mut self) -> ParseResult<Vec<ExprId>> {
+        self.node(SyntaxKind::ArgList, |p| p.with_condition(false, |p| {
+            p.bump_expect(T!("("))?;
 
-            LiteralKind::Int => {
-                let Ok(value) = lexeme.parse::<u128>() else { 
-                    unreachable!("could not parse integer"); 
-                };
-                LitKind::Int(value)
-            }
+            let mut first_arg = true;
+            let mut arguments = Vec::new();
 
-            LiteralKind::Float => {
-                let Ok(value) = lexeme.parse::<f64>() else {
-                    unreachable!("could not parse float");
-                };
-                LitKind::Float(value)
-            }
+            const CLOSE: TokenKind = T!(")");
+
+            loop {
+                let (peek_0, peek_1) = (p.peek(0).kind, p.peek(1).kind);
+                if peek_0 == CLOSE || (peek_1 == CLOSE && !first_arg) { break }
+
+                if !first_arg { 
+                    p.bump_expect(T!(","))?;
+                } else { first_arg = false; }
 
-            LiteralKind::Str { terminated: _ } => {
-                let value = String::from(&self.src[1..lexeme.len()-1]);
-                LitKind::Str(value)
+                arguments.push(p.parse_expr(0)?);
             }
 
-            LiteralKind::Char { terminated: _ } => {
-                let lexeme = &self.src[1..lexeme.len()-1];
-                let Ok(value) = lexeme.parse::<char>() else {
-                    unreachable!("could not parse char");
-                };
-                LitKind::Char(value)
+            // Optional `,` after the last argument
+            p.bump_check(T!(","));
+            p.bump_expect(CLOSE)?;
+
+            Ok(arguments)
+        }))
+    }
+
+    // Whether the path at the current token starts a struct literal, which it does if the `{` after
+    // it is followed by a field and a `:`, since a block can't start like that, or by a `}`.
+    fn struct_literal_ahead(&mut self) -> bool {
+        let len = self.peek_path_len();
+        if self.peek(len).kind != T!("{") {
+            return false;
+        }
+        match (self.peek(len + 1).kind, self.peek(len + 2).kind) {
+            (T!("ID"), T!(":")) => true,
+            (T!("}"), _) => !self.condition,
+            _ => false,
+        }
+    }
+
+    // `Point { x: 1, y: 2 }`
+    pub(super) fn parse_struct_literal(&mut self) -> ParseResult<StructExpression> {
+        let start = self.peek(0).start;
+        let (path, name_span) = self.parse_path()?;
+        self.bump_expect(T!("{"))?;
+
+        let fields = self.with_condition(false, |p| {
+            let mut fields = Vec::new();
+            while !p.check(T!("}")) {
+                fields.push(p.node(SyntaxKind::FieldInit, |p| {
+                    let name = p.take_expect(T!("ID"))?;
+                    p.bump_expect(T!(":"))?;
+                    let value = p.parse_expr(0)?;
+                    Ok(FieldInit { name: p.get_name(name), value, name_span: name.span() })
+                })?);
+
+                // Optional `,` after the last field
+                if !p.bump_check(T!(",")) { break }
             }
-        };
-        LiteralExpression { kind }
+            Ok(fields)
+        })?;
+        self.bump_expect(T!("}"))?;
+
+        let name = Symbol::join(&path, "::");
+        Ok(StructExpression { name, fields, name_span, span: self.span_from(start) })
     }
 
     pub(super) fn parse_closure(&mut self) -> ParseResult<ClosureExpression> {
-        self.bump(); // `\`
+        let start = self.take().start; // `\`
 
         let arguments = self.parse_params(T!("("), T!(")"))?;
 
@@ -316,76 +406,88 @@ impl<'src> Parser<'src> {
 
         let block = self.parse_block()?;
 
-        Ok(ClosureExpression { arguments, block, return_type })
+        Ok(ClosureExpression { arguments, block, return_type, span: self.span_from(start) })
     }
 
     pub(super) fn parse_if(&mut self) -> ParseResult<IfExpression> {
-        self.bump(); // `if`
+        let start = self.take().start; // `if`
         
-        let condition = self.parse_expr(0)?;
+        let condition = self.with_condition(true, |p| p.parse_expr(0))?;
+        // `if a = b` is almost certainly meant to be a comparison.
+        if self.check(T!("=")) {
+            let found = self.peek(0);
+            let fix = Fix::replace("use `==` to compare", found.span(), "==");
+            return Err(ParseError::ExpectedSingle { expected: T!("{"), found }.with_fix(fix));
+        }
         let body = self.parse_block()?;
         
-        if self.bump_check(T!("else")) {
-            let else_body = if self.bump_check(T!("if")) {
-                let else_body = self.parse_if()?;
-                ElseExpression::ElseIf(else_body)
-            } else {
-                let else_body = self.parse_block()?;
-                ElseExpression::Else(else_body)
-            };
+        if self.check(T!("else")) {
+            let else_body = self.node(SyntaxKind::Else, |p| {
+                p.bump();
+                // parse_if() bumps the `if` itself.
+                if p.check(T!("if")) {
+                    let else_body = p.node(SyntaxKind::If, Self::parse_if)?;
+                    Ok(ElseExpression::ElseIf(p.exprs.alloc(Expression::If(else_body))))
+                } else {
+                    let else_body = p.parse_block()?;
+                    Ok(ElseExpression::Else(else_body))
+                }
+            })?;
 
-            let else_body = Some(Box::new(else_body));
-            return Ok(IfExpression { condition, body, else_body });
+            let else_body = Some(else_body);
+            return Ok(IfExpression { condition, body, else_body, span: self.span_from(start) });
         }
 
-        Ok(IfExpression { condition, body, else_body: None })
+        Ok(IfExpression { condition, body, else_body: None, span: self.span_from(start) })
     }
 
     pub(super) fn parse_block(&mut self) -> ParseResult<BlockExpression> {
-        self.bump_expect(T!("{"))?;
-
-        let mut statements = Vec::new();
-        loop {
-            if self.peek(0).kind == T!("}") { break }
-            match self.parse_statement() {
-                Ok(statement) => statements.push(statement),
-                Err(err) => {
-                    self.recover_error(err);
-                    break
-                }
+        self.node(SyntaxKind::Block, |p| p.with_condition(false, |p| {
+            let start = p.take_expect(T!("{"))?.start;
+
+            let mut statements = Vec::new();
+            loop {
+                // Running out of input is an unclosed block, which `bump_expect()` reports below.
+                if matches!(p.peek(0).kind, T!("}") | T!("EOF")) { break }
+                statements.push(p.parse_statement_recover(true));
             }
-        }
 
-        self.bump_expect(T!("}"))?;
-
-        if statements.is_empty() {
-            return Ok(BlockExpression { statements, expression: None })
-        }
+            p.bump_expect(T!("}"))?;
 
+            let span = p.span_from(start);
+            if statements.is_empty() {
+                return Ok(BlockExpression { statements, expression: None, span })
+            }
 
-        for statement in &statements[..statements.len()-1] {
-            self.validate_statement(statement);
-        }
 
-        let expression = match statements.last() {
-            Some(Statement::Expression { expr: _, end_token }) if end_token.kind != T!(";") => {
-                let expr = statements.pop();
-                let Some(Statement::Expression { expr, .. }) = expr else { unreachable!() };
-                Some(expr)
+            for statement in &statements[..statements.len()-1] {
+                p.validate_statement(statement);
             }
-            _ => None,
-        };
 
-        Ok(BlockExpression { statements, expression })
+            let expression = match statements.last() {
+                Some(Statement::Expression { end_token, .. }) if end_token.kind != T!(";") => {
+                    let expr = statements.pop();
+                    let Some(Statement::Expression { expr, .. }) = expr else { unreachable!() };
+                    Some(expr)
+                }
+                _ => None,
+            };
+
+            Ok(BlockExpression { statements, expression, span })
+        }))
     }
 
+    // Expressions that aren't at the end of a block need a `;` unless they end with a block
+    // themselves, like `if` and `{}` do.
     fn validate_statement(&mut self, statement: &Statement) {
-        if let Statement::Expression { expr: _, end_token } = statement {
-            if end_token.kind == T!(";") {
+        if let Statement::Expression { expr, end_token, .. } = *statement {
+            let block_like = matches!(self.exprs[expr], Expression::If(_) | Expression::Block(_));
+            if end_token.kind != T!(";") && !block_like {
+                let fix = self.statement_fix(expr, end_token);
                 self.recover_error(ParseError::ExpectedSingle {
                     expected: T!(";"),
-                    found: *end_token,
-                })
+                    found: end_token,
+                }.with_fix(fix))
             }
         }
     }
@@ -0,0 +1,240 @@
+// Turns the text of a literal token into its value. The lexer only decides where a literal ends,
+// so anything wrong with what's inside of it gets reported from here.
+
+use crate::lex::{self, StrPiece};
+use crate::parse::{Parser, ParseError, ParseResult};
+use crate::ast::token::LiteralKind;
+use crate::ast::{LitKind, LiteralExpression, Exprs, Span, StrPart, Type, FloatKind};
+
+// The expressions embedded in a string go into `exprs`.
+pub(crate) fn parse_literal(kind: LiteralKind, lexeme: &str, span: Span, exprs: &mut Exprs) -> ParseResult<LiteralExpression> {
+    let error = |error| ParseError::InvalidLiteral { error, span };
+    let kind = match kind {
+        LiteralKind::Bool => match lexeme.parse::<bool>() {
+            Ok(value) => LitKind::Bool(value),
+            Err(_) => return Err(error(LiteralError::InvalidBool)),
+        },
+
+        LiteralKind::Int => int(lexeme).map_err(error)?,
+
+        LiteralKind::Float => float(lexeme).map_err(error)?,
+
+        LiteralKind::Str { terminated: false } => return Err(error(LiteralError::Unterminated { quote: '"' })),
+        LiteralKind::Str { terminated: true } => string(&lexeme[1..lexeme.len()-1], span, exprs)?,
+
+        LiteralKind::RawStr { terminated: false } => return Err(error(LiteralError::Unterminated { quote: '"' })),
+        LiteralKind::RawStr { terminated: true } => {
+            let hashes = lexeme[1..].len() - lexeme[1..].trim_start_matches('#').len();
+            LitKind::Str(lexeme[hashes+2..lexeme.len()-hashes-1].to_string())
+        }
+
+        LiteralKind::Char { terminated: false } => return Err(error(LiteralError::Unterminated { quote: '\'' })),
+        LiteralKind::Char { terminated: true } => {
+            let value = unescape(&lexeme[1..lexeme.len()-1]).map_err(error)?;
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(value), None) => LitKind::Char(value),
+                _ => return Err(error(LiteralError::CharLength)),
+            }
+        }
+    };
+    Ok(LiteralExpression { kind, span })
+}
+
+// What's between the quotes of a string literal at `span`. It's only `Interpolated` if it has
+// expressions embedded in it.
+fn string(contents: &str, span: Span, exprs: &mut Exprs) -> ParseResult<LitKind> {
+    let error = |error| ParseError::InvalidLiteral { error, span };
+    let pieces = lex::str_pieces(contents);
+    if let [] | [StrPiece::Text(_)] = &pieces[..] {
+        return unescape(contents).map(LitKind::Str).map_err(error);
+    }
+
+    let mut parts = Vec::new();
+    for piece in pieces {
+        let part = match piece {
+            StrPiece::Text(range) => StrPart::Text(unescape(&contents[range]).map_err(error)?),
+            StrPiece::Expr(range) => {
+                // Past the opening quote.
+                let offset = span.start + 1 + range.start;
+                let expr = Parser::parse_expression_at(&contents[range], offset, exprs);
+                // Only the first error is kept, the same as for the rest of the literal.
+                StrPart::Expr(expr.map_err(|errors| errors[0].clone())?)
+            }
+        };
+        parts.push(part);
+    }
+    Ok(LitKind::Interpolated(parts))
+}
+
+// The contents of a string or character literal, with its escapes decoded.
+fn unescape(s: &str) -> Result<String, LiteralError> {
+    let mut value = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        let c = match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '\'' | '"' | '{' | '}')) => c,
+            Some('x') => {
+                // Only ASCII, everything else needs `\u{...}`.
+                let digits: String = chars.by_ref().take(2).collect();
+                let valid = digits.len() == 2 && digits.chars().all(|c| c.is_ascii_hexdigit());
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if valid && byte.is_ascii() => char::from(byte),
+                    _ => return Err(LiteralError::InvalidEscape { escape: format!("\\x{digits}") }),
+                }
+            }
+            Some('u') => {
+                let rest = chars.as_str();
+                let end = rest.find('}').filter(|_| rest.starts_with('{')).map_or(rest.len(), |end| end + 1);
+                chars = rest[end..].chars();
+
+                let digits = rest[..end].strip_prefix('{').and_then(|digits| digits.strip_suffix('}'));
+                let valid = digits.is_some_and(|digits| {
+                    (1..=6).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_hexdigit())
+                });
+                let c = digits.filter(|_| valid).and_then(|digits| u32::from_str_radix(digits, 16).ok());
+                match c.and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => return Err(LiteralError::InvalidEscape { escape: format!("\\u{}", &rest[..end]) }),
+                }
+            }
+            Some(c) => return Err(LiteralError::UnknownEscape { escape: c }),
+            // The lexer never ends a literal on a `\`.
+            None => return Err(LiteralError::UnknownEscape { escape: ' ' }),
+        };
+        value.push(c);
+    }
+    Ok(value)
+}
+
+// `1_000`, `0xff`, `0o17` and `0b1010`, each with an optional suffix. Decimal ones can also have a
+// float suffix, which makes them floats.
+fn int(lexeme: &str) -> Result<LitKind, LiteralError> {
+    let (base, digits) = match lexeme.get(..2) {
+        Some("0x") => (16, &lexeme[2..]),
+        Some("0o") => (8, &lexeme[2..]),
+        Some("0b") => (2, &lexeme[2..]),
+        _ => (10, lexeme),
+    };
+
+    // Same as in the lexer, hexadecimal digits can't start a suffix.
+    let suffix_start = digits
+        .find(|c: char| c != '_' && !(c.is_ascii_digit() || base == 16 && c.is_ascii_hexdigit()))
+        .unwrap_or(digits.len());
+    let (digits, suffix) = digits.split_at(suffix_start);
+
+    let ty = suffix_type(suffix)?;
+    if let Some(ty @ Type::Float { .. }) = ty {
+        if base != 10 {
+            return Err(LiteralError::InvalidSuffix { suffix: suffix.into() });
+        }
+        return float_value(digits).map(|value| LitKind::Float { value, ty: Some(ty) });
+    }
+
+    let digits = digits.replace('_', "");
+    if digits.is_empty() {
+        return Err(LiteralError::NoDigits);
+    }
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(base)) {
+        return Err(LiteralError::InvalidDigit { digit, base });
+    }
+    match u128::from_str_radix(&digits, base) {
+        Ok(value) => Ok(LitKind::Int { value, ty }),
+        Err(_) => Err(LiteralError::TooLarge),
+    }
+}
+
+// `1.5`, `.5`, `1.`, `1e10` and `2.5E-3`, with an optional `f32` or `f64` suffix.
+fn float(lexeme: &str) -> Result<LitKind, LiteralError> {
+    let mut suffix_start = lexeme
+        .find(|c: char| c != '_' && c != '.' && !c.is_ascii_digit())
+        .unwrap_or(lexeme.len());
+
+    // Same as in the lexer, an `e` is only an exponent if it has digits.
+    if let Some(exponent) = lexeme[suffix_start..].strip_prefix(['e', 'E']) {
+        let unsigned = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if unsigned.starts_with(|c: char| c.is_ascii_digit()) {
+            let digits = unsigned.find(|c: char| c != '_' && !c.is_ascii_digit()).unwrap_or(unsigned.len());
+            suffix_start = lexeme.len() - unsigned.len() + digits;
+        }
+    }
+    let (digits, suffix) = lexeme.split_at(suffix_start);
+
+    let ty = suffix_type(suffix)?;
+    if let Some(Type::Int { .. }) = ty {
+        return Err(LiteralError::InvalidSuffix { suffix: suffix.into() });
+    }
+    let value = float_value(digits)?;
+    // Rust's parsing rounds anything too large up to infinity instead of failing.
+    let max = match ty {
+        Some(Type::Float { kind: FloatKind::Bit32 }) => f32::MAX.into(),
+        _ => f64::MAX,
+    };
+    if value > max {
+        return Err(LiteralError::FloatTooLarge);
+    }
+    Ok(LitKind::Float { value, ty })
+}
+
+// Rust's float parsing doesn't know about `_`.
+fn float_value(digits: &str) -> Result<f64, LiteralError> {
+    digits.replace('_', "").parse::<f64>().map_err(|_| LiteralError::InvalidFloat)
+}
+
+// The type a suffix names, if there is one.
+fn suffix_type(suffix: &str) -> Result<Option<Type>, LiteralError> {
+    if suffix.is_empty() {
+        return Ok(None);
+    }
+    match Parser::parse_type_from_ident(suffix) {
+        ty @ (Type::Int { .. } | Type::Float { .. }) => Ok(Some(ty)),
+        _ => Err(LiteralError::InvalidSuffix { suffix: suffix.into() }),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LiteralError {
+    // Like `0x`.
+    NoDigits,
+    InvalidDigit { digit: char, base: u32 },
+    InvalidSuffix { suffix: String },
+    // More than any integer type can hold, no matter the suffix.
+    TooLarge,
+    InvalidFloat,
+    // More than the float type can hold, which would otherwise round up to infinity.
+    FloatTooLarge,
+    InvalidBool,
+    Unterminated { quote: char },
+    // A character literal with anything but a single character in it.
+    CharLength,
+    UnknownEscape { escape: char },
+    // A `\x` or `\u` escape that's malformed or isn't a valid character.
+    InvalidEscape { escape: String },
+}
+
+impl std::fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiteralError::NoDigits => write!(f, "integer literal has no digits"),
+            LiteralError::InvalidDigit { digit, base } => write!(f, "invalid digit `{digit}` in a base {base} literal"),
+            LiteralError::InvalidSuffix { suffix } => write!(f, "invalid suffix `{suffix}` for a number literal"),
+            LiteralError::TooLarge => write!(f, "integer literal is too large"),
+            LiteralError::InvalidFloat => write!(f, "invalid float literal"),
+            LiteralError::FloatTooLarge => write!(f, "float literal is too large"),
+            LiteralError::InvalidBool => write!(f, "invalid boolean literal"),
+            LiteralError::Unterminated { quote } => write!(f, "literal is missing its closing `{quote}`"),
+            LiteralError::CharLength => write!(f, "character literals must have exactly one character"),
+            LiteralError::UnknownEscape { escape } => write!(f, "unknown escape `\\{escape}`"),
+            LiteralError::InvalidEscape { escape } => write!(f, "invalid escape `{escape}`"),
+        }
+    }
+}
@@ -1,53 +1,157 @@
 pub mod stream;
+pub mod tree;
 mod statement;
 mod expression;
 mod types;
+mod literal;
+mod delimiter;
 
+pub(crate) use expression::{infix_binding_power, prefix_binding_power};
+pub(crate) use expression::{binop_tok_to_ast, unop_tok_to_ast};
+pub(crate) use literal::parse_literal;
+pub use literal::LiteralError;
+
+use crate::lex;
+use crate::fix::{self, Fix};
 use crate::parse::stream::TokenStream;
+use crate::syntax::{self, Event, SyntaxKind, SyntaxNode, SyntaxElement};
 
-use crate::ast::token::{Token, TokenKind};
-use crate::ast::Statement;
+use crate::ast::token::{T, Token, TokenKind, OpKind, KEYWORDS};
+use crate::ast::{Statement, Expression, ExprId, Exprs, Span, Symbol, Type};
 use crate::ast::ASTree;
 
 pub struct Parser<'src> {
     pub src: &'src str,
+    // Where `src` starts in the file, which isn't zero for expressions embedded in strings.
+    offset: usize,
     pub stream: TokenStream,
     token: Token,
+    // Where the last token that was taken or bumped ends.
+    prev_end: usize,
+
+    // What the types in the item being parsed can refer to besides declared types.
+    scope: TypeScope,
 
-    errors: Vec<ParseError>
+    errors: Vec<ParseError>,
+    // Everything that was parsed, in order, for building the syntax tree.
+    events: Vec<Event>,
+    // The expressions of the tree being parsed.
+    exprs: Exprs,
+    // Whether function bodies get skipped instead of parsed.
+    outline: bool,
+    // Whether an `if` condition is being parsed, where the `{}` in `if x {}` is the body rather
+    // than an empty struct literal.
+    condition: bool,
 }
 
 impl<'src> Parser<'src> {
-    pub(self) fn new(input: &'src str) -> Parser {
-        let mut stream = TokenStream::new(input);
+    fn new(input: &'src str) -> Parser<'src> {
+        Parser::new_at(input, 0)
+    }
+
+    fn new_at(input: &'src str, offset: usize) -> Parser<'src> {
+        let mut stream = TokenStream::new_at(input, offset);
 
         let tok = stream.next_token();
+        // There's an expression for every few tokens or so.
+        let exprs = Exprs::with_capacity(stream.tokens.len() / 4);
         Self { 
             src: input,
+            offset,
             stream,
             token: tok,
+            prev_end: offset,
+            scope: TypeScope::default(),
             errors: Vec::new(),
+            events: Vec::new(),
+            exprs,
+            outline: false,
+            condition: false,
         }
     }
 
     // Advances the token stream and returns the current token.
     pub(self) fn take(&mut self) -> Token {
         let tok = self.token;
-        self.token = self.stream.next_token();
+        self.bump();
         tok
     }
 
     // Advances the token stream without returning anything.
     pub(self) fn bump(&mut self) {
+        self.events.push(Event::Token(self.token));
+        self.prev_end = self.token.end;
         self.token = self.stream.next_token();
     }
 
+    // Bumps only the first `>` of a `>>`, like the one at the end of `Option<Option<i32>>`, and
+    // leaves the second one as the current token.
+    pub(self) fn bump_half_shift(&mut self) -> bool {
+        let tok = self.token;
+        if tok.kind != T!(">>") { return false }
+
+        self.events.push(Event::Token(Token::new(T!(">"), tok.start, tok.start + 1)));
+        self.prev_end = tok.start + 1;
+        self.token = Token::new(T!(">"), tok.start + 1, tok.end);
+        true
+    }
+
+    // Where a node can be started later on with `node_at()`, for when it isn't known what's being
+    // parsed until after its first child, like with binary expressions.
+    pub(self) fn checkpoint(&self) -> usize {
+        self.events.len()
+    }
+
+    // Wraps everything `f` consumes in a syntax node of `kind`.
+    pub(self) fn node<T>(&mut self, kind: SyntaxKind, f: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let checkpoint = self.checkpoint();
+        self.node_at(checkpoint, kind, f)
+    }
+
+    // Same as `node()`, but the node also wraps everything consumed since `checkpoint`. The node
+    // gets finished even if `f` fails so that the tree stays balanced.
+    pub(self) fn node_at<T>(
+        &mut self,
+        checkpoint: usize,
+        kind: SyntaxKind,
+        f: impl FnOnce(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        self.events.insert(checkpoint, Event::Start(kind));
+        let result = f(self);
+        self.events.push(Event::Finish);
+        result
+    }
+
+    // Parses `f` with `scope` as what's in scope. Items don't see the type parameters of whatever
+    // they're nested in, same as they don't see its variables.
+    pub(self) fn with_scope<T>(&mut self, scope: TypeScope, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = std::mem::replace(&mut self.scope, scope);
+        let result = f(self);
+        self.scope = outer;
+        result
+    }
+
+    // Parses `f` with `condition` as whether it's directly in an `if` condition. Anything in
+    // delimiters isn't, even if the delimiters are.
+    pub(self) fn with_condition<T>(&mut self, condition: bool, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = std::mem::replace(&mut self.condition, condition);
+        let result = f(self);
+        self.condition = outer;
+        result
+    }
+
+    // Span from `start` up to the end of the last token that got consumed.
+    pub(self) fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.prev_end)
+    }
+
     // Checks whether the current token matches `tok`.
     pub(self) fn check(&self, tok: TokenKind) -> bool {
         self.token.kind == tok
     }
 
     // Advances the token stream and returns the current token if the next token matches `tok`.
+    #[allow(dead_code)]
     pub(self) fn take_check(&mut self, tok: TokenKind) -> Option<Token> {
         if self.check(tok) {
             return Some(self.take());
@@ -61,8 +165,31 @@ impl<'src> Parser<'src> {
         if self.check(tok) {
             return Ok(self.take());
         }
+        Err(self.expected(tok))
+    }
+
+    // The error for when `tok` is missing. A `;` or `}` can just be put right after whatever came
+    // before it.
+    pub(self) fn expected(&mut self, tok: TokenKind) -> ParseError {
         let err = ParseError::ExpectedSingle { expected: tok, found: self.peek(0) };
-        Err(err)
+        let text = match tok {
+            T!(";") => ";",
+            T!("}") => "}",
+            _ => return err,
+        };
+        err.with_fix(Fix::insert(format!("insert `{text}`"), self.prev_end, text))
+    }
+
+    // The fix for an expression statement that's missing its `;`. A name followed by another
+    // name is more likely a misspelled keyword though, like `fnn main`.
+    pub(self) fn statement_fix(&self, expr: ExprId, end_token: Token) -> Fix {
+        let expr = &self.exprs[expr];
+        if let (Expression::Identifier(ident), T!("ID")) = (expr, end_token.kind) {
+            if let Some(keyword) = fix::closest(&ident.name.as_str(), KEYWORDS) {
+                return Fix::replace(format!("did you mean `{keyword}`?"), ident.span, keyword);
+            }
+        }
+        Fix::insert("insert `;`", expr.span().end, ";")
     }
 
     // Advances the token stream and returns if the next token matches `tok`.
@@ -80,8 +207,7 @@ impl<'src> Parser<'src> {
             self.bump();
             return Ok(());
         }
-        let err = ParseError::ExpectedSingle { expected: tok, found: self.peek(0) };
-        Err(err)
+        Err(self.expected(tok))
     }
 
     // Advances the token stream while the given predicate is true.
@@ -115,55 +241,395 @@ impl<'src> Parser<'src> {
         tok
     }
 
-    pub(self) fn get_lexeme(&self, tok: Token) -> &str {
-        &self.src[tok.start..tok.end]
+    pub(self) fn get_lexeme(&self, tok: Token) -> &'src str {
+        &self.src[tok.start - self.offset..tok.end - self.offset]
+    }
+
+    // The name an identifier token stands for, which is its lexeme in NFC.
+    pub(self) fn get_name(&self, tok: Token) -> Symbol {
+        Symbol::intern(&lex::normalize_identifier(self.get_lexeme(tok)))
+    }
+
+    // A name that might be qualified by the modules it's in, like `geom::Point`.
+    pub(self) fn parse_path(&mut self) -> ParseResult<(Vec<Symbol>, Span)> {
+        let start = self.peek(0).start;
+        let mut path = Vec::new();
+        loop {
+            let name = self.take_expect(T!("ID"))?;
+            path.push(self.get_name(name));
+            if !self.bump_check(T!("::")) { break }
+        }
+        Ok((path, self.span_from(start)))
+    }
+
+    // How many tokens the path starting at the current token is made of, without consuming it.
+    pub(self) fn peek_path_len(&mut self) -> usize {
+        let mut len = 1;
+        while self.peek(len).kind == T!("::") && self.peek(len + 1).kind == T!("ID") {
+            len += 2;
+        }
+        len
     }
 
     pub(self) fn recover_error(&mut self, err: ParseError) {
         self.errors.push(err);
     }
 
-    pub fn parse(input: &'src str) -> ASTree {
-        let mut parser = Parser::new(input);
+    // Parses a statement, or if it fails, reports the error and skips ahead to where the next
+    // statement probably starts. Whatever got skipped, along with what the statement had consumed,
+    // ends up in an error node, and the expressions it had parsed are dropped. `in_block` is
+    // whether a `}` closes what the statement is in.
+    pub(self) fn parse_statement_recover(&mut self, in_block: bool) -> Statement {
+        let start = self.token.start;
+        let checkpoint = self.checkpoint();
+        let consumed = self.events.len();
+        let exprs = self.exprs.len();
+        match self.parse_statement() {
+            Ok(statement) => statement,
+            Err(err) => {
+                self.errors.push(err);
+                self.exprs.truncate(exprs);
+                let progress = self.events.len() > consumed;
+                let _ = self.node_at(checkpoint, SyntaxKind::Error, |p| {
+                    p.synchronize(in_block, progress);
+                    Ok(())
+                });
+                Statement::Error { span: self.span_from(start) }
+            }
+        }
+    }
 
-        let mut statements = Vec::new();
+    // Skips tokens up to a `;` or `}`, which get skipped too, or up to a keyword that starts a
+    // statement. Balanced groups are skipped as a whole. If `progress` isn't set, at least one
+    // token gets skipped so that the same error doesn't come up again.
+    fn synchronize(&mut self, in_block: bool, mut progress: bool) {
         loop {
-            match parser.parse_statement() {
-                Ok(Statement::EOF) => break,
-                Ok(statement) => statements.push(statement),
-                Err(err) => {
-                    parser.errors.push(err);
-                    break
+            match self.token.kind {
+                T!("EOF") => return,
+                // The block's own `}` is left for the block to take.
+                T!("}") if in_block => return,
+                T!(";") | T!("}") => {
+                    self.bump();
+                    return
                 }
+                T!("fn") | T!("struct") | T!("enum") | T!("impl") | T!("trait") | T!("import") | T!("mod") | T!("pub") | T!("let")
+                    if progress => return,
+                _ => (),
             }
+            self.bump_group();
+            progress = true;
         }
+    }
+
+    // Where the balanced group the current token opens ends, if it opens one.
+    pub(self) fn group_end(&self) -> Option<usize> {
+        // The stream is always one token past the current one.
+        self.stream.group_end(self.stream.pos - 1)
+    }
+
+    // Bumps the current token, or the whole group if it opens a balanced one.
+    pub(self) fn bump_group(&mut self) {
+        let end = self.group_end().unwrap_or(self.stream.pos);
+        while self.stream.pos <= end && !self.check(T!("EOF")) {
+            self.bump();
+        }
+    }
+
+    // Parses every statement up until EOF, recovering from any errors along the way.
+    fn parse_statements(&mut self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        loop {
+            match self.parse_statement_recover(false) {
+                Statement::EOF => break,
+                statement => statements.push(statement),
+            }
+        }
+        statements
+    }
+
+    fn parse_file(&mut self) -> ASTree {
+        let statements = self.parse_statements();
 
-        // TODO: add actual error reporting.
         for statement in &statements {
-            let Statement::Expression { expr: _, end_token } = statement else { continue };
+            let Statement::Expression { expr, end_token, span } = *statement else { continue };
             if end_token.kind != TokenKind::Semi { 
-                parser.errors.push(ParseError::OuterExpression);
+                let fix = self.statement_fix(expr, end_token);
+                self.errors.push(ParseError::OuterExpression { span }.with_fix(fix));
             }
         }
+        self.check_delimiters();
 
-        for err in &parser.errors {
-            println!("{:?}", err);
+        ASTree::new(statements, std::mem::take(&mut self.exprs))
+    }
+
+    // Delimiters that were never closed properly are reported where they were opened, and
+    // anything the parser ran into around them is most likely just because of them.
+    fn check_delimiters(&mut self) {
+        let (errors, regions) = delimiter::check(self.src, &self.stream.tokens);
+        if errors.is_empty() { return }
+        self.errors.retain(|err| {
+            let start = err.span().start;
+            !regions.iter().any(|region| region.start <= start && start <= region.end)
+        });
+        self.errors.extend(errors);
+    }
+
+    pub fn parse(input: &'src str) -> (ASTree, Vec<ParseError>) {
+        let mut parser = Parser::new(input);
+        let tree = parser.parse_file();
+        (tree, parser.errors)
+    }
+
+    // Same as `parse()`, but function bodies are skipped by matching up their braces and come out
+    // as empty blocks, for when only the signatures of items are needed.
+    pub fn parse_outline(input: &'src str) -> (ASTree, Vec<ParseError>) {
+        let mut parser = Parser::new(input);
+        parser.outline = true;
+        let tree = parser.parse_file();
+        (tree, parser.errors)
+    }
+
+    // Parses `input` into a lossless syntax tree. The `ASTree` can be derived from it again with
+    // `syntax::lower::lower()`.
+    pub fn parse_syntax(input: &'src str) -> (SyntaxNode, Vec<ParseError>) {
+        let mut parser = Parser::new(input);
+        parser.parse_file();
+        let root = syntax::build(input, &parser.events, &parser.stream.tokens);
+        (root, parser.errors)
+    }
+
+    // Parses `input` as a single block or item and returns its node, for reparsing just that part
+    // of a file. Gives up if `input` turns out to be anything else.
+    pub fn parse_syntax_fragment(input: &'src str, kind: SyntaxKind) -> Option<(SyntaxNode, Vec<ParseError>)> {
+        let mut parser = Parser::new(input);
+        let parsed = match kind {
+            SyntaxKind::Block => parser.parse_block().is_ok(),
+            _ => parser.parse_statement().is_ok(),
+        };
+        // Delimiters have to match up within the fragment, since they're checked over the whole
+        // file otherwise.
+        let balanced = delimiter::check(input, &parser.stream.tokens).0.is_empty();
+        if !parsed || !balanced || !parser.check(TokenKind::EOF) {
+            return None;
+        }
+
+        let root = syntax::build(input, &parser.events, &parser.stream.tokens);
+        let mut children = root.children().into_iter();
+        match (children.next(), children.next()) {
+            (Some(SyntaxElement::Node(node)), None) if node.kind() == kind => Some((node, parser.errors)),
+            _ => None,
         }
+    }
+
+    // Same as `parse()`, but the last statement is allowed to be an expression without a `;` so
+    // that its value can be shown back to the user.
+    pub fn parse_interactive(input: &'src str) -> (ASTree, Vec<ParseError>) {
+        let mut parser = Parser::new(input);
+        let statements = parser.parse_statements();
+
+        if let Some((_, rest)) = statements.split_last() {
+            for statement in rest {
+                let Statement::Expression { expr, end_token, span } = *statement else { continue };
+                if end_token.kind != TokenKind::Semi { 
+                    let fix = parser.statement_fix(expr, end_token);
+                    parser.errors.push(ParseError::OuterExpression { span }.with_fix(fix));
+                }
+            }
+        }
+        parser.check_delimiters();
+
+        (ASTree::new(statements, parser.exprs), parser.errors)
+    }
 
-        ASTree::new(statements)
+    // Parses `input` as a single expression with nothing else after it, which comes back along
+    // with the expressions it's made of.
+    pub fn parse_expression(input: &'src str) -> Result<(ExprId, Exprs), Vec<ParseError>> {
+        let mut exprs = Exprs::new();
+        let expr = Parser::parse_expression_at(input, 0, &mut exprs)?;
+        Ok((expr, exprs))
+    }
+
+    // Same as `parse_expression()`, for `input` that starts `offset` bytes into the source. The
+    // expressions go into `exprs`.
+    pub(crate) fn parse_expression_at(input: &'src str, offset: usize, exprs: &mut Exprs) -> Result<ExprId, Vec<ParseError>> {
+        let mut parser = Parser::new_at(input, offset);
+        parser.exprs = std::mem::take(exprs);
+        let result = parser.parse_expr(0);
+        *exprs = std::mem::take(&mut parser.exprs);
+        match result {
+            Ok(expr) => {
+                if let Err(err) = parser.bump_expect(TokenKind::EOF) {
+                    parser.errors.push(err);
+                }
+                match parser.errors.is_empty() {
+                    true => Ok(expr),
+                    false => Err(parser.errors),
+                }
+            }
+            Err(err) => {
+                parser.errors.push(err);
+                Err(parser.errors)
+            }
+        }
     }
 }
 
-#[derive(Debug)]
+// The types an item can name besides the ones declared at the top level.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TypeScope {
+    // The type parameters of the item and of the `impl` it's in.
+    pub generics: Vec<Symbol>,
+    // What `Self` stands for inside of an `impl` or a `trait`.
+    pub self_type: Option<Type>,
+}
+
+impl TypeScope {
+    // The scope inside of an item with `generics` that's declared in this scope.
+    pub fn with_generics(&self, generics: &[Symbol]) -> Self {
+        let mut scope = self.clone();
+        scope.generics.extend(generics.iter().cloned());
+        scope
+    }
+
+    // The type a name refers to, if it's a type parameter or `Self`.
+    pub fn resolve(&self, name: Symbol) -> Option<Type> {
+        if self.generics.contains(&name) {
+            return Some(Type::Generic { name });
+        }
+        match &*name.as_str() {
+            "Self" => self.self_type.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn self_type(&self) -> Type {
+        self.self_type.clone().unwrap_or_else(|| Type::UserDefined { name: "Self".into() })
+    }
+}
+
+#[derive(Clone, Debug)]
 pub enum ParseError {
     ExpectedSingle{expected: TokenKind, found: Token},
     ExpectedAlternatives{expected: Box<[TokenKind]>, found: Token},
     ExpectedNode{expected: String, found: Token},
-    // UnclosedDelimiter{delimiter: Token},
-    OuterExpression,
+    UnclosedDelimiter{delimiter: Token},
+    // `delimiter` closed by the wrong kind of closer.
+    MismatchedDelimiter{delimiter: Token, found: Token},
+    // A closer with no group for it to close.
+    UnexpectedDelimiter{found: Token},
+    OuterExpression{span: Span},
+    InvalidLiteral{error: LiteralError, span: Span},
+    // `error` along with a change that would fix it.
+    Fixable{error: Box<ParseError>, fix: Fix},
+}
+
+impl ParseError {
+    pub fn with_fix(self, fix: Fix) -> ParseError {
+        ParseError::Fixable { error: Box::new(self), fix }
+    }
+
+    pub fn fix(&self) -> Option<&Fix> {
+        match self {
+            ParseError::Fixable { fix, .. } => Some(fix),
+            _ => None,
+        }
+    }
+
+    // The error itself, without the fix for it if it has one.
+    pub fn without_fix(&self) -> &ParseError {
+        match self {
+            ParseError::Fixable { error, .. } => error,
+            error => error,
+        }
+    }
+
+    // Where in the source the error should be reported.
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::ExpectedSingle { found, .. }
+            | ParseError::ExpectedAlternatives { found, .. }
+            | ParseError::ExpectedNode { found, .. }
+            | ParseError::MismatchedDelimiter { found, .. }
+            | ParseError::UnexpectedDelimiter { found } => found.span(),
+            ParseError::UnclosedDelimiter { delimiter } => delimiter.span(),
+            ParseError::OuterExpression { span } | ParseError::InvalidLiteral { span, .. } => *span,
+            ParseError::Fixable { error, .. } => error.span(),
+        }
+    }
+
+    // The same error with every offset in it passed through `f`.
+    pub fn map_offsets(&self, f: impl Fn(usize) -> usize) -> ParseError {
+        self.map_offsets_with(&f)
+    }
+
+    fn map_offsets_with(&self, f: &dyn Fn(usize) -> usize) -> ParseError {
+        let token = |tok: &Token| Token::new(tok.kind, f(tok.start), f(tok.end));
+        match self {
+            ParseError::ExpectedSingle { expected, found } => {
+                ParseError::ExpectedSingle { expected: *expected, found: token(found) }
+            }
+            ParseError::ExpectedAlternatives { expected, found } => {
+                ParseError::ExpectedAlternatives { expected: expected.clone(), found: token(found) }
+            }
+            ParseError::ExpectedNode { expected, found } => {
+                ParseError::ExpectedNode { expected: expected.clone(), found: token(found) }
+            }
+            ParseError::UnclosedDelimiter { delimiter } => {
+                ParseError::UnclosedDelimiter { delimiter: token(delimiter) }
+            }
+            ParseError::MismatchedDelimiter { delimiter, found } => {
+                ParseError::MismatchedDelimiter { delimiter: token(delimiter), found: token(found) }
+            }
+            ParseError::UnexpectedDelimiter { found } => ParseError::UnexpectedDelimiter { found: token(found) },
+            ParseError::OuterExpression { span } => {
+                ParseError::OuterExpression { span: Span::new(f(span.start), f(span.end)) }
+            }
+            ParseError::InvalidLiteral { error, span } => {
+                ParseError::InvalidLiteral { error: error.clone(), span: Span::new(f(span.start), f(span.end)) }
+            }
+            ParseError::Fixable { error, fix } => {
+                ParseError::Fixable { error: Box::new(error.map_offsets_with(f)), fix: fix.map_offsets(f) }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::ExpectedSingle { expected, found } => write!(
+                f, "expected {:?}, found {:?} at {}..{}",
+                expected, found.kind, found.start, found.end
+            ),
+            ParseError::ExpectedAlternatives { expected, found } => write!(
+                f, "expected one of {:?}, found {:?} at {}..{}",
+                expected, found.kind, found.start, found.end
+            ),
+            ParseError::ExpectedNode { expected, found } => write!(
+                f, "expected {}, found {:?} at {}..{}",
+                expected, found.kind, found.start, found.end
+            ),
+            ParseError::UnclosedDelimiter { delimiter } => write!(
+                f, "unclosed delimiter `{}` at {}..{}",
+                delimiter::text(delimiter.kind), delimiter.start, delimiter.end
+            ),
+            ParseError::MismatchedDelimiter { delimiter, found } => write!(
+                f, "mismatched closing delimiter `{}` at {}..{} for `{}` at {}..{}",
+                delimiter::text(found.kind), found.start, found.end,
+                delimiter::text(delimiter.kind), delimiter.start, delimiter.end
+            ),
+            ParseError::UnexpectedDelimiter { found } => write!(
+                f, "unexpected closing delimiter `{}` at {}..{}",
+                delimiter::text(found.kind), found.start, found.end
+            ),
+            ParseError::OuterExpression { .. } => write!(f, "expressions outside of a block must end with `;`"),
+            ParseError::InvalidLiteral { error, span } => write!(f, "{error} at {}..{}", span.start, span.end),
+            ParseError::Fixable { error, .. } => error.fmt(f),
+        }
+    }
 }
 
-pub(self) type ParseResult<T> = Result<T, ParseError>;
+type ParseResult<T> = Result<T, ParseError>;
 
 #[cfg(test)]
 mod tests;
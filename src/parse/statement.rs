@@ -1,88 +1,171 @@
-use crate::parse::{Parser, ParseError, ParseResult};
+use crate::parse::{Parser, ParseError, ParseResult, TypeScope};
 
 use crate::ast::token::T;
 use crate::ast::token::TokenKind;
-use crate::ast::Statement;
+use crate::ast::{Statement, Symbol, Type};
 use crate::ast::{FunctionStatement, StructStatement, EnumStatement, LetStatement};
+use crate::ast::{ImplStatement, TraitStatement, TraitMethod, ImportStatement, BlockExpression};
+use crate::syntax::SyntaxKind;
 
 impl<'src> Parser<'src> {
     pub(super) fn parse_statement(&mut self) -> ParseResult<Statement> {
-        match self.peek(0).kind {
+        // The item parsers take the `pub` themselves so that it ends up in the item's node.
+        let public = self.check(T!("pub"));
+        let item = self.peek(usize::from(public));
+        if public && !matches!(item.kind, T!("fn") | T!("struct") | T!("enum") | T!("trait")) {
+            let err = ParseError::ExpectedAlternatives {
+                expected: [T!("fn"), T!("struct"), T!("enum"), T!("trait")].into(),
+                found: item,
+            };
+            return Err(err);
+        }
+
+        match item.kind {
             T!("fn") => {
-                let item = self.parse_function()?;
+                let item = self.node(SyntaxKind::Function, Self::parse_function)?;
                 Ok(Statement::Function(item))
             }
 
             T!("struct") => {
-                let item = self.parse_struct()?;
+                let item = self.node(SyntaxKind::Struct, Self::parse_struct)?;
                 Ok(Statement::Struct(item))
             }
 
             T!("enum") => {
-                let item = self.parse_enum()?;
+                let item = self.node(SyntaxKind::Enum, Self::parse_enum)?;
                 Ok(Statement::Enum(item))
             }
             
+            T!("impl") => {
+                let item = self.node(SyntaxKind::Impl, Self::parse_impl)?;
+                Ok(Statement::Impl(item))
+            }
+
+            T!("trait") => {
+                let item = self.node(SyntaxKind::Trait, Self::parse_trait)?;
+                Ok(Statement::Trait(item))
+            }
+
+            T!("import") | T!("mod") => {
+                let item = self.node(SyntaxKind::Import, Self::parse_import)?;
+                Ok(Statement::Import(item))
+            }
+
             T!("let") => {
-                let item = self.parse_let()?;
+                let item = self.node(SyntaxKind::Let, Self::parse_let)?;
                 Ok(Statement::Let(item))
             }
 
             T!("EOF") => Ok(Statement::EOF),
 
-            _ => match self.parse_expr(0) {
+            _ => self.node(SyntaxKind::ExprStatement, |p| match p.parse_expr(0) {
                 Ok(expr) => {
-                    let end_token = self.peek(0);
-                    if end_token.kind == T!(";") { self.bump() };
-                    Ok(Statement::Expression { 
-                        expr,
-                        end_token,
-                    })
+                    let end_token = p.peek(0);
+                    if end_token.kind == T!(";") { p.bump() };
+                    Ok(Statement::expression(expr, end_token, &p.exprs))
                 },
                 Err(err) => Err(err)
-            }
+            })
         }
     }
 
     pub(super) fn parse_function(&mut self) -> ParseResult<FunctionStatement> {
-        self.bump();
+        let start = self.peek(0).start;
+        let public = self.bump_check(T!("pub"));
+
+        let function = self.parse_function_in(&TypeScope::default())?;
+        Ok(FunctionStatement { public, span: self.span_from(start), ..function })
+    }
+
+    // A function declared inside of something with `outer` in scope, like a method in an `impl`.
+    fn parse_function_in(&mut self, outer: &TypeScope) -> ParseResult<FunctionStatement> {
+        let method = self.parse_method(outer, false)?;
+        let TraitMethod { name, generics, arguments, return_type, block, name_span, span } = method;
+        let Some(block) = block else { unreachable!("functions always have a body") };
+        Ok(FunctionStatement { public: false, name, generics, return_type, arguments, block, name_span, span })
+    }
+
+    // Parses a function, or if `optional_body` is set, a trait method that might end at a `;`
+    // instead of having a body.
+    fn parse_method(&mut self, outer: &TypeScope, optional_body: bool) -> ParseResult<TraitMethod> {
+        let start = self.take().start;
 
         let name = self.take_expect(T!("ID"))?;
-        let arguments = self.parse_params(T!("("), T!(")"))?;
+        let generics = self.parse_generic_params()?;
 
-        self.bump_expect(T!("->"))?;
+        let (arguments, return_type, block) = self.with_scope(outer.with_generics(&generics), |p| {
+            let arguments = p.parse_params(T!("("), T!(")"))?;
 
-        let return_type = self.parse_type()?;
+            p.bump_expect(T!("->"))?;
 
-        let block = self.parse_block()?;
-        let name = self.get_lexeme(name);
+            let return_type = p.parse_type()?;
 
-        Ok(FunctionStatement { name: name.into(), return_type, arguments, block })
+            let block = match optional_body && p.bump_check(T!(";")) {
+                true => None,
+                false => Some(p.parse_body()?),
+            };
+            Ok((arguments, return_type, block))
+        })?;
+        let name_span = name.span();
+        let name = self.get_name(name);
+
+        Ok(TraitMethod {
+            name,
+            generics,
+            arguments,
+            return_type,
+            block,
+            name_span,
+            span: self.span_from(start),
+        })
+    }
+
+    // A function's body. When only parsing the outline it's skipped, unless its braces don't match
+    // up and it has to be parsed to see where it ends.
+    fn parse_body(&mut self) -> ParseResult<BlockExpression> {
+        if !self.outline || !self.check(T!("{")) || self.group_end().is_none() {
+            return self.parse_block();
+        }
+        let start = self.peek(0).start;
+        self.node(SyntaxKind::Block, |p| {
+            p.bump_group();
+            Ok(BlockExpression { statements: Vec::new(), expression: None, span: p.span_from(start) })
+        })
     }
 
     pub(super) fn parse_struct(&mut self) -> ParseResult<StructStatement> {
+        let start = self.peek(0).start;
+        let public = self.bump_check(T!("pub"));
         self.bump();
 
         let name = self.take_expect(T!("ID"))?;
-        let fields = self.parse_params(T!("{"), T!("}"))?;
+        let generics = self.parse_generic_params()?;
+        let scope = TypeScope::default().with_generics(&generics);
+        let fields = self.with_scope(scope, |p| p.parse_params(T!("{"), T!("}")))?;
 
-        let name = self.get_lexeme(name);
-        Ok(StructStatement { name: name.into(), fields })
+        let name_span = name.span();
+        let name = self.get_name(name);
+        Ok(StructStatement { public, name, generics, fields, name_span, span: self.span_from(start) })
     }
 
     pub(super) fn parse_enum(&mut self) -> ParseResult<EnumStatement> {
+        let start = self.peek(0).start;
+        let public = self.bump_check(T!("pub"));
         self.bump();
 
         let name = self.take_expect(T!("ID"))?;
+        let generics = self.parse_generic_params()?;
         self.bump_expect(T!("{"))?;
 
         let mut variants = Vec::new();
+        let mut variant_spans = Vec::new();
 
         loop {
             match self.peek(0).kind {
                 T!("ID") => {
                     let variant = self.take();
-                    let variant = String::from(self.get_lexeme(variant));
+                    variant_spans.push(variant.span());
+                    let variant = self.get_name(variant);
                     variants.push(variant);
                 },
 
@@ -104,20 +187,131 @@ impl<'src> Parser<'src> {
         // There is no need to check for `T!(CloseBrace)` here since the only way to break
         // out of the loop is with the parser finding a `T!(CloseBrace)` in the loop which
         // then bumps it.
-        let name = self.get_lexeme(name);
-        Ok(EnumStatement { name: name.into(), variants })
+        let name_span = name.span();
+        let name = self.get_name(name);
+        Ok(EnumStatement { public, name, generics, variants, variant_spans, name_span, span: self.span_from(start) })
     }
 
-    pub(super) fn parse_let(&mut self) -> ParseResult<LetStatement> {
+    // `impl Type { ... }` or `impl Trait for Type { ... }`
+    pub(super) fn parse_impl(&mut self) -> ParseResult<ImplStatement> {
+        let start = self.take().start;
+
+        let generics = self.parse_generic_params()?;
+        let mut scope = TypeScope::default().with_generics(&generics);
+
+        let path_len = self.peek_path_len();
+        let trait_name = match (self.peek(0).kind, self.peek(path_len).kind) {
+            (T!("ID"), T!("for")) => {
+                let (path, _) = self.parse_path()?;
+                self.bump();
+                Some(Symbol::join(&path, "::"))
+            }
+            _ => None,
+        };
+
+        let self_type = self.with_scope(scope.clone(), Self::parse_type)?;
+        scope.self_type = Some(self_type.clone());
+
+        self.bump_expect(T!("{"))?;
+        let mut methods = Vec::new();
+        while !self.bump_check(T!("}")) {
+            self.expect_method()?;
+            methods.push(self.node(SyntaxKind::Function, |p| p.parse_function_in(&scope))?);
+        }
+
+        Ok(ImplStatement { generics, trait_name, self_type, methods, span: self.span_from(start) })
+    }
+
+    pub(super) fn parse_trait(&mut self) -> ParseResult<TraitStatement> {
+        let start = self.peek(0).start;
+        let public = self.bump_check(T!("pub"));
         self.bump();
 
         let name = self.take_expect(T!("ID"))?;
+        // Every implementation has its own `Self`, so in here it's only known to be some type.
+        let scope = TypeScope { self_type: Some(Type::Generic { name: "Self".into() }), ..Default::default() };
+
+        self.bump_expect(T!("{"))?;
+        let mut methods = Vec::new();
+        while !self.bump_check(T!("}")) {
+            self.expect_method()?;
+            methods.push(self.node(SyntaxKind::Function, |p| p.parse_method(&scope, true))?);
+        }
+
+        let name_span = name.span();
+        let name = self.get_name(name);
+        Ok(TraitStatement { public, name, methods, name_span, span: self.span_from(start) })
+    }
+
+    // Only methods can go in an `impl` or a `trait`.
+    fn expect_method(&mut self) -> ParseResult<()> {
+        if self.check(T!("fn")) {
+            return Ok(());
+        }
+        Err(ParseError::ExpectedAlternatives { expected: [T!("fn"), T!("}")].into(), found: self.peek(0) })
+    }
+
+    // The optional `<A, B>` after an item's name.
+    pub(super) fn parse_generic_params(&mut self) -> ParseResult<Vec<Symbol>> {
+        if !self.check(T!("<")) {
+            return Ok(Vec::new());
+        }
+
+        self.node(SyntaxKind::GenericParams, |p| {
+            p.bump();
+
+            let mut generics = Vec::new();
+            while !p.bump_check(T!(">")) {
+                let name = p.take_expect(T!("ID"))?;
+                generics.push(p.get_name(name));
+
+                if p.bump_check(T!(",")) { continue }
+                if p.bump_check(T!(">")) { break }
+
+                let err = ParseError::ExpectedAlternatives {
+                    expected: [T!(","), T!(">")].into(),
+                    found: p.peek(0),
+                };
+                return Err(err);
+            }
+            Ok(generics)
+        })
+    }
+
+    pub(super) fn parse_import(&mut self) -> ParseResult<ImportStatement> {
+        let keyword = self.take();
+
+        // A `mod` names a single module, the path is only for `import`s.
+        let path = match keyword.kind {
+            T!("mod") => {
+                let name = self.take_expect(T!("ID"))?;
+                vec![self.get_name(name)]
+            }
+            _ => self.parse_path()?.0,
+        };
+        self.bump_expect(T!(";"))?;
+
+        Ok(ImportStatement { path, child: keyword.kind == T!("mod"), span: self.span_from(keyword.start) })
+    }
+
+    pub(super) fn parse_let(&mut self) -> ParseResult<LetStatement> {
+        let start = self.take().start;
+
+        let name = self.take_expect(T!("ID"))?;
+
+        // Optional type annotation.
+        let var_type = match self.bump_check(T!(":")) {
+            true => Some(self.parse_type()?),
+            false => None,
+        };
+
         self.bump_expect(T!("="))?;
         let value = self.parse_expr(0)?;
         self.bump_expect(T!(";"))?;
 
-        let name = self.get_lexeme(name);
+        let name_span = name.span();
+        let name = self.get_name(name);
 
-        Ok(LetStatement { name: name.into(), value })
+        Ok(LetStatement { name, var_type, value, name_span, span: self.span_from(start) })
     }
 }
@@ -1,4 +1,8 @@
+use std::cell::OnceCell;
+use std::io::{self, BufRead};
+
 use crate::lex::{self, lexer::Lexer};
+use crate::parse::tree::{self, TokenTree};
 
 use crate::ast::token as ast_token;
 use crate::ast::token::{LiteralKind, OpKind};
@@ -7,6 +11,9 @@ use crate::ast::token::{LiteralKind, OpKind};
 pub struct TokenStream{
     pub pos: usize,
     pub tokens: Vec<ast_token::Token>,
+    // For each token, the index right after the balanced group it opens, if it opens one. Only
+    // worked out the first time it's needed, since most parses never skip over a group.
+    group_ends: OnceCell<Vec<Option<usize>>>,
 }
 
 impl TokenStream {
@@ -22,9 +29,37 @@ impl TokenStream {
         Self {
             pos: 0,
             tokens,
+            group_ends: OnceCell::new(),
         }
     }
 
+    // The tokens grouped into balanced `()`, `[]` and `{}`.
+    pub fn trees(&self) -> Vec<TokenTree> {
+        tree::build(&self.tokens)
+    }
+
+    // The index right after the group that the token at `index` opens, if it opens one that's
+    // closed by the right closer.
+    pub fn group_end(&self, index: usize) -> Option<usize> {
+        let group_ends = self.group_ends.get_or_init(|| {
+            let mut group_ends = vec![None; self.tokens.len()];
+            index_groups(&self.trees(), 0, &mut group_ends);
+            group_ends
+        });
+        group_ends.get(index).copied().flatten()
+    }
+
+    // Same as `new()`, but for `input` that starts `offset` bytes into the source, like an
+    // expression embedded in a string.
+    pub fn new_at(input: &str, offset: usize) -> TokenStream {
+        let mut stream = TokenStream::new(input);
+        for token in &mut stream.tokens {
+            token.start += offset;
+            token.end += offset;
+        }
+        stream
+    }
+
     // Returns the token in the current position and advances.
     pub fn next_token(&mut self) -> ast_token::Token {
         match self.tokens.get(self.pos) {
@@ -51,6 +86,127 @@ impl TokenStream {
     }
 }
 
+// How many bytes past the end of a token have to have been read before it's taken as it is. The
+// lexer looks at most three characters ahead, and the longest token is made of three lexer tokens.
+const LOOKAHEAD: usize = 16;
+
+// Tokens lexed a bit at a time from `reader`, for sources too big to hold in memory all at once.
+// Their offsets are still from the start of the whole input. Ends with an EOF token, like
+// `TokenStream` does.
+pub struct TokenReader<R> {
+    reader: R,
+    // What's been read so far, minus what was dropped from the front, which is `base` bytes long.
+    text: String,
+    base: usize,
+    // Bytes at the end of what was read that don't make up a whole character yet.
+    partial: Vec<u8>,
+    // Where in `text` the next token gets lexed from.
+    pos: usize,
+    exhausted: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> TokenReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, text: String::new(), base: 0, partial: Vec::new(), pos: 0, exhausted: false, finished: false }
+    }
+
+    // The text of `tok`, if it's still being held on to. The token that was read last always is.
+    pub fn lexeme(&self, tok: &ast_token::Token) -> Option<&str> {
+        let start = tok.start.checked_sub(self.base)?;
+        self.text.get(start..tok.end - self.base)
+    }
+
+    // How many bytes of the input are being held on to.
+    pub fn buffered(&self) -> usize {
+        self.text.len() + self.partial.len()
+    }
+
+    fn next_token(&mut self) -> io::Result<Option<ast_token::Token>> {
+        if self.finished { return Ok(None) }
+
+        // A token that ends too close to the end of what's been read might be part of a longer
+        // one, so it's lexed again once more has been read. At least as much again as what's
+        // waiting gets read each time, so that a long comment or string isn't lexed from the start
+        // over and over for every chunk that goes into it.
+        let tok = loop {
+            let tok = StringReader::new(&self.text[self.pos..]).take();
+            let complete = tok.kind != ast_token::TokenKind::EOF && self.pos + tok.end + LOOKAHEAD <= self.text.len();
+            if complete || self.exhausted { break tok }
+
+            let wanted = self.text.len() + (self.text.len() - self.pos).max(LOOKAHEAD);
+            while self.text.len() < wanted && !self.exhausted {
+                self.fill()?;
+            }
+        };
+
+        let start = self.pos + tok.start;
+        self.pos += tok.end;
+        let tok = ast_token::Token::new(tok.kind, self.base + start, self.base + self.pos);
+        self.finished = tok.kind == ast_token::TokenKind::EOF;
+
+        // Everything before the token can go, though not every time so that it doesn't get
+        // copied over and over.
+        if start > self.text.len() / 2 {
+            self.text.drain(..start);
+            self.base += start;
+            self.pos -= start;
+        }
+        Ok(Some(tok))
+    }
+
+    // Reads whatever `reader` has next onto the end of `text`.
+    fn fill(&mut self) -> io::Result<()> {
+        let chunk = self.reader.fill_buf()?;
+        if chunk.is_empty() {
+            self.exhausted = true;
+            if !self.partial.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "stream ended in the middle of a character"));
+            }
+            return Ok(());
+        }
+        self.partial.extend_from_slice(chunk);
+        let length = chunk.len();
+        self.reader.consume(length);
+
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(text) => text.len(),
+            // The last character just hasn't been read all the way yet.
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+        let text = std::str::from_utf8(&self.partial[..valid]).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        self.text.push_str(text);
+        self.partial.drain(..valid);
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for TokenReader<R> {
+    type Item = io::Result<ast_token::Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.next_token().transpose();
+        if let Some(Err(_)) = result {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+// Fills in `group_ends` for `trees`, the first of which starts at `index`.
+fn index_groups(trees: &[TokenTree], mut index: usize, group_ends: &mut [Option<usize>]) {
+    for tree in trees {
+        if let TokenTree::Group(group) = tree {
+            index_groups(&group.trees, index + 1, group_ends);
+            if group.is_balanced() {
+                group_ends[index] = Some(index + group.token_count());
+            }
+        }
+        index += tree.token_count();
+    }
+}
+
 #[derive(Clone, Debug)]
 struct StringReader<'a> {
     src: &'a str,
@@ -64,7 +220,7 @@ struct StringReader<'a> {
 
 
 impl<'a> StringReader<'a> {
-    fn new(input: &'a str) -> StringReader {
+    fn new(input: &'a str) -> StringReader<'a> {
         Self { 
             src: input,
             lex: Lexer::new(input),
@@ -86,7 +242,7 @@ impl<'a> StringReader<'a> {
             self.pos += lex_token.length;
 
             let kind = match lex_token.kind {
-                lex::TokenKind::Whitespace => {
+                lex::TokenKind::Whitespace | lex::TokenKind::LineComment => {
                     // println!("{:?}: {}", lex_token, start); 
                     continue
                 }
@@ -94,7 +250,7 @@ impl<'a> StringReader<'a> {
                 lex::TokenKind::Identifier => self.identifier_or_other(start, lex_token.length),
 
                 lex::TokenKind::Semi => ast_token::TokenKind::Semi,
-                lex::TokenKind::Colon => ast_token::TokenKind::Colon,
+                lex::TokenKind::Colon => self.colon(),
                 lex::TokenKind::Comma => ast_token::TokenKind::Comma,
                 lex::TokenKind::Dot => ast_token::TokenKind::Dot,
                 lex::TokenKind::OpenParen => ast_token::TokenKind::OpenParen,
@@ -123,10 +279,7 @@ impl<'a> StringReader<'a> {
                   | lex::TokenKind::FSlash
                   | lex::TokenKind::Percent) => self.operator(op),
 
-                lex::TokenKind::Unknown => {
-                    let end = start+lex_token.length;
-                    panic!("Found Unknown Token: {} at {} -> {}.", &self.src[start..end], start, end)
-                }
+                lex::TokenKind::Unknown => ast_token::TokenKind::Unknown,
 
             };
             let end = self.pos;
@@ -144,6 +297,12 @@ impl<'a> StringReader<'a> {
             "let" => ast_token::TokenKind::Let,
             "if" => ast_token::TokenKind::If,
             "else" => ast_token::TokenKind::Else,
+            "impl" => ast_token::TokenKind::Impl,
+            "trait" => ast_token::TokenKind::Trait,
+            "for" => ast_token::TokenKind::For,
+            "pub" => ast_token::TokenKind::Pub,
+            "import" => ast_token::TokenKind::Import,
+            "mod" => ast_token::TokenKind::Mod,
 
             "true" => ast_token::TokenKind::Literal { kind: LiteralKind::Bool },
             "false" => ast_token::TokenKind::Literal { kind: LiteralKind::Bool },
@@ -152,6 +311,17 @@ impl<'a> StringReader<'a> {
         }
     }
 
+    // `::` if the next colon is right after this one.
+    fn colon(&mut self) -> ast_token::TokenKind {
+        let peek = self.lex.next_token();
+        if peek.kind == lex::TokenKind::Colon {
+            self.pos += peek.length;
+            return ast_token::TokenKind::ColonColon;
+        }
+        self.reserved_lex_token = Some(peek);
+        ast_token::TokenKind::Colon
+    }
+
     fn operator(&mut self, op: lex::TokenKind) -> ast_token::TokenKind {
         let mut peek = self.lex.next_token();
         let op = match op {
@@ -1,4 +1,6 @@
 use super::stream::TokenStream;
+use super::{Parser, ParseError, LiteralError};
+use crate::ast::Span;
 use crate::ast::token::*;
 
 fn stream_check(s: &str, expected: TokenKind) {
@@ -12,8 +14,16 @@ fn identifier_keyword_tokens() {
     stream_check("fn", TokenKind::Fn);
     stream_check("struct", TokenKind::Struct);
     stream_check("enum", TokenKind::Enum);
+    stream_check("impl", TokenKind::Impl);
+    stream_check("trait", TokenKind::Trait);
+    stream_check("for", TokenKind::For);
+    stream_check("pub", TokenKind::Pub);
+    stream_check("import", TokenKind::Import);
+    stream_check("mod", TokenKind::Mod);
     stream_check("let", TokenKind::Let);
     stream_check("->", TokenKind::RArrow);
+    stream_check("::", TokenKind::ColonColon);
+    stream_check(": :", TokenKind::Colon);
     stream_check("identifier", TokenKind::Identifier);
 }
 
@@ -107,6 +117,126 @@ fn number_literal_tokens() {
     );
 }
 
+#[test]
+fn number_literals() {
+    use crate::ast::{Expression, LitKind, Type, IntKind, FloatKind};
+
+    let literal = |s: &str| match Parser::parse_expression(s) {
+        Ok((expr, mut exprs)) => match exprs.take(expr) {
+            Expression::Literal(literal) => literal.kind,
+            expr => panic!("{expr:?}"),
+        },
+        result => panic!("{result:?}"),
+    };
+    let u8 = Some(Type::Int { sign: false, kind: IntKind::Bit8 });
+    let f32 = Some(Type::Float { kind: FloatKind::Bit32 });
+
+    assert!(matches!(literal("1_000"), LitKind::Int { value: 1000, ty: None }));
+    assert!(matches!(literal("0xFF_ff"), LitKind::Int { value: 0xffff, ty: None }));
+    assert!(matches!(literal("0o17"), LitKind::Int { value: 0o17, ty: None }));
+    assert!(matches!(literal("0b1010"), LitKind::Int { value: 10, ty: None }));
+    assert!(matches!(literal("0xffu8"), LitKind::Int { value: 255, ty } if ty == u8));
+    assert!(matches!(literal("3i64"), LitKind::Int { value: 3, ty: Some(Type::Int { sign: true, kind: IntKind::Bit64 }) }));
+    assert!(matches!(literal("1_0.5_0"), LitKind::Float { value, ty: None } if value == 10.5));
+    assert!(matches!(literal("1.5f32"), LitKind::Float { value, ty } if value == 1.5 && ty == f32));
+    assert!(matches!(literal("2f32"), LitKind::Float { value, ty } if value == 2.0 && ty == f32));
+    assert!(matches!(literal("1e10"), LitKind::Float { value, ty: None } if value == 1e10));
+    assert!(matches!(literal("2.5E-3"), LitKind::Float { value, ty: None } if value == 2.5e-3));
+    assert!(matches!(literal("1_000.0"), LitKind::Float { value, ty: None } if value == 1000.0));
+    assert!(matches!(literal("1_0e1_0f32"), LitKind::Float { value, ty } if value == 10e10 && ty == f32));
+
+    let error = |s: &str| match Parser::parse_expression(s) {
+        Err(errors) => match &errors[..] {
+            [ParseError::InvalidLiteral { error, span }] => {
+                assert_eq!(*span, Span::new(0, s.len()));
+                error.clone()
+            }
+            errors => panic!("{errors:?}"),
+        },
+        Ok(expr) => panic!("{expr:?}"),
+    };
+    assert_eq!(error("0x"), LiteralError::NoDigits);
+    assert_eq!(error("0b102"), LiteralError::InvalidDigit { digit: '2', base: 2 });
+    assert_eq!(error("0o8"), LiteralError::InvalidDigit { digit: '8', base: 8 });
+    assert_eq!(error("10u7"), LiteralError::InvalidSuffix { suffix: "u7".into() });
+    assert_eq!(error("1.5u8"), LiteralError::InvalidSuffix { suffix: "u8".into() });
+    assert_eq!(error("0b1f32"), LiteralError::InvalidSuffix { suffix: "f32".into() });
+    assert_eq!(error("0x1_0000_0000_0000_0000_0000_0000_0000_0000"), LiteralError::TooLarge);
+    assert_eq!(error("1e"), LiteralError::InvalidSuffix { suffix: "e".into() });
+    assert_eq!(error("1e999"), LiteralError::FloatTooLarge);
+    assert_eq!(error("1e39f32"), LiteralError::FloatTooLarge);
+    assert_eq!(error("\"abc"), LiteralError::Unterminated { quote: '"' });
+    assert_eq!(error("'"), LiteralError::Unterminated { quote: '\'' });
+}
+
+#[test]
+fn escaped_literals() {
+    use crate::ast::{Expression, LitKind};
+
+    let literal = |s: &str| match Parser::parse_expression(s) {
+        Ok((expr, mut exprs)) => match exprs.take(expr) {
+            Expression::Literal(literal) => literal.kind,
+            expr => panic!("{expr:?}"),
+        },
+        result => panic!("{result:?}"),
+    };
+    assert!(matches!(literal(r#""a\n\t\"b\\""#), LitKind::Str(s) if s == "a\n\t\"b\\"));
+    assert!(matches!(literal(r#""\x41\u{e9}\0""#), LitKind::Str(s) if s == "A\u{e9}\0"));
+    assert!(matches!(literal(r"'\''"), LitKind::Char('\'')));
+    assert!(matches!(literal(r"'\x41'"), LitKind::Char('A')));
+    assert!(matches!(literal(r"'\u{1F600}'"), LitKind::Char('\u{1F600}')));
+
+    let error = |s: &str| match Parser::parse_expression(s) {
+        Err(errors) => match &errors[..] {
+            [ParseError::InvalidLiteral { error, .. }] => error.clone(),
+            errors => panic!("{errors:?}"),
+        },
+        Ok(expr) => panic!("{expr:?}"),
+    };
+    assert_eq!(error(r#""\q""#), LiteralError::UnknownEscape { escape: 'q' });
+    assert_eq!(error(r"'\x80'"), LiteralError::InvalidEscape { escape: r"\x80".into() });
+    assert_eq!(error(r"'\x4'"), LiteralError::InvalidEscape { escape: r"\x4".into() });
+    assert_eq!(error(r#""\u{110000}""#), LiteralError::InvalidEscape { escape: r"\u{110000}".into() });
+    assert_eq!(error(r#""\u{}""#), LiteralError::InvalidEscape { escape: r"\u{}".into() });
+    assert_eq!(error("'ab'"), LiteralError::CharLength);
+}
+
+#[test]
+fn interpolated_and_raw_literals() {
+    use crate::ast::{Expression, LiteralExpression, LitKind, StrPart};
+
+    let literal = |s: &str| match Parser::parse_expression(s) {
+        Ok((expr, mut exprs)) => match exprs.take(expr) {
+            Expression::Literal(literal) => literal.kind,
+            expr => panic!("{expr:?}"),
+        },
+        result => panic!("{result:?}"),
+    };
+    let (expr, exprs) = Parser::parse_expression(r#""hello {name}, you are {age + 1}\n""#).unwrap();
+    let Expression::Literal(LiteralExpression { kind: LitKind::Interpolated(parts), .. }) = &exprs[expr] else { panic!("{exprs:?}") };
+    match &parts[..] {
+        [StrPart::Text(hello), StrPart::Expr(name), StrPart::Text(you), StrPart::Expr(age), StrPart::Text(newline)] => {
+            let (Expression::Identifier(name), Expression::Binary(age)) = (&exprs[*name], &exprs[*age]) else { panic!("{exprs:?}") };
+            assert_eq!((hello.as_str(), you.as_str(), newline.as_str()), ("hello ", ", you are ", "\n"));
+            // Embedded expressions are spanned where they are in the source.
+            assert_eq!((name.name, name.span), ("name".into(), Span::new(8, 12)));
+            assert_eq!(age.span, Span::new(24, 31));
+        }
+        parts => panic!("{parts:?}"),
+    }
+    assert!(matches!(literal(r#""\{not} {"}"}""#), LitKind::Interpolated(parts) if parts.len() == 2));
+    assert!(matches!(literal(r#"r"a\n{b}""#), LitKind::Str(s) if s == r"a\n{b}"));
+    assert!(matches!(literal(r##"r#"say "hi""#"##), LitKind::Str(s) if s == r#"say "hi""#));
+
+    let errors = |s: &str| Parser::parse_expression(s).unwrap_err();
+    assert!(matches!(&errors(r#""{}""#)[..], [ParseError::ExpectedNode { found, .. }] if found.span() == Span::new(2, 2)));
+    assert!(matches!(&errors(r#""a {1 +}""#)[..], [ParseError::ExpectedNode { found, .. }] if found.span() == Span::new(7, 7)));
+    assert!(matches!(
+        &errors(r##"r#"a""##)[..],
+        [ParseError::InvalidLiteral { error: LiteralError::Unterminated { quote: '"' }, .. }],
+    ));
+}
+
 #[test]
 fn operator_tokens() {
     // Boolean Operators
@@ -151,3 +281,226 @@ fn assignment_tokens() {
     stream_check("/=", TokenKind::OpEq { kind: OpKind::FSlash });
     stream_check("%=", TokenKind::OpEq { kind: OpKind::Percent });
 }
+
+#[test]
+fn unknown_tokens() {
+    stream_check("`", TokenKind::Unknown);
+    stream_check("$foo", TokenKind::Unknown);
+}
+
+#[test]
+fn error_recovery() {
+    use crate::ast::Statement;
+
+    let src = "
+let a = ;
+fn f(x: i32) -> i32 {
+    let y = x + ;
+    y * 2
+}
+struct P { x: i32 y: i32 }
+let b = 1 +* 2;
+fn g() -> void { let z = (1, ; }
+let ok = 3;
+";
+    let (tree, errors) = Parser::parse(src);
+    let starts: Vec<usize> = errors.iter().map(|err| err.span().start).collect();
+    assert_eq!(starts, [9, 49, 81, 101, 131]);
+
+    // Everything that parsed is still there, with error nodes in place of the rest.
+    assert!(matches!(
+        tree.root[..],
+        [Statement::Error { .. }, Statement::Function(_), Statement::Struct(_), Statement::Error { .. }, Statement::Function(_), Statement::Let(_)],
+    ));
+    assert_eq!(tree.root[0].span(), Span::new(1, 10));
+    let Statement::Function(f) = &tree.root[1] else { panic!() };
+    assert!(matches!(f.block.statements[..], [Statement::Error { span }] if span == Span::new(37, 50)));
+    assert!(f.block.expression.is_some());
+
+    // Recovery always gets past whatever it stopped at.
+    for src in ["}", ")))", "pub", "pub let x = 1;", "fn f() { ) }", "{ { ; } fn", "let x = (1, fn g() {} ;"] {
+        let (tree, errors) = Parser::parse(src);
+        assert!(!errors.is_empty() && !tree.root.is_empty(), "{src}");
+    }
+    let (tree, errors) = Parser::parse("pub let x = 1;");
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(tree.root[..], [Statement::Error { .. }, Statement::Let(_)]));
+}
+
+#[test]
+fn fixes() {
+    let fixed = |src: &str| {
+        let (_, errors) = Parser::parse(src);
+        let fixes: Vec<crate::fix::Fix> = errors.iter().filter_map(|err| err.fix().cloned()).collect();
+        crate::fix::apply(src, &fixes)
+    };
+    assert_eq!(fixed("let a = 1"), "let a = 1;");
+    assert_eq!(fixed("fn f() -> i32 { let a = 1 a }"), "fn f() -> i32 { let a = 1; a }");
+    assert_eq!(fixed("fn f() -> void { g() h() }"), "fn f() -> void { g(); h() }");
+    assert_eq!(fixed("fn f() -> i32 { 1"), "fn f() -> i32 { 1 }");
+    // The rest of the errors here only come from `stuct` not being a keyword.
+    let fixes = crate::fix::fixes("stuct P { x: i32 }");
+    assert_eq!(crate::fix::apply("stuct P { x: i32 }", &fixes[..1]), "struct P { x: i32 }");
+    assert_eq!(fixed("print(1)"), "print(1);");
+
+    let (_, errors) = Parser::parse("let a = if b = 1 { 2 } else { 3 };");
+    let fix = errors[0].fix().unwrap();
+    assert_eq!(fix.message, "use `==` to compare");
+    assert_eq!(fix.edit.span, Span::new(13, 14));
+}
+
+#[test]
+fn unclosed_delimiters() {
+    let check = |src: &str, messages: &[&str], fixed: &str| {
+        let (_, errors) = Parser::parse(src);
+        let found: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(found, messages, "{src}");
+        let fixes: Vec<crate::fix::Fix> = errors.iter().filter_map(|err| err.fix().cloned()).collect();
+        assert_eq!(crate::fix::apply(src, &fixes), fixed, "{src}");
+        assert!(Parser::parse(fixed).1.is_empty(), "{fixed}");
+    };
+
+    // The closer goes after the last line that's indented further than the opener's.
+    check(
+        "fn f() -> i32 {\n    let x = 1;\n    x\n\nfn g() -> i32 { 2 }\n",
+        &["unclosed delimiter `{` at 14..15"],
+        "fn f() -> i32 {\n    let x = 1;\n    x\n}\n\nfn g() -> i32 { 2 }\n",
+    );
+    check(
+        "fn f() -> i32 {\n    let x = (1 + 2;\n    x\n}\n",
+        &["unclosed delimiter `(` at 28..29"],
+        "fn f() -> i32 {\n    let x = (1 + 2);\n    x\n}\n",
+    );
+    check(
+        "fn f() -> void {\n    g(1, [2, 3\n}\n",
+        &["unclosed delimiter `[` at 26..27", "unclosed delimiter `(` at 22..23"],
+        "fn f() -> void {\n    g(1, [2, 3])\n}\n",
+    );
+    check("let x = (1, 2];", &["mismatched closing delimiter `]` at 13..14 for `(` at 8..9"], "let x = (1, 2);");
+    // A closer inside a group that gets closed properly later on is just out of place.
+    check(
+        "fn f() -> void {\n    a );\n}\n",
+        &["unexpected closing delimiter `)` at 23..24"],
+        "fn f() -> void {\n    a ;\n}\n",
+    );
+    // A `(` isn't closed after a block or a return type that comes later in it, but a function
+    // type's return type stays in.
+    check(
+        "fn f( -> i32 { 1 }\n",
+        &["unclosed delimiter `(` at 4..5"],
+        "fn f() -> i32 { 1 }\n",
+    );
+    check(
+        "fn f(a: i32 -> i32 { a }\n",
+        &["unclosed delimiter `(` at 4..5"],
+        "fn f(a: i32) -> i32 { a }\n",
+    );
+    check(
+        "fn f(g: fn(i32) -> i32 -> i32 { 1 }\n",
+        &["unclosed delimiter `(` at 4..5"],
+        "fn f(g: fn(i32) -> i32) -> i32 { 1 }\n",
+    );
+
+    // Errors from before the delimiter went wrong are still reported.
+    let (_, errors) = Parser::parse("let a = ;\nfn f() -> i32 { (1\n");
+    let starts: Vec<usize> = errors.iter().map(|err| err.span().start).collect();
+    assert_eq!(starts, [8, 26, 24]);
+    // And so are those from after where the closer should have been.
+    let (_, errors) = Parser::parse("let a = ;\nfn f( -> i32 { 1 }\nstruct S { x: }\nlet c = 2 2;\n");
+    let starts: Vec<usize> = errors.iter().map(|err| err.span().start).collect();
+    assert_eq!(starts, [8, 43, 55, 14]);
+}
+
+#[test]
+fn token_trees() {
+    use super::tree::TokenTree;
+
+    // Each tree as its first token's kind, with groups written out as what's in them.
+    fn shape(trees: &[TokenTree]) -> String {
+        let parts: Vec<String> = trees.iter().map(|tree| match tree {
+            TokenTree::Token(tok) => format!("{:?}", tok.kind),
+            TokenTree::Group(group) => {
+                let close = group.close.map_or("_".into(), |close| format!("{:?}", close.kind));
+                format!("{:?}[{}]{close}", group.open.kind, shape(&group.trees))
+            }
+        }).collect();
+        parts.join(" ")
+    }
+    let trees = |src: &str| shape(&TokenStream::new(src).trees());
+
+    assert_eq!(trees("f(a, [b]) { c }"), "Identifier OpenParen[Identifier Comma OpenBracket[Identifier]CloseBracket]CloseParen OpenBrace[Identifier]CloseBrace EOF");
+    // The `}` closes the `{`, so the `(` is left unclosed inside it.
+    assert_eq!(trees("{ (a }"), "OpenBrace[OpenParen[Identifier]_]CloseBrace EOF");
+    assert_eq!(trees("(a ]"), "OpenParen[Identifier]CloseBracket EOF");
+    // Unless the group gets closed properly after it.
+    assert_eq!(trees("{ a ) b }"), "OpenBrace[Identifier CloseParen Identifier]CloseBrace EOF");
+    assert_eq!(trees(") ("), "CloseParen OpenParen[]_ EOF");
+
+    // Only groups that are balanced can be skipped, even if something inside them isn't.
+    let stream = TokenStream::new("f(a, [b]) { (c }");
+    let ends: Vec<Option<usize>> = (0..stream.tokens.len()).map(|i| stream.group_end(i)).collect();
+    assert_eq!(ends, [None, Some(8), None, None, Some(7), None, None, None, Some(12), None, None, None, None]);
+}
+
+#[test]
+fn streaming_tokens() {
+    use super::stream::TokenReader;
+    use std::io::BufReader;
+
+    let src = "fn f(x: i32) -> i32 { x <<= 2; x >> 1 != 3 } // comment\nlet café = \"a {\n1 + 2} ü\" + r#\"raw\"#; 1.5e-3 'ç'";
+    let expected = TokenStream::new(src).tokens;
+
+    // However the input gets split up, the tokens come out the same, even across characters.
+    for capacity in [1, 2, 3, 7, 64] {
+        let reader = TokenReader::new(BufReader::with_capacity(capacity, src.as_bytes()));
+        let tokens: Vec<Token> = reader.map(Result::unwrap).collect();
+        assert_eq!(tokens, expected, "{capacity}");
+    }
+
+    // Only so much is held on to at once, and what's needed of it is still there.
+    let src = "let x = 1;\n".repeat(10_000);
+    let mut reader = TokenReader::new(BufReader::with_capacity(64, src.as_bytes()));
+    let mut count = 0;
+    while let Some(tok) = reader.next() {
+        let tok = tok.unwrap();
+        assert!(reader.buffered() < 256);
+        assert_eq!(reader.lexeme(&tok), Some(&src[tok.start..tok.end]));
+        count += 1;
+    }
+    assert_eq!(count, 50_001);
+
+    // Tokens much longer than what gets read at a time are still only lexed a few times over.
+    for long in [format!("// {}\n1", "a".repeat(1 << 18)), format!("\"{}\" 1", "a".repeat(1 << 18))] {
+        let started = std::time::Instant::now();
+        let reader = TokenReader::new(BufReader::with_capacity(16, long.as_bytes()));
+        let tokens: Vec<Token> = reader.map(Result::unwrap).collect();
+        assert_eq!(tokens, TokenStream::new(&long).tokens);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5), "{:?}", started.elapsed());
+    }
+
+    let invalid: &[u8] = b"let x = \"\xff\";";
+    assert!(TokenReader::new(invalid).any(|tok| tok.is_err()));
+    let cut_off: &[u8] = b"let \xc3";
+    assert!(TokenReader::new(cut_off).any(|tok| tok.is_err()));
+}
+
+#[test]
+fn interned_names() {
+    use crate::ast::{Statement, Symbol, Expression, Type};
+
+    // The same name is the same symbol wherever it shows up, including once normalized.
+    let (tree, errors) = Parser::parse("fn cafe\u{301}(p: Point) -> geom::Point { café }");
+    assert!(errors.is_empty(), "{errors:?}");
+    let Statement::Function(f) = &tree.root[0] else { panic!() };
+    let Expression::Identifier(ident) = &tree.exprs[f.block.expression.unwrap()] else { panic!() };
+    assert_eq!(f.name, ident.name);
+    assert_eq!(f.name, Symbol::intern("café"));
+    assert_eq!(f.arguments[0].param_type, Type::UserDefined { name: "Point".into() });
+    assert_eq!(f.return_type, Type::UserDefined { name: Symbol::join(&["geom".into(), "Point".into()], "::") });
+    assert_eq!(f.return_type.to_string(), "geom::Point");
+
+    // Symbols sort by name, not by when they were interned.
+    let (b, a) = (Symbol::intern("interned_b"), Symbol::intern("interned_a"));
+    assert!(a < b);
+    assert_ne!(a, b);
+}
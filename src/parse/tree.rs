@@ -0,0 +1,158 @@
+// Tokens grouped into balanced `()`, `[]` and `{}`, for looking at a file a group at a time rather
+// than a token at a time.
+
+use crate::ast::Span;
+use crate::ast::token::{T, Token, TokenKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenTree {
+    Token(Token),
+    Group(Group),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    pub open: Token,
+    pub trees: Vec<TokenTree>,
+    // Missing if the group was never closed. It can be the wrong kind of closer if that's what
+    // was there instead.
+    pub close: Option<Token>,
+}
+
+impl TokenTree {
+    pub fn span(&self) -> Span {
+        match self {
+            TokenTree::Token(tok) => tok.span(),
+            TokenTree::Group(group) => group.span(),
+        }
+    }
+
+    // How many tokens there are in it.
+    pub fn token_count(&self) -> usize {
+        match self {
+            TokenTree::Token(_) => 1,
+            TokenTree::Group(group) => group.token_count(),
+        }
+    }
+}
+
+impl Group {
+    // Up to the end of the closer, or the last token in the group if it's unclosed.
+    pub fn span(&self) -> Span {
+        let end = match (&self.close, self.trees.last()) {
+            (Some(close), _) => close.end,
+            (None, Some(last)) => last.span().end,
+            (None, None) => self.open.end,
+        };
+        Span::new(self.open.start, end)
+    }
+
+    pub fn token_count(&self) -> usize {
+        1 + self.trees.iter().map(TokenTree::token_count).sum::<usize>() + usize::from(self.close.is_some())
+    }
+
+    // Whether it was closed by the closer that matches its opener.
+    pub fn is_balanced(&self) -> bool {
+        self.close.is_some_and(|close| close.kind == closer(self.open.kind))
+    }
+
+    // Every token in the group, in order, along with its delimiters.
+    pub fn tokens(&self) -> Vec<Token> {
+        let mut tokens = vec![self.open];
+        flatten(&self.trees, &mut tokens);
+        tokens.extend(self.close);
+        tokens
+    }
+}
+
+fn flatten(trees: &[TokenTree], tokens: &mut Vec<Token>) {
+    for tree in trees {
+        match tree {
+            TokenTree::Token(tok) => tokens.push(*tok),
+            TokenTree::Group(group) => tokens.extend(group.tokens()),
+        }
+    }
+}
+
+// Groups `tokens` into trees. A closer closes the innermost group it matches, and any groups
+// opened inside that one are left unclosed. One that doesn't match any group closes the innermost
+// group anyway, since it was probably meant to, unless that group gets closed properly later on.
+// Then it's just a stray token in it, the same as one with no group to close.
+pub fn build(tokens: &[Token]) -> Vec<TokenTree> {
+    // The groups that are still open, outermost first.
+    let mut open: Vec<Group> = Vec::new();
+    let mut trees = Vec::new();
+
+    for (i, &tok) in tokens.iter().enumerate() {
+        match tok.kind {
+            T!("(") | T!("[") | T!("{") => {
+                open.push(Group { open: tok, trees: Vec::new(), close: None });
+                continue;
+            }
+            T!(")") | T!("]") | T!("}") if !open.is_empty() => {
+                let depth = open.iter().rposition(|group| closer(group.open.kind) == tok.kind);
+                let innermost = open.last_mut().expect("there's an open group");
+                if depth.is_none() && closed_later(&tokens[i + 1..], innermost.open.kind) {
+                    innermost.trees.push(TokenTree::Token(tok));
+                    continue;
+                }
+                while open.len() > depth.unwrap_or(open.len() - 1) + 1 {
+                    close(&mut open, &mut trees, None);
+                }
+                close(&mut open, &mut trees, Some(tok));
+                continue;
+            }
+            // Whatever is still open when the file ends is never closed.
+            T!("EOF") => {
+                while !open.is_empty() {
+                    close(&mut open, &mut trees, None);
+                }
+            }
+            _ => (),
+        }
+        match open.last_mut() {
+            Some(group) => group.trees.push(TokenTree::Token(tok)),
+            None => trees.push(TokenTree::Token(tok)),
+        }
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut trees, None);
+    }
+    trees
+}
+
+// Whether a group opened by `opener` is closed somewhere in `tokens`, which come right after
+// whatever is already in it.
+fn closed_later(tokens: &[Token], opener: TokenKind) -> bool {
+    let mut depth = 0usize;
+    for tok in tokens {
+        match tok.kind {
+            T!("(") | T!("[") | T!("{") => depth += 1,
+            kind if kind == closer(opener) && depth == 0 => return true,
+            T!(")") | T!("]") | T!("}") => match depth.checked_sub(1) {
+                Some(outer) => depth = outer,
+                None => return false,
+            },
+            _ => (),
+        }
+    }
+    false
+}
+
+// Finishes the innermost open group with `closer`.
+fn close(open: &mut Vec<Group>, trees: &mut Vec<TokenTree>, closer: Option<Token>) {
+    let Some(mut group) = open.pop() else { return };
+    group.close = closer;
+    match open.last_mut() {
+        Some(outer) => outer.trees.push(TokenTree::Group(group)),
+        None => trees.push(TokenTree::Group(group)),
+    }
+}
+
+pub(super) fn closer(opener: TokenKind) -> TokenKind {
+    match opener {
+        T!("(") => T!(")"),
+        T!("[") => T!("]"),
+        _ => T!("}"),
+    }
+}
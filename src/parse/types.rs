@@ -1,96 +1,133 @@
 use crate::parse::{Parser, ParseError, ParseResult};
 
-use crate::ast::token::{T, TokenKind};
-use crate::ast::Parameter;
+use crate::ast::token::{T, TokenKind, OpKind};
+use crate::ast::{Parameter, Symbol};
 use crate::ast::{Type, IntKind, FloatKind, TupleType};
+use crate::syntax::SyntaxKind;
 
 
 impl<'src> Parser<'src> {
     // #[inline]
     pub(super) fn parse_params(&mut self, open: TokenKind, close: TokenKind) -> ParseResult<Vec<Parameter>> {
-        self.bump_expect(open)?;
-
-        let mut parameters = Vec::new();
-        let mut first_param = true;
-
+        self.node(SyntaxKind::ParamList, |p| {
+            p.bump_expect(open)?;
 
-        loop {
-            // Peek current token and token ahead to check if it's the closing delimiter.
-            let (peek_0, peek_1) = (self.peek(0).kind, self.peek(1).kind);
-            if peek_0 == close || peek_1 == close { break }
+            let mut parameters = Vec::new();
+            let mut first_param = true;
 
-            let err = if !first_param { 
-                self.bump_expect(T!(","))
-            } else { first_param = false; Ok(()) };
-
-            // After we check if there's a comma, we want to check if the next token is an
-            // Identifier or a Comma since those two things are the only two things that should be
-            // coming after the opening delimiter or a successful parameter parse.
-            let peek = self.peek(0);
-            if !matches!(peek.kind, T!("ID") | T!(",")) {
-                // If it's not one of those two things, we know for sure parsing the parameter list
-                // should be over. If there wasn't an error with checking the comma, we just say
-                // that we're expecting the closing delimiter. If there was an issue, we say that
-                // we expected a comma OR the closing delimiter.
-                let err = match err.is_ok() {
-                    true => ParseError::ExpectedSingle { expected: close, found: peek },
-                    false => ParseError::ExpectedAlternatives { expected: Box::new([T!(","), close]), found: peek },
-                };
-                return Err(err);
+            // Methods take a `self` that doesn't need a type.
+            if let Some(parameter) = p.parse_self_param() {
+                parameters.push(parameter);
+                first_param = false;
             }
 
-            if let Err(err) = err {
-                self.recover_error(err);
-            }
 
-            let parameter = match self.parse_param() {
-                Ok(parameter) => parameter,
-                Err(err) => {
-                    self.bump_while(|kind| {
-                        matches!(kind, T!("ID") | T!(":"))
-                    });
+            loop {
+                // Peek current token and token ahead to check if it's the closing delimiter.
+                let (peek_0, peek_1) = (p.peek(0).kind, p.peek(1).kind);
+                if peek_0 == close || peek_1 == close { break }
+
+                let err = if !first_param { 
+                    p.bump_expect(T!(","))
+                } else { first_param = false; Ok(()) };
+
+                // After we check if there's a comma, we want to check if the next token is an
+                // Identifier or a Comma since those two things are the only two things that should
+                // be coming after the opening delimiter or a successful parameter parse.
+                let peek = p.peek(0);
+                if !matches!(peek.kind, T!("ID") | T!(",")) {
+                    // If it's not one of those two things, we know for sure parsing the parameter
+                    // list should be over. If there wasn't an error with checking the comma, we
+                    // just say that we're expecting the closing delimiter. If there was an issue,
+                    // we say that we expected a comma OR the closing delimiter.
+                    let err = match err.is_ok() {
+                        true => ParseError::ExpectedSingle { expected: close, found: peek },
+                        false => ParseError::ExpectedAlternatives { expected: Box::new([T!(","), close]), found: peek },
+                    };
+                    return Err(err);
+                }
 
-                    let peek = self.peek(0);
-                    if !matches!(peek.kind, T!("ID") | T!(",")) {
-                        return Err(err);
-                    }
-                    self.recover_error(err);
-                    continue
+                if let Err(err) = err {
+                    p.recover_error(err);
                 }
-            };
 
-            parameters.push(parameter);
-        }
+                let parameter = match p.parse_param() {
+                    Ok(parameter) => parameter,
+                    Err(err) => {
+                        p.bump_while(|kind| {
+                            matches!(kind, T!("ID") | T!(":"))
+                        });
+
+                        let peek = p.peek(0);
+                        if !matches!(peek.kind, T!("ID") | T!(",")) {
+                            return Err(err);
+                        }
+                        p.recover_error(err);
+                        continue
+                    }
+                };
+
+                parameters.push(parameter);
+            }
         
-        // Optional `,` after the last type_arg
-        self.bump_check(T!(","));
-        self.bump_expect(close)?;
+            // Optional `,` after the last type_arg
+            p.bump_check(T!(","));
+            p.bump_expect(close)?;
 
-        Ok(parameters)
+            Ok(parameters)
+        })
     }
 
     // #[inline]
     pub(super) fn parse_param(&mut self) -> ParseResult<Parameter> {
-        let name = self.take_expect(T!("ID"))?;
-        self.bump_expect(T!(":"))?;
-        let param_type = self.parse_type()?;
-
-        let name = self.get_lexeme(name);
-        Ok(Parameter { name: name.into(), param_type })
+        self.node(SyntaxKind::Param, |p| {
+            let name = p.take_expect(T!("ID"))?;
+            p.bump_expect(T!(":"))?;
+            let param_type = p.parse_type()?;
+
+            let name_span = name.span();
+            let name = p.get_name(name);
+            Ok(Parameter { name, param_type, name_span })
+        })
     }
 
+    // A bare `self`, which takes the type of whatever the method is declared for.
+    pub(super) fn parse_self_param(&mut self) -> Option<Parameter> {
+        let tok = self.peek(0);
+        if tok.kind != T!("ID") || self.get_lexeme(tok) != "self" || self.peek(1).kind == T!(":") {
+            return None;
+        }
+
+        self.node(SyntaxKind::Param, |p| {
+            p.bump();
+            Ok(())
+        }).ok()?;
+        Some(Parameter { name: "self".into(), param_type: self.scope.self_type(), name_span: tok.span() })
+    }
 
     pub(super) fn parse_type(&mut self) -> ParseResult<Type> {
         match self.peek(0).kind {
-            T!("ID") => {
-                let name = self.take();
-                let name = self.get_lexeme(name);
-                Ok(Parser::parse_type_from_ident(&name))
-            },
+            T!("ID") => self.node(SyntaxKind::TypeName, |p| {
+                let (path, _) = p.parse_path()?;
+                let name = Symbol::join(&path, "::");
+
+                if p.check(T!("<")) {
+                    let arguments = p.node(SyntaxKind::TypeArgs, |p| p.parse_type_args(T!("<"), T!(">")))?;
+                    return Ok(Type::Applied { name, arguments: arguments.into() });
+                }
+                // Only types from other modules are qualified, so those can't be primitives.
+                if path.len() > 1 {
+                    return Ok(Type::UserDefined { name });
+                }
+                match p.scope.resolve(name) {
+                    Some(ty) => Ok(ty),
+                    None => Ok(Parser::parse_type_from_ident(&name.as_str())),
+                }
+            }),
 
-            T!("(") => self.parse_type_tuple(),
-            T!("{") => self.parse_type_list(),
-            T!("fn") => self.parse_type_fn(),
+            T!("(") => self.node(SyntaxKind::TupleType, Self::parse_type_tuple),
+            T!("{") => self.node(SyntaxKind::ListType, Self::parse_type_list),
+            T!("fn") => self.node(SyntaxKind::FnType, Self::parse_type_fn),
 
             _ => Err(ParseError::ExpectedNode {
                 expected: "type".into(),
@@ -137,7 +174,8 @@ impl<'src> Parser<'src> {
 
         loop {
             let (peek_0, peek_1) = (self.peek(0).kind, self.peek(1).kind);
-            if peek_0 == close || (peek_1 == close && !first_type) { break }
+            let closing = peek_0 == close || (close == T!(">") && peek_0 == T!(">>"));
+            if closing || (peek_1 == close && !first_type) { break }
 
             let err = if !first_type { 
                 self.bump_expect(T!(","))
@@ -194,12 +232,14 @@ impl<'src> Parser<'src> {
         }
 
         self.bump_check(T!(","));
-        self.bump_expect(close)?;
+        if !(close == T!(">") && self.bump_half_shift()) {
+            self.bump_expect(close)?;
+        }
         Ok(arguments)
     }
 
     #[inline]
-    pub(super) fn parse_type_from_ident(typename: &str) -> Type {
+    pub(crate) fn parse_type_from_ident(typename: &str) -> Type {
         match typename {
             "bool" => Type::Bool,
             "str" => Type::Str,
@@ -220,7 +260,7 @@ impl<'src> Parser<'src> {
 
             "void" => Type::Void,
 
-            _ => Type::UserDefined { name: String::from(typename) }
+            _ => Type::UserDefined { name: Symbol::intern(typename) }
         }
     }
 }
@@ -0,0 +1,246 @@
+// Functions every program can use without declaring or importing them, implemented in Rust. The
+// ones that work on a single type are named after it, like `str::len` or `f64::sqrt`, the same way
+// items from another module are.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+use crate::ast::{Symbol, Type, IntKind, FloatKind, TupleType};
+use crate::eval::{Interpreter, EvalResult, RuntimeError};
+use crate::eval::value::Value;
+
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<Value>) -> EvalResult<Value>;
+
+// A function implemented in Rust. The type checker sees it as `ty`, which is always a `Type::Fn`
+// and can mention `generics`, so `function` only ever gets called with arguments that fit it.
+pub struct NativeFunction {
+    pub name: Symbol,
+    pub generics: Vec<Symbol>,
+    pub ty: Type,
+    pub function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: impl AsRef<str>,
+        generics: &[&str],
+        arguments: Vec<Type>,
+        return_type: Type,
+        function: impl Fn(&mut Interpreter, Vec<Value>) -> EvalResult<Value> + 'static,
+    ) -> Self {
+        Self {
+            name: Symbol::intern(name.as_ref()),
+            generics: generics.iter().map(|&name| Symbol::intern(name)).collect(),
+            ty: Type::Fn { arguments, return_type: Box::new(return_type) },
+            function: Box::new(function),
+        }
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NativeFunction({}: {})", self.name, self.ty)
+    }
+}
+
+// The names of every function in `functions()`. They're only worked out once, since the linker
+// asks about every qualified name it can't find in a module.
+static NAMES: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    functions().iter().map(|function| function.name.to_string()).collect()
+});
+
+pub fn contains(name: &str) -> bool {
+    NAMES.contains(name)
+}
+
+const I32: Type = Type::Int { sign: true, kind: IntKind::Bit32 };
+const I64: Type = Type::Int { sign: true, kind: IntKind::Bit64 };
+
+fn generic(name: &str) -> Type {
+    Type::Generic { name: name.into() }
+}
+
+fn list(ty: Type) -> Type {
+    Type::List(Box::new(ty))
+}
+
+fn function(arguments: Vec<Type>, return_type: Type) -> Type {
+    Type::Fn { arguments, return_type: Box::new(return_type) }
+}
+
+type Unary = fn(f64) -> f64;
+type Binary = fn(f64, f64) -> f64;
+type Class = fn(&char) -> bool;
+
+pub fn functions() -> Vec<Rc<NativeFunction>> {
+    let mut functions = Vec::new();
+    let mut add = |name: &str, generics: &[&str], arguments: Vec<Type>, return_type: Type, f: fn(&mut Interpreter, Vec<Value>) -> EvalResult<Value>| {
+        functions.push(NativeFunction::new(name, generics, arguments, return_type, f));
+    };
+
+    // I/O
+    add("print", &["T"], vec![generic("T")], Type::Void, |interpreter, arguments| {
+        arguments.iter().for_each(|value| interpreter.output.write(&text(value)));
+        Ok(Value::Void)
+    });
+    add("println", &["T"], vec![generic("T")], Type::Void, |interpreter, arguments| {
+        arguments.iter().for_each(|value| interpreter.output.write(&text(value)));
+        interpreter.output.write("\n");
+        Ok(Value::Void)
+    });
+
+    // Strings
+    add("str::from", &["T"], vec![generic("T")], Type::Str, |_, arguments| match &arguments[..] {
+        [value] => Ok(Value::Str(text(value))),
+        _ => Err(RuntimeError::Invalid),
+    });
+    add("str::len", &[], vec![Type::Str], I64, |_, arguments| match &arguments[..] {
+        [Value::Str(s)] => Ok(Value::Int(s.chars().count() as i128)),
+        _ => Err(RuntimeError::Invalid),
+    });
+    add("str::concat", &[], vec![Type::Str, Type::Str], Type::Str, |_, arguments| match &arguments[..] {
+        [Value::Str(a), Value::Str(b)] => Ok(Value::Str(format!("{a}{b}"))),
+        _ => Err(RuntimeError::Invalid),
+    });
+    add("str::split", &[], vec![Type::Str, Type::Str], list(Type::Str), |_, arguments| match &arguments[..] {
+        [Value::Str(s), Value::Str(separator)] => {
+            Ok(Value::List(s.split(separator.as_str()).map(|part| Value::Str(part.into())).collect()))
+        }
+        _ => Err(RuntimeError::Invalid),
+    });
+
+    // Lists. Values can't be changed in place, so these all give back a new list.
+    let (t, a, b) = (generic("T"), generic("A"), generic("B"));
+    add("list::len", &["T"], vec![list(t.clone())], I64, |_, arguments| match &arguments[..] {
+        [Value::List(values)] => Ok(Value::Int(values.len() as i128)),
+        _ => Err(RuntimeError::Invalid),
+    });
+    add("list::push", &["T"], vec![list(t.clone()), t.clone()], list(t.clone()), |_, arguments| {
+        match <[Value; 2]>::try_from(arguments) {
+            Ok([Value::List(mut values), value]) => {
+                values.push(value);
+                Ok(Value::List(values))
+            }
+            _ => Err(RuntimeError::Invalid),
+        }
+    });
+    // Gives back the rest of the list along with its last element.
+    let popped = Type::Tuple(TupleType(vec![list(t.clone()), t.clone()]));
+    add("list::pop", &["T"], vec![list(t.clone())], popped, |_, arguments| {
+        let Ok([Value::List(mut values)]) = <[Value; 1]>::try_from(arguments) else { return Err(RuntimeError::Invalid) };
+        match values.pop() {
+            Some(last) => Ok(Value::Tuple(vec![Value::List(values), last])),
+            None => Err(RuntimeError::Native { function: "list::pop".into(), message: "the list is empty".into() }),
+        }
+    });
+    let mapper = function(vec![a.clone()], b.clone());
+    add("list::map", &["A", "B"], vec![list(a.clone()), mapper], list(b.clone()), |interpreter, arguments| {
+        let Ok([Value::List(values), f]) = <[Value; 2]>::try_from(arguments) else { return Err(RuntimeError::Invalid) };
+        let values = values.into_iter().map(|value| interpreter.call(&f, vec![value])).collect::<EvalResult<_>>()?;
+        Ok(Value::List(values))
+    });
+    let predicate = function(vec![t.clone()], Type::Bool);
+    add("list::filter", &["T"], vec![list(t.clone()), predicate], list(t.clone()), |interpreter, arguments| {
+        let Ok([Value::List(values), f]) = <[Value; 2]>::try_from(arguments) else { return Err(RuntimeError::Invalid) };
+        let mut kept = Vec::new();
+        for value in values {
+            match interpreter.call(&f, vec![value.clone()])? {
+                Value::Bool(true) => kept.push(value),
+                Value::Bool(false) => (),
+                _ => return Err(RuntimeError::Invalid),
+            }
+        }
+        Ok(Value::List(kept))
+    });
+    let folder = function(vec![a.clone(), t.clone()], a.clone());
+    add("list::fold", &["T", "A"], vec![list(t.clone()), a.clone(), folder], a.clone(), |interpreter, arguments| {
+        let Ok([Value::List(values), init, f]) = <[Value; 3]>::try_from(arguments) else { return Err(RuntimeError::Invalid) };
+        values.into_iter().try_fold(init, |acc, value| interpreter.call(&f, vec![acc, value]))
+    });
+
+    // Numbers. Values don't remember how wide they are, so the `f32` versions round their result
+    // to what an `f32` can hold.
+    for (prefix, kind) in [("f32", FloatKind::Bit32), ("f64", FloatKind::Bit64)] {
+        let ty = Type::Float { kind };
+        let unary: [(&str, Unary); 10] = [
+            ("sqrt", f64::sqrt), ("abs", f64::abs), ("floor", f64::floor), ("ceil", f64::ceil),
+            ("round", f64::round), ("sin", f64::sin), ("cos", f64::cos), ("tan", f64::tan),
+            ("ln", f64::ln), ("exp", f64::exp),
+        ];
+        for (name, op) in unary {
+            let name = format!("{prefix}::{name}");
+            functions.push(NativeFunction::new(name, &[], vec![ty.clone()], ty.clone(), move |_, arguments| {
+                match &arguments[..] {
+                    [Value::Float(x)] => Ok(Value::Float(narrow(kind, op(*x)))),
+                    _ => Err(RuntimeError::Invalid),
+                }
+            }));
+        }
+
+        let binary: [(&str, Binary); 3] = [("pow", f64::powf), ("min", f64::min), ("max", f64::max)];
+        for (name, op) in binary {
+            let name = format!("{prefix}::{name}");
+            let arguments = vec![ty.clone(), ty.clone()];
+            functions.push(NativeFunction::new(name, &[], arguments, ty.clone(), move |_, arguments| {
+                match &arguments[..] {
+                    [Value::Float(x), Value::Float(y)] => Ok(Value::Float(narrow(kind, op(*x, *y)))),
+                    _ => Err(RuntimeError::Invalid),
+                }
+            }));
+        }
+    }
+
+    for (name, ty, min, max) in [("i32::parse", I32, i32::MIN as i128, i32::MAX as i128), ("i64::parse", I64, i64::MIN as i128, i64::MAX as i128)] {
+        functions.push(NativeFunction::new(name, &[], vec![Type::Str], ty, move |_, arguments| {
+            let [Value::Str(s)] = &arguments[..] else { return Err(RuntimeError::Invalid) };
+            match s.trim().parse::<i128>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(Value::Int(n)),
+                Ok(_) => Err(RuntimeError::Native { function: name.into(), message: format!("`{s}` is out of range") }),
+                Err(err) => Err(RuntimeError::Native { function: name.into(), message: format!("`{s}`: {err}") }),
+            }
+        }));
+    }
+
+    // Characters
+    let classes: [(&str, Class); 6] = [
+        ("is_digit", char::is_ascii_digit),
+        ("is_alphabetic", |c| c.is_alphabetic()),
+        ("is_alphanumeric", |c| c.is_alphanumeric()),
+        ("is_whitespace", |c| c.is_whitespace()),
+        ("is_uppercase", |c| c.is_uppercase()),
+        ("is_lowercase", |c| c.is_lowercase()),
+    ];
+    for (name, class) in classes {
+        functions.push(NativeFunction::new(format!("char::{name}"), &[], vec![Type::Char], Type::Bool, move |_, arguments| {
+            match &arguments[..] {
+                [Value::Char(c)] => Ok(Value::Bool(class(c))),
+                _ => Err(RuntimeError::Invalid),
+            }
+        }));
+    }
+
+    functions.into_iter().map(Rc::new).collect()
+}
+
+// What `print` shows for a value. Strings and characters are written out as they are, without
+// the quotes they're displayed with otherwise.
+pub(crate) fn text(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.clone(),
+        Value::Char(c) => c.to_string(),
+        value => value.to_string(),
+    }
+}
+
+// `x` rounded to what the float type can hold. Values are all stored as `f64`.
+pub(crate) fn narrow(kind: FloatKind, x: f64) -> f64 {
+    match kind {
+        FloatKind::Bit32 => x as f32 as f64,
+        FloatKind::Bit64 => x,
+    }
+}
+
+#[cfg(test)]
+mod tests;
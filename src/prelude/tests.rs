@@ -0,0 +1,93 @@
+use super::*;
+use crate::eval::Output;
+use crate::parse::Parser;
+use crate::typeck::TypeChecker;
+
+// Runs a program that's been type checked, giving back what it printed.
+fn run(s: &str) -> EvalResult<String> {
+    let (mut tree, errors) = Parser::parse_interactive(s);
+    assert!(errors.is_empty(), "{errors:?}");
+    TypeChecker::new().check_tree(&mut tree).unwrap();
+
+    let mut interpreter = Interpreter::new();
+    interpreter.output = Output::Captured(String::new());
+    interpreter.eval_tree(&tree)?;
+    Ok(interpreter.output.take())
+}
+
+fn check(s: &str, expected: &str) {
+    assert_eq!(run(s), Ok(expected.to_string()));
+}
+
+// Calls a native function directly, for arguments that are easier to write out in Rust.
+fn call(name: &str, arguments: Vec<Value>) -> EvalResult<Value> {
+    let function = functions().into_iter().find(|function| function.name == name).unwrap();
+    (function.function)(&mut Interpreter::new(), arguments)
+}
+
+fn string(s: &str) -> Value {
+    Value::Str(s.into())
+}
+
+#[test]
+fn printing() {
+    check("print(1); print(2); println(str::from(false)); println((true, [1.5]));", "12false\n(true, [1.5])\n");
+    assert_eq!(call("str::from", vec![string("a")]), Ok(string("a")));
+    assert_eq!(call("str::from", vec![Value::Char('b')]), Ok(string("b")));
+}
+
+#[test]
+fn strings_and_lists() {
+    check("println(str::len(str::from(12345)));", "5\n");
+    check(r#"print(str::concat("a\tb", "\u{e9}\n")); println(str::len("\x41\"")); println('\'');"#, "a\tb\u{e9}\n2\n'\n");
+    assert_eq!(call("str::len", vec![string("héllo")]), Ok(Value::Int(5)));
+    assert_eq!(call("str::concat", vec![string("a,b"), string(",c")]), Ok(string("a,b,c")));
+    let parts = Value::List(vec![string("a"), string("b"), string("c")]);
+    assert_eq!(call("str::split", vec![string("a,b,c"), string(",")]), Ok(parts));
+    check("println(list::push([1, 2], 3)); println(list::len([true]));", "[1, 2, 3]\n1\n");
+    check("println(list::pop([1, 2, 3]));", "([1, 2], 3)\n");
+    check("
+        let evens = list::filter([1, 2, 3, 4], \\(n: i32) -> bool { n % 2 == 0 });
+        let names = list::map(evens, \\(n: i32) -> str { str::from(n) });
+        println(names);
+        println(list::fold([1, 2, 3], 10, \\(acc: i32, n: i32) -> i32 { acc + n }));
+    ", "[\"2\", \"4\"]\n16\n");
+
+    let err = run("list::pop([1]); list::pop(list::filter([1], \\(n: i32) -> bool { false }));");
+    let message = "the list is empty".to_string();
+    assert_eq!(err, Err(RuntimeError::Native { function: "list::pop".into(), message }));
+}
+
+#[test]
+fn numbers_and_chars() {
+    check("println(f64::sqrt(16.0)); println(f64::pow(2.0, 10.0)); println(f64::max(1.0, f64::abs(-2.5)));", "4.0\n1024.0\n2.5\n");
+    check("let x: f32 = f32::sqrt(2.0); println(x == 1.4142135381698608);", "true\n");
+    check("println(i32::parse(str::from(-42)) + 1); println(i64::parse(str::from(3000000000i64)));", "-41\n3000000000\n");
+    assert_eq!(call("i32::parse", vec![string(" 7 ")]), Ok(Value::Int(7)));
+    let Err(RuntimeError::Native { function, .. }) = call("i32::parse", vec![string("3000000000")]) else { panic!() };
+    assert_eq!(function, "i32::parse");
+    assert!(matches!(call("i64::parse", vec![string("twelve")]), Err(RuntimeError::Native { .. })));
+
+    let class = |name: &str, c: char| call(&format!("char::{name}"), vec![Value::Char(c)]).unwrap();
+    assert_eq!(class("is_digit", '7'), Value::Bool(true));
+    assert_eq!(class("is_alphabetic", 'é'), Value::Bool(true));
+    assert_eq!(class("is_alphanumeric", '_'), Value::Bool(false));
+    assert_eq!(class("is_whitespace", '\t'), Value::Bool(true));
+    assert_eq!(class("is_uppercase", 'A'), Value::Bool(true));
+    assert_eq!(class("is_lowercase", 'A'), Value::Bool(false));
+}
+
+#[test]
+fn signatures() {
+    let mut checker = TypeChecker::new();
+    let ty = |checker: &mut TypeChecker, s: &str| {
+        let (expr, mut exprs) = Parser::parse_expression(s).unwrap();
+        checker.check_expr(&mut exprs, expr, None).map(|ty| ty.to_string())
+    };
+    assert_eq!(ty(&mut checker, "str::len").unwrap(), "fn(str) -> i64");
+    assert_eq!(ty(&mut checker, "list::pop([true])").unwrap(), "({bool}, bool)");
+    assert_eq!(ty(&mut checker, "list::map([1], \\(n: i32) -> bool { true })").unwrap(), "{bool}");
+    assert!(ty(&mut checker, "str::len(1)").is_err());
+    assert!(ty(&mut checker, "f64::sqrt(true)").is_err());
+    assert!(ty(&mut checker, "list::filter([1], \\(n: i32) -> i32 { n })").is_err());
+}
@@ -0,0 +1,175 @@
+use std::io::{self, BufRead, Write};
+
+use crate::ast::Statement;
+use crate::ast::token::TokenKind;
+use crate::eval::{Interpreter, Output};
+use crate::parse::Parser;
+use crate::parse::stream::TokenStream;
+use crate::typeck::TypeChecker;
+
+const HELP: &str = "\
+:type <expr>    show the type of an expression
+:ast <expr>     show the syntax tree of an expression
+:tokens <expr>  show the tokens of an expression
+:help           show this message
+:quit           exit the repl";
+
+// Declarations and top-level `let`s are kept around between inputs.
+pub struct Repl {
+    checker: TypeChecker,
+    interpreter: Interpreter,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        // Whatever the program prints is shown before its value, same as everything else the repl
+        // prints.
+        let mut interpreter = Interpreter::new();
+        interpreter.output = Output::Captured(String::new());
+        Self { checker: TypeChecker::new(), interpreter }
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        let stdin = io::stdin();
+        let mut stdout = io::stdout();
+        let mut lines = stdin.lock().lines();
+
+        loop {
+            write!(stdout, ">> ")?;
+            stdout.flush()?;
+
+            let Some(line) = lines.next() else { break };
+            let mut input = line?;
+
+            // Keep reading until every `{` has been closed.
+            while brace_depth(&input) > 0 {
+                write!(stdout, ".. ")?;
+                stdout.flush()?;
+
+                let Some(line) = lines.next() else { break };
+                input.push('\n');
+                input.push_str(&line?);
+            }
+
+            match input.trim() {
+                ":quit" | ":q" => break,
+                "" => continue,
+                input => {
+                    for line in self.eval_input(input) {
+                        writeln!(stdout, "{line}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Handles a single (possibly multi-line) input, returning whatever should be printed.
+    pub fn eval_input(&mut self, input: &str) -> Vec<String> {
+        if let Some(command) = input.strip_prefix(':') {
+            return self.command(command);
+        }
+
+        let (mut tree, errors) = Parser::parse_interactive(input);
+        if !errors.is_empty() {
+            return errors.iter().map(|err| format!("error: {err}")).collect();
+        }
+
+        // Nothing should be remembered from an input that doesn't type check, or that fails while
+        // it runs, since whatever comes after it wouldn't have been declared.
+        let snapshot = self.checker.clone();
+        let types = match self.checker.check_tree(&mut tree) {
+            Ok(types) => types,
+            Err(err) => {
+                self.checker = snapshot;
+                return vec![format!("error: {err}")];
+            }
+        };
+
+        let declared = self.interpreter.snapshot();
+        let result = self.interpreter.eval_tree(&tree);
+        let mut lines: Vec<String> = self.interpreter.output.take().lines().map(String::from).collect();
+        let value = match result {
+            Ok(value) => value,
+            Err(err) => {
+                self.checker = snapshot;
+                self.interpreter.restore(declared);
+                lines.push(format!("error: {err}"));
+                return lines;
+            }
+        };
+
+        // Only show the value if the input ended with an expression that wasn't terminated.
+        match (tree.root.last(), types.last()) {
+            (Some(Statement::Expression { end_token, .. }), Some(ty)) if end_token.kind != TokenKind::Semi => {
+                lines.push(format!("{value}: {ty}"));
+            }
+            _ => (),
+        }
+        lines
+    }
+
+    fn command(&mut self, command: &str) -> Vec<String> {
+        let (name, argument) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        match name {
+            "type" | "t" => {
+                let (expr, mut exprs) = match Parser::parse_expression(argument) {
+                    Ok(parsed) => parsed,
+                    Err(errors) => return errors.iter().map(|err| format!("error: {err}")).collect(),
+                };
+
+                // Checking an expression can still declare things inside of it, so that's done on
+                // a copy.
+                match self.checker.clone().check_expr(&mut exprs, expr, None) {
+                    Ok(ty) => vec![ty.to_string()],
+                    Err(err) => vec![format!("error: {err}")],
+                }
+            }
+
+            "ast" => match Parser::parse_expression(argument) {
+                // Each expression comes after the ones inside of it, so the whole one is last.
+                Ok((_, exprs)) => vec![format!("{exprs:#?}")],
+                Err(errors) => errors.iter().map(|err| format!("error: {err}")).collect(),
+            },
+
+            "tokens" => {
+                let mut stream = TokenStream::new(argument);
+                let mut lines = Vec::new();
+                loop {
+                    let tok = stream.next_token();
+                    if tok.kind == TokenKind::EOF { break }
+                    lines.push(format!("{:?} `{}` {}..{}", tok.kind, &argument[tok.start..tok.end], tok.start, tok.end));
+                }
+                lines
+            }
+
+            "help" | "h" => vec![HELP.into()],
+
+            _ => vec![format!("unknown command `:{name}`, try `:help`")],
+        }
+    }
+}
+
+// How many more `{` there are than `}`.
+pub fn brace_depth(input: &str) -> isize {
+    let mut stream = TokenStream::new(input);
+    let mut depth = 0;
+    loop {
+        match stream.next_token().kind {
+            TokenKind::OpenBrace => depth += 1,
+            TokenKind::CloseBrace => depth -= 1,
+            TokenKind::EOF => break,
+            _ => (),
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,57 @@
+use super::*;
+
+#[test]
+fn brace_depths() {
+    assert_eq!(brace_depth("1 + 2"), 0);
+    assert_eq!(brace_depth("fn f() -> i32 {"), 1);
+    assert_eq!(brace_depth("fn f() -> i32 { if true {"), 2);
+    assert_eq!(brace_depth("fn f() -> i32 { 1 }"), 0);
+}
+
+#[test]
+fn remembers_declarations() {
+    let mut repl = Repl::new();
+    assert!(repl.eval_input("let x = 2;").is_empty());
+    assert!(repl.eval_input("fn double(n: i32) -> i32 {\n    n * 2\n}").is_empty());
+    assert_eq!(repl.eval_input("double(x)"), vec!["4: i32"]);
+    assert!(repl.eval_input("double(x);").is_empty());
+}
+
+#[test]
+fn commands() {
+    let mut repl = Repl::new();
+    repl.eval_input("fn f(a: bool) -> (bool, i64) { (a, 1) }");
+    assert_eq!(repl.eval_input(":type f"), vec!["fn(bool) -> (bool, i64)"]);
+    assert_eq!(repl.eval_input(":type f(true)"), vec!["(bool, i64)"]);
+    assert_eq!(repl.eval_input(":tokens a+1"), vec![
+        "Identifier `a` 0..1",
+        "Op { kind: Plus } `+` 1..2",
+        "Literal { kind: Int } `1` 2..3",
+    ]);
+}
+
+#[test]
+fn errors_are_not_remembered() {
+    let mut repl = Repl::new();
+    assert_eq!(repl.eval_input("let y: bool = 1;"), vec!["error: expected `bool`, found `i32`"]);
+    assert_eq!(repl.eval_input("y"), vec!["error: cannot find `y` in this scope"]);
+
+    assert_eq!(repl.eval_input("let z = 1 / 0;"), vec!["error: attempted to divide by zero"]);
+    assert_eq!(repl.eval_input("z"), vec!["error: cannot find `z` in this scope"]);
+    assert_eq!(repl.eval_input("0u8 - 1u8"), vec!["error: arithmetic overflow"]);
+
+    // What was declared before is still there, as it was.
+    assert!(repl.eval_input("let x = 1;").is_empty());
+    assert_eq!(repl.eval_input("let x = true; 1 / 0;"), vec!["error: attempted to divide by zero"]);
+    assert_eq!(repl.eval_input("x + 1"), vec!["2: i32"]);
+}
+
+#[test]
+fn printed_output() {
+    let mut repl = Repl::new();
+    assert_eq!(repl.eval_input("println(1); print(2); 3"), vec!["1", "2", "3: i32"]);
+    // A default repl is a new one, so it captures what's printed too.
+    assert_eq!(Repl::default().eval_input("let x = 1; print(x);"), vec!["1"]);
+    let error = "error: i32::parse: `true`: invalid digit found in string";
+    assert_eq!(repl.eval_input("println(4); i32::parse(str::from(true));"), vec!["4", error]);
+}
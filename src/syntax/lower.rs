@@ -0,0 +1,448 @@
+// Derives the `ASTree` from a syntax tree by dropping everything that doesn't carry meaning, like
+// trivia, punctuation and parentheses. Nodes that are missing pieces because of a parse error are
+// skipped.
+
+use std::cell::RefCell;
+
+use crate::lex;
+use crate::ast::token::{T, Token, TokenKind};
+use crate::ast::{ASTree, Statement, Expression, ExprId, Exprs, Parameter, Symbol, Type, TupleType};
+use crate::ast::{FunctionStatement, StructStatement, EnumStatement, LetStatement};
+use crate::ast::{ImplStatement, TraitStatement, TraitMethod, ImportStatement};
+use crate::ast::{ClosureExpression, IdentExpression, BlockExpression, CallExpression};
+use crate::ast::{MethodCallExpression, StructExpression, FieldInit, FieldExpression, Span};
+use crate::ast::{IfExpression, ElseExpression};
+use crate::ast::{LitKind, LiteralExpression, Tuple, List};
+use crate::ast::{BinaryExpression, UnaryExpression};
+use crate::parse::{Parser, TypeScope, binop_tok_to_ast, unop_tok_to_ast, parse_literal};
+
+use super::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub fn lower(root: &SyntaxNode) -> ASTree {
+    let lowerer = Lowerer::new(root);
+    let statements = root
+        .child_nodes()
+        .filter_map(|node| lowerer.statement(&node))
+        .collect();
+    ASTree::new(statements, lowerer.exprs.into_inner())
+}
+
+struct Lowerer {
+    // Every token that isn't trivia, ending with EOF.
+    tokens: Vec<Token>,
+    // Same as `Parser::scope`.
+    scope: RefCell<TypeScope>,
+    // The expressions of the tree, in the same order the parser allocates them in.
+    exprs: RefCell<Exprs>,
+}
+
+impl Lowerer {
+    fn new(root: &SyntaxNode) -> Self {
+        let len = root.span().end;
+        let mut tokens: Vec<Token> = root
+            .tokens()
+            .iter()
+            .filter_map(|token| {
+                let span = token.span();
+                Some(Token::new(token.token_kind()?, span.start, span.end))
+            })
+            .collect();
+        tokens.push(Token::new(TokenKind::EOF, len, len));
+        Self { tokens, scope: RefCell::default(), exprs: RefCell::default() }
+    }
+
+    fn with_scope<T>(&self, scope: TypeScope, f: impl FnOnce() -> T) -> T {
+        let outer = self.scope.replace(scope);
+        let result = f();
+        self.scope.replace(outer);
+        result
+    }
+
+    // The first token at or after `offset`, which is what the parser keeps as an expression
+    // statement's `end_token`.
+    fn token_after(&self, offset: usize) -> Token {
+        let index = self.tokens.partition_point(|tok| tok.start < offset);
+        self.tokens[index.min(self.tokens.len() - 1)]
+    }
+
+    // Whatever expressions a statement that can't be lowered had already allocated are dropped,
+    // the same as the parser drops the ones of a statement that doesn't parse.
+    fn statement(&self, node: &SyntaxNode) -> Option<Statement> {
+        let exprs = self.exprs.borrow().len();
+        let statement = self.statement_inner(node);
+        if statement.is_none() {
+            self.exprs.borrow_mut().truncate(exprs);
+        }
+        statement
+    }
+
+    fn statement_inner(&self, node: &SyntaxNode) -> Option<Statement> {
+        let statement = match node.kind() {
+            SyntaxKind::Function => Statement::Function(self.function(node)?),
+            SyntaxKind::Struct => Statement::Struct(self.structure(node)?),
+            SyntaxKind::Enum => Statement::Enum(self.enumeration(node)?),
+            SyntaxKind::Impl => Statement::Impl(self.implementation(node)?),
+            SyntaxKind::Trait => Statement::Trait(self.trait_statement(node)?),
+            SyntaxKind::Import => Statement::Import(self.import(node)?),
+            SyntaxKind::Let => Statement::Let(self.let_statement(node)?),
+            SyntaxKind::ExprStatement => {
+                let expr = node.child_nodes().next()?;
+                let end_token = self.token_after(expr.span().end);
+                let expr = self.expr(&expr)?;
+                Statement::expression(expr, end_token, &self.exprs.borrow())
+            }
+            SyntaxKind::Error => Statement::Error { span: node.span() },
+            _ => return None,
+        };
+        Some(statement)
+    }
+
+    fn function(&self, node: &SyntaxNode) -> Option<FunctionStatement> {
+        let function = self.function_in(node, &TypeScope::default())?;
+        Some(FunctionStatement { public: is_public(node), ..function })
+    }
+
+    fn function_in(&self, node: &SyntaxNode, outer: &TypeScope) -> Option<FunctionStatement> {
+        let TraitMethod { name, generics, arguments, return_type, block, name_span, span } = self.method(node, outer)?;
+        Some(FunctionStatement { public: false, name, generics, return_type, arguments, block: block?, name_span, span })
+    }
+
+    fn method(&self, node: &SyntaxNode, outer: &TypeScope) -> Option<TraitMethod> {
+        if node.kind() != SyntaxKind::Function { return None }
+
+        let name = identifiers(node).next()?;
+        let generics = generic_params(node);
+        let (arguments, return_type, block) = self.with_scope(outer.with_generics(&generics), || {
+            let mut children = node.child_nodes().filter(|child| child.kind() != SyntaxKind::GenericParams);
+            let arguments = self.params(&children.next()?)?;
+            let return_type = self.ty(&children.next()?)?;
+            let block = match children.next() {
+                Some(block) => Some(self.block(&block)?),
+                None => None,
+            };
+            Some((arguments, return_type, block))
+        })?;
+
+        Some(TraitMethod {
+            name: name_of(&name),
+            generics,
+            arguments,
+            return_type,
+            block,
+            name_span: name.span(),
+            span: node.span(),
+        })
+    }
+
+    fn structure(&self, node: &SyntaxNode) -> Option<StructStatement> {
+        let name = identifiers(node).next()?;
+        let generics = generic_params(node);
+        let fields = node.child_nodes().find(|child| child.kind() == SyntaxKind::ParamList)?;
+        let fields = self.with_scope(TypeScope::default().with_generics(&generics), || self.params(&fields))?;
+
+        let name_span = name.span();
+        Some(StructStatement { public: is_public(node), name: name_of(&name), generics, fields, name_span, span: node.span() })
+    }
+
+    fn enumeration(&self, node: &SyntaxNode) -> Option<EnumStatement> {
+        let mut identifiers = identifiers(node);
+        let name = identifiers.next()?;
+        let (variants, variant_spans) = identifiers.map(|variant| (name_of(&variant), variant.span())).unzip();
+
+        let generics = generic_params(node);
+        let name_span = name.span();
+        Some(EnumStatement { public: is_public(node), name: name_of(&name), generics, variants, variant_spans, name_span, span: node.span() })
+    }
+
+    fn implementation(&self, node: &SyntaxNode) -> Option<ImplStatement> {
+        let generics = generic_params(node);
+        let mut scope = TypeScope::default().with_generics(&generics);
+
+        let self_type = node.child_nodes().find(|child| is_type(child.kind()))?;
+        let self_type = self.with_scope(scope.clone(), || self.ty(&self_type))?;
+        scope.self_type = Some(self_type.clone());
+
+        let methods = node
+            .child_nodes()
+            .filter(|child| child.kind() == SyntaxKind::Function)
+            .map(|method| self.function_in(&method, &scope))
+            .collect::<Option<_>>()?;
+
+        // The only name that isn't inside of a type is the trait's.
+        let trait_name = path(node);
+        Some(ImplStatement { generics, trait_name, self_type, methods, span: node.span() })
+    }
+
+    fn trait_statement(&self, node: &SyntaxNode) -> Option<TraitStatement> {
+        let name = identifiers(node).next()?;
+        let scope = TypeScope { self_type: Some(Type::Generic { name: "Self".into() }), ..Default::default() };
+        let methods = node
+            .child_nodes()
+            .map(|method| self.method(&method, &scope))
+            .collect::<Option<_>>()?;
+        let public = is_public(node);
+        Some(TraitStatement { public, name: name_of(&name), methods, name_span: name.span(), span: node.span() })
+    }
+
+    fn import(&self, node: &SyntaxNode) -> Option<ImportStatement> {
+        let path: Vec<Symbol> = identifiers(node).map(|name| name_of(&name)).collect();
+        if path.is_empty() { return None }
+        let child = node.child_tokens().any(|token| token.token_kind() == Some(T!("mod")));
+        Some(ImportStatement { path, child, span: node.span() })
+    }
+
+    fn let_statement(&self, node: &SyntaxNode) -> Option<LetStatement> {
+        let name = identifiers(node).next()?;
+
+        let mut var_type = None;
+        let mut value = None;
+        for child in node.child_nodes() {
+            match is_type(child.kind()) {
+                true => var_type = Some(self.ty(&child)?),
+                false => value = Some(self.expr(&child)?),
+            }
+        }
+
+        Some(LetStatement {
+            name: name_of(&name),
+            var_type,
+            value: value?,
+            name_span: name.span(),
+            span: node.span(),
+        })
+    }
+
+    fn params(&self, node: &SyntaxNode) -> Option<Vec<Parameter>> {
+        if node.kind() != SyntaxKind::ParamList { return None }
+
+        node.child_nodes()
+            .map(|param| {
+                let name = identifiers(&param).next()?;
+                // Only `self` gets to leave out its type.
+                let param_type = match param.child_nodes().next() {
+                    Some(param_type) => self.ty(&param_type)?,
+                    None if name.text() == "self" => self.scope.borrow().self_type(),
+                    None => return None,
+                };
+                Some(Parameter { name: name_of(&name), param_type, name_span: name.span() })
+            })
+            .collect()
+    }
+
+    fn ty(&self, node: &SyntaxNode) -> Option<Type> {
+        let ty = match node.kind() {
+            SyntaxKind::TypeName => {
+                let name = path(node)?;
+                match node.child_nodes().next() {
+                    Some(arguments) => Type::Applied { name, arguments: self.types(&arguments)?.into() },
+                    None if name.as_str().contains("::") => Type::UserDefined { name },
+                    None => match self.scope.borrow().resolve(name) {
+                        Some(ty) => ty,
+                        None => Parser::parse_type_from_ident(&name.as_str()),
+                    },
+                }
+            }
+
+            SyntaxKind::TupleType => Type::Tuple(TupleType(self.types(node)?)),
+
+            SyntaxKind::ListType => Type::List(Box::new(self.ty(&node.child_nodes().next()?)?)),
+
+            SyntaxKind::FnType => {
+                if !node.child_tokens().any(|token| token.token_kind() == Some(T!("->"))) {
+                    return None;
+                }
+                let mut arguments = self.types(node)?;
+                let return_type = Box::new(arguments.pop()?);
+                Type::Fn { arguments, return_type }
+            }
+
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    fn types(&self, node: &SyntaxNode) -> Option<Vec<Type>> {
+        node.child_nodes().map(|child| self.ty(&child)).collect()
+    }
+
+    fn expr(&self, node: &SyntaxNode) -> Option<ExprId> {
+        let span = node.span();
+        let expr = match node.kind() {
+            SyntaxKind::Literal => {
+                let token = node.child_tokens().next()?;
+                let Some(TokenKind::Literal { kind }) = token.token_kind() else { return None };
+                Expression::Literal(parse_literal(kind, token.text(), span, &mut self.exprs.borrow_mut()).ok()?)
+            }
+
+            SyntaxKind::Name => {
+                Expression::Identifier(IdentExpression { name: path(node)?, span })
+            }
+
+            SyntaxKind::Paren => return self.expr(&node.child_nodes().next()?),
+
+            SyntaxKind::Tuple => {
+                let kind = LitKind::Tuple(Tuple(self.exprs(node)?));
+                Expression::Literal(LiteralExpression { kind, span })
+            }
+
+            SyntaxKind::List => {
+                let kind = LitKind::List(List(self.exprs(node)?));
+                Expression::Literal(LiteralExpression { kind, span })
+            }
+
+            SyntaxKind::Block => Expression::Block(self.block(node)?),
+
+            SyntaxKind::If => Expression::If(self.if_expr(node)?),
+
+            SyntaxKind::Closure => {
+                let mut children = node.child_nodes();
+                let arguments = self.params(&children.next()?)?;
+                let return_type = self.ty(&children.next()?)?;
+                let block = self.block(&children.next()?)?;
+                Expression::Closure(ClosureExpression { arguments, block, return_type, span })
+            }
+
+            SyntaxKind::Call => {
+                let mut children = node.child_nodes();
+                let function = self.expr(&children.next()?)?;
+                let arguments = self.exprs(&children.next()?)?;
+                Expression::Call(CallExpression { function, arguments, span })
+            }
+
+            SyntaxKind::MethodCall => {
+                let method = identifiers(node).next()?;
+                let mut children = node.child_nodes();
+                let receiver = self.expr(&children.next()?)?;
+                let arguments = self.exprs(&children.next()?)?;
+                Expression::MethodCall(MethodCallExpression {
+                    receiver,
+                    method: name_of(&method),
+                    method_span: method.span(),
+                    arguments,
+                    self_type: None,
+                    span,
+                })
+            }
+
+            SyntaxKind::StructLit => {
+                let names: Vec<SyntaxToken> = identifiers(node).collect();
+                let name_span = Span::new(names.first()?.span().start, names.last()?.span().end);
+                let fields = node
+                    .child_nodes()
+                    .map(|field| {
+                        let name = identifiers(&field).next()?;
+                        let value = self.expr(&field.child_nodes().next()?)?;
+                        Some(FieldInit { name: name_of(&name), value, name_span: name.span() })
+                    })
+                    .collect::<Option<_>>()?;
+                Expression::Struct(StructExpression { name: path(node)?, fields, name_span, span })
+            }
+
+            SyntaxKind::Field => {
+                let field = identifiers(node).next()?;
+                let receiver = self.expr(&node.child_nodes().next()?)?;
+                Expression::Field(FieldExpression {
+                    receiver,
+                    field: name_of(&field),
+                    field_span: field.span(),
+                    span,
+                })
+            }
+
+            SyntaxKind::Unary => {
+                let op = unop_tok_to_ast(node.child_tokens().next()?.token_kind()?)?;
+                let rhs = self.expr(&node.child_nodes().next()?)?;
+                Expression::Unary(UnaryExpression { rhs, op, ty: None, span })
+            }
+
+            SyntaxKind::Binary => {
+                let op = binop_tok_to_ast(node.child_tokens().next()?.token_kind()?)?;
+                let mut children = node.child_nodes();
+                let lhs = self.expr(&children.next()?)?;
+                let rhs = self.expr(&children.next()?)?;
+                Expression::Binary(BinaryExpression { lhs, rhs, op, ty: None, span })
+            }
+
+            _ => return None,
+        };
+        Some(self.exprs.borrow_mut().alloc(expr))
+    }
+
+    fn exprs(&self, node: &SyntaxNode) -> Option<Vec<ExprId>> {
+        node.child_nodes().map(|child| self.expr(&child)).collect()
+    }
+
+    fn block(&self, node: &SyntaxNode) -> Option<BlockExpression> {
+        if node.kind() != SyntaxKind::Block { return None }
+
+        let mut statements: Vec<Statement> = node
+            .child_nodes()
+            .map(|child| self.statement(&child))
+            .collect::<Option<_>>()?;
+
+        // Same as in `parse_block()`, an expression at the end without a `;` is the block's value.
+        let expression = match statements.last() {
+            Some(Statement::Expression { end_token, .. }) if end_token.kind != T!(";") => {
+                let Some(Statement::Expression { expr, .. }) = statements.pop() else { unreachable!() };
+                Some(expr)
+            }
+            _ => None,
+        };
+
+        Some(BlockExpression { statements, expression, span: node.span() })
+    }
+
+    fn if_expr(&self, node: &SyntaxNode) -> Option<IfExpression> {
+        let mut children = node.child_nodes();
+        let condition = self.expr(&children.next()?)?;
+        let body = self.block(&children.next()?)?;
+
+        let else_body = match children.next() {
+            Some(else_node) => {
+                let child = else_node.child_nodes().next()?;
+                let else_body = match child.kind() {
+                    SyntaxKind::If => ElseExpression::ElseIf(self.expr(&child)?),
+                    _ => ElseExpression::Else(self.block(&child)?),
+                };
+                Some(else_body)
+            }
+            None => None,
+        };
+
+        Some(IfExpression { condition, body, else_body, span: node.span() })
+    }
+}
+
+// The name an identifier token stands for, the same as the parser gives it.
+fn name_of(token: &SyntaxToken) -> Symbol {
+    Symbol::intern(&lex::normalize_identifier(token.text()))
+}
+
+fn identifiers(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> {
+    node.child_tokens().filter(|token| token.token_kind() == Some(T!("ID")))
+}
+
+// The names making up a path like `geom::Point`, joined back together.
+fn path(node: &SyntaxNode) -> Option<Symbol> {
+    let names: Vec<Symbol> = identifiers(node).map(|name| name_of(&name)).collect();
+    if names.is_empty() { return None }
+    Some(Symbol::join(&names, "::"))
+}
+
+fn is_public(node: &SyntaxNode) -> bool {
+    node.child_tokens().any(|token| token.token_kind() == Some(T!("pub")))
+}
+
+fn generic_params(node: &SyntaxNode) -> Vec<Symbol> {
+    let Some(params) = node.child_nodes().find(|child| child.kind() == SyntaxKind::GenericParams) else {
+        return Vec::new();
+    };
+    identifiers(&params).map(|name| name_of(&name)).collect()
+}
+
+fn is_type(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::TypeName | SyntaxKind::TupleType | SyntaxKind::ListType | SyntaxKind::FnType
+    )
+}
@@ -0,0 +1,412 @@
+// A lossless concrete syntax tree. Every token the source was made of, whitespace and comments
+// included, ends up somewhere in the tree, so printing it back out gives the exact same text.
+//
+// The tree is split in two layers, same as how rust-analyzer does it. Green nodes only know their
+// kind, their length and their children, so they can be shared and reused. Red nodes
+// (`SyntaxNode`, `SyntaxToken`) are built on top of them on demand and know their absolute
+// position and their parent.
+
+pub mod lower;
+pub mod reparse;
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::Span;
+use crate::ast::token::{Token, TokenKind};
+use crate::lex::{self, lexer::Lexer};
+use crate::parse::stream::TokenStream;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyntaxKind {
+    // Tokens
+    Token(TokenKind),
+    Whitespace,
+    Comment,
+
+    // Nodes
+    Root,
+    Function,
+    Struct,
+    Enum,
+    Impl,
+    Trait,
+    Import,
+    Let,
+    ExprStatement,
+
+    ParamList,
+    Param,
+    ArgList,
+    GenericParams,
+
+    Block,
+    If,
+    Else,
+    Closure,
+    Call,
+    MethodCall,
+    StructLit,
+    FieldInit,
+    Field,
+    Binary,
+    Unary,
+    Literal,
+    Paren,
+    Tuple,
+    List,
+    Name,
+
+    TypeName,
+    TupleType,
+    ListType,
+    FnType,
+    TypeArgs,
+
+    // Whatever the parser couldn't make sense of.
+    Error,
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Comment)
+    }
+}
+
+// What the parser records as it goes, turned into a tree by `build()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Start(SyntaxKind),
+    Finish,
+    Token(Token),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenToken {
+    pub kind: SyntaxKind,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenNode {
+    pub kind: SyntaxKind,
+    pub len: usize,
+    pub children: Vec<GreenElement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        Self { kind, len, children }
+    }
+}
+
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        Self(Rc::new(NodeData { green, offset: 0, parent: None }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.len)
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+
+    pub fn children(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut children = Vec::with_capacity(self.0.green.children.len());
+        for child in &self.0.green.children {
+            let element = match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    offset,
+                    parent: Some(self.clone()),
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    offset,
+                }),
+            };
+            offset += child.len();
+            children.push(element);
+        }
+        children
+    }
+
+    pub fn child_nodes(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.children().into_iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    // Direct child tokens that aren't whitespace or comments.
+    pub fn child_tokens(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.children().into_iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) if !token.kind().is_trivia() => Some(token),
+            _ => None,
+        })
+    }
+
+    // Every token under this node in order, trivia included.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.text.len())
+    }
+
+    // The token kind the parser saw, if this isn't trivia.
+    pub fn token_kind(&self) -> Option<TokenKind> {
+        match self.kind() {
+            SyntaxKind::Token(kind) => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.green)
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => write!(f, "{node}")?,
+                GreenElement::Token(token) => write!(f, "{}", token.text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Prints the tree one element per line, indented by depth.
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let depth = std::iter::successors(self.parent(), |node| node.parent()).count();
+        let span = self.span();
+        writeln!(f, "{:indent$}{:?}@{}..{}", "", self.kind(), span.start, span.end, indent = depth * 2)?;
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => write!(f, "{node:?}")?,
+                SyntaxElement::Token(token) => writeln!(f, "{:indent$}{token:?}", "", indent = depth * 2 + 2)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{} {:?}", self.kind(), span.start, span.end, self.text())
+    }
+}
+
+// Turns the parser's events into a tree. Trivia isn't part of the events, it's whatever text sits
+// between two tokens. Leading trivia is kept out of nodes that are about to start so that a node's
+// span starts at its first real token.
+pub fn build(src: &str, events: &[Event], tokens: &[Token]) -> SyntaxNode {
+    let mut builder = Builder { src, pos: 0, stack: vec![(SyntaxKind::Root, Vec::new())] };
+
+    let token_starts: Vec<usize> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Token(tok) if tok.kind != TokenKind::EOF => Some(tok.start),
+            _ => None,
+        })
+        .collect();
+    let mut next_token = 0;
+
+    for event in events {
+        match *event {
+            Event::Start(kind) => {
+                if let Some(&start) = token_starts.get(next_token) {
+                    builder.trivia(start);
+                }
+                builder.stack.push((kind, Vec::new()));
+            }
+
+            Event::Finish => builder.finish(),
+
+            Event::Token(tok) => {
+                if tok.kind == TokenKind::EOF { continue }
+                next_token += 1;
+                // Shouldn't happen, but a token can't be in the tree twice.
+                if tok.start < builder.pos { continue }
+                builder.trivia(tok.start);
+                builder.token(SyntaxKind::Token(tok.kind), tok.end);
+            }
+        }
+    }
+
+    // Anything left open didn't get finished because of an error.
+    while builder.stack.len() > 1 {
+        builder.finish();
+    }
+
+    // If the parser stopped before the end, like for a fragment, whatever comes after that gets
+    // put in an error node.
+    // A `>>` can be half used up from closing a list of type arguments.
+    let rest: Vec<&Token> = tokens
+        .iter()
+        .filter(|tok| tok.end > builder.pos && tok.kind != TokenKind::EOF)
+        .collect();
+    if let Some(first) = rest.first() {
+        builder.trivia(first.start);
+        builder.stack.push((SyntaxKind::Error, Vec::new()));
+        for tok in rest {
+            builder.trivia(tok.start);
+            builder.token(SyntaxKind::Token(tok.kind), tok.end);
+        }
+        builder.finish();
+    }
+    builder.trivia(src.len());
+
+    let (kind, children) = builder.stack.pop().unwrap_or((SyntaxKind::Root, Vec::new()));
+    SyntaxNode::new_root(Rc::new(GreenNode::new(kind, children)))
+}
+
+struct Builder<'src> {
+    src: &'src str,
+    pos: usize,
+    stack: Vec<(SyntaxKind, Vec<GreenElement>)>,
+}
+
+impl<'src> Builder<'src> {
+    fn token(&mut self, kind: SyntaxKind, end: usize) {
+        let text = self.src[self.pos..end].to_string();
+        let token = GreenElement::Token(Rc::new(GreenToken { kind, text }));
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(token);
+        }
+        self.pos = end;
+    }
+
+    fn finish(&mut self) {
+        if self.stack.len() < 2 { return }
+        let Some((kind, children)) = self.stack.pop() else { return };
+        // Nodes for things that failed before consuming anything would only get in the way.
+        if children.is_empty() { return }
+        let node = GreenElement::Node(Rc::new(GreenNode::new(kind, children)));
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(node);
+        }
+    }
+
+    // Adds the whitespace and comments up to `end`.
+    fn trivia(&mut self, end: usize) {
+        if end <= self.pos { return }
+
+        for (kind, len) in trivia(&self.src[self.pos..end]) {
+            self.token(kind, self.pos + len);
+        }
+    }
+}
+
+// Splits up text that sits between two tokens.
+fn trivia(text: &str) -> Vec<(SyntaxKind, usize)> {
+    let mut lexer = Lexer::new(text);
+    let mut trivia = Vec::new();
+    loop {
+        let tok = lexer.next_token();
+        let kind = match tok.kind {
+            lex::TokenKind::EOF => break,
+            lex::TokenKind::Whitespace => SyntaxKind::Whitespace,
+            lex::TokenKind::LineComment => SyntaxKind::Comment,
+            // Only trivia should ever be between two tokens.
+            _ => SyntaxKind::Error,
+        };
+        trivia.push((kind, tok.length));
+    }
+    trivia
+}
+
+// Every token in `src` along with its length, trivia included. This is what the syntax tree's
+// tokens would be without having to parse anything.
+pub fn tokenize(src: &str) -> Vec<(SyntaxKind, usize)> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    for tok in TokenStream::new(src).tokens {
+        tokens.extend(trivia(&src[pos..tok.start]));
+        if tok.kind == TokenKind::EOF { break }
+        tokens.push((SyntaxKind::Token(tok.kind), tok.end - tok.start));
+        pos = tok.end;
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests;
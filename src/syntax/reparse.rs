@@ -0,0 +1,243 @@
+// Incremental reparsing for editors. An edit first tries to only relex the token it's in, then to
+// reparse the innermost block or item around it, and only if neither works out does the whole
+// file get parsed again. Everything outside of what got reparsed keeps sharing its green nodes with
+// the old tree.
+
+use std::rc::Rc;
+
+use crate::ast::Span;
+use crate::ast::token::TokenKind;
+use crate::parse::{Parser, ParseError};
+
+use super::{tokenize, SyntaxKind, SyntaxNode, GreenNode, GreenToken, GreenElement};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    // What gets replaced in the old text.
+    pub span: Span,
+    pub insert: String,
+}
+
+impl Edit {
+    pub fn new(span: Span, insert: impl Into<String>) -> Self {
+        Self { span, insert: insert.into() }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        format!("{}{}{}", &text[..self.span.start], self.insert, &text[self.span.end..])
+    }
+
+    // Applies the edit to `text`, where `text` starts at `offset` in the old text and covers the
+    // whole edit.
+    fn apply_at(&self, text: &str, offset: usize) -> String {
+        let span = Span::new(self.span.start - offset, self.span.end - offset);
+        Edit { span, insert: self.insert.clone() }.apply(text)
+    }
+
+    // How much longer the text gets.
+    fn delta(&self) -> isize {
+        self.insert.len() as isize - (self.span.end - self.span.start) as isize
+    }
+}
+
+pub fn reparse(root: &SyntaxNode, errors: &[ParseError], edit: &Edit) -> (SyntaxNode, Vec<ParseError>) {
+    // Which errors get reported around unclosed delimiters depends on the whole file, down to how
+    // it's indented.
+    let unbalanced = errors.iter().any(|err| matches!(
+        err.without_fix(),
+        ParseError::UnclosedDelimiter { .. }
+            | ParseError::MismatchedDelimiter { .. }
+            | ParseError::UnexpectedDelimiter { .. }
+    ));
+    if unbalanced {
+        return Parser::parse_syntax(&edit.apply(&root.to_string()));
+    }
+    if let Some(result) = reparse_token(root, errors, edit) {
+        return result;
+    }
+    if let Some(result) = reparse_node(root, errors, edit) {
+        return result;
+    }
+    Parser::parse_syntax(&edit.apply(&root.to_string()))
+}
+
+// Replaces the token the edit is in if it's still the same kind of token afterwards and doesn't
+// run into its neighbours. Nothing gets parsed, so this only works for tokens the parser doesn't
+// look inside of.
+fn reparse_token(root: &SyntaxNode, errors: &[ParseError], edit: &Edit) -> Option<(SyntaxNode, Vec<ParseError>)> {
+    let path = covering(root.green(), edit.span);
+    let &(index, offset) = path.last()?;
+    let parent = parent_of(root.green(), &path);
+    let GreenElement::Token(token) = &parent.children[index] else { return None };
+
+    let text = edit.apply_at(&token.text, offset);
+    if text.is_empty() || !opaque(token.kind, &token.text) || !opaque(token.kind, &text) {
+        return None;
+    }
+    // An error around the token can depend on what it says, like a misspelled keyword does.
+    let end = offset + token.text.len();
+    let touches = |span: Span| span.start <= end && offset <= span.end;
+    if errors.iter().any(|err| touches(err.span()) || err.fix().is_some_and(|fix| touches(fix.edit.span))) {
+        return None;
+    }
+
+    // Lexing the token along with whatever's right next to it has to give back the same tokens.
+    let before = offset.checked_sub(1).and_then(|start| token_at(root.green(), Span::new(start, offset)));
+    let after = token_at(root.green(), Span::new(end, end + 1));
+
+    let mut expected = Vec::new();
+    let mut window = String::new();
+    for (token, text) in [(before.as_deref(), None), (Some(&**token), Some(&text)), (after.as_deref(), None)] {
+        let Some(token) = token else { continue };
+        let text = text.unwrap_or(&token.text);
+        expected.push((token.kind, text.len()));
+        window.push_str(text);
+    }
+    if tokenize(&window) != expected {
+        return None;
+    }
+
+    let replacement = GreenElement::Token(Rc::new(GreenToken { kind: token.kind, text }));
+    let green = replace(root.green(), &path, replacement);
+
+    let errors = shift_errors(errors, Span::new(offset, end), edit.delta());
+    Some((SyntaxNode::new_root(green), errors))
+}
+
+// Whether the parser only ever looks at what kind of token this is. A bare `self` parameter is
+// told apart from any other name by its text, literals get checked for whether they're valid, and
+// strings can have whole expressions in them.
+fn opaque(kind: SyntaxKind, text: &str) -> bool {
+    match kind {
+        SyntaxKind::Token(TokenKind::Identifier) => text != "self" && text != "Self",
+        SyntaxKind::Token(TokenKind::Literal { .. }) => false,
+        _ => true,
+    }
+}
+
+// Reparses the innermost block or item that the edit is inside of.
+fn reparse_node(root: &SyntaxNode, errors: &[ParseError], edit: &Edit) -> Option<(SyntaxNode, Vec<ParseError>)> {
+    let path = covering(root.green(), edit.span);
+
+    // The edit can't touch the first or the last token, otherwise what's around it could change.
+    for depth in (0..path.len()).rev() {
+        let (index, offset) = path[depth];
+        let GreenElement::Node(node) = &parent_of(root.green(), &path[..=depth]).children[index] else { continue };
+        let end = offset + node.len;
+        let reparsable = matches!(
+            node.kind,
+            SyntaxKind::Block | SyntaxKind::Function | SyntaxKind::Struct | SyntaxKind::Enum | SyntaxKind::Let
+        );
+        if !reparsable || edit.span.start <= offset || edit.span.end >= end {
+            continue;
+        }
+
+        // If the node didn't parse before, the parser skipped ahead past it while recovering, and
+        // fixing it means everything after it needs parsing again too.
+        let old_text = node.to_string();
+        let Some((_, old_errors)) = Parser::parse_syntax_fragment(&old_text, node.kind) else { continue };
+
+        // Whatever's around the node can have errors on it too, like a missing `;` before it that
+        // gets reported on its first token. Parsing it on its own won't bring those back.
+        let span = Span::new(offset, end);
+        let (within, outside): (Vec<ParseError>, Vec<ParseError>) =
+            errors.iter().cloned().partition(|err| inside(err.span(), span));
+        let old_errors: Vec<ParseError> = old_errors.iter().map(|err| err.map_offsets(|o| o + offset)).collect();
+        if sorted(&within) != sorted(&old_errors) {
+            continue;
+        }
+
+        let text = edit.apply_at(&old_text, offset);
+        let Some((fragment, fragment_errors)) = Parser::parse_syntax_fragment(&text, node.kind) else { continue };
+
+        let green = replace(root.green(), &path[..=depth], GreenElement::Node(fragment.green().clone()));
+
+        // The old errors from inside the node are gone, and the new ones are relative to it.
+        let mut errors = shift_errors(&outside, span, edit.delta());
+        errors.extend(fragment_errors.iter().map(|err| err.map_offsets(|o| o + offset)));
+
+        return Some((SyntaxNode::new_root(green), errors));
+    }
+    None
+}
+
+// The child indices, along with the offsets of those children, leading down to the innermost
+// element that covers `span`.
+fn covering(root: &GreenNode, span: Span) -> Vec<(usize, usize)> {
+    let mut path = Vec::new();
+    let mut node = root;
+    let mut offset = 0;
+    'descend: loop {
+        for (index, child) in node.children.iter().enumerate() {
+            let end = offset + child.len();
+            if offset <= span.start && span.end <= end {
+                path.push((index, offset));
+                match child {
+                    GreenElement::Node(child) => {
+                        node = child;
+                        continue 'descend;
+                    }
+                    GreenElement::Token(_) => break 'descend,
+                }
+            }
+            offset = end;
+        }
+        break;
+    }
+    path
+}
+
+// The node holding the last element in `path`.
+fn parent_of<'a>(root: &'a GreenNode, path: &[(usize, usize)]) -> &'a GreenNode {
+    let mut node = root;
+    for &(index, _) in &path[..path.len().saturating_sub(1)] {
+        let GreenElement::Node(child) = &node.children[index] else { break };
+        node = child;
+    }
+    node
+}
+
+// The token that covers `span`, if there's a single one.
+fn token_at(root: &GreenNode, span: Span) -> Option<Rc<GreenToken>> {
+    let path = covering(root, span);
+    let &(index, _) = path.last()?;
+    match &parent_of(root, &path).children[index] {
+        GreenElement::Token(token) => Some(token.clone()),
+        GreenElement::Node(_) => None,
+    }
+}
+
+// Rebuilds the nodes along `path` with the element at its end swapped out. Every other child is
+// shared with the old tree.
+fn replace(node: &GreenNode, path: &[(usize, usize)], replacement: GreenElement) -> Rc<GreenNode> {
+    let mut children = node.children.clone();
+    match path {
+        [] => unreachable!("the path to replace should never be empty"),
+        [(index, _)] => children[*index] = replacement,
+        [(index, _), rest @ ..] => {
+            let GreenElement::Node(child) = &node.children[*index] else { unreachable!() };
+            children[*index] = GreenElement::Node(replace(child, rest, replacement));
+        }
+    }
+    Rc::new(GreenNode::new(node.kind, children))
+}
+
+// Errors in an order that doesn't depend on when the parser ran into them.
+fn sorted(errors: &[ParseError]) -> Vec<String> {
+    let mut errors: Vec<String> = errors.iter().map(|err| format!("{err:?}")).collect();
+    errors.sort();
+    errors
+}
+
+fn inside(span: Span, outer: Span) -> bool {
+    outer.start <= span.start && span.end <= outer.end
+}
+
+// Moves everything at or after the end of `span` by `delta`.
+fn shift_errors(errors: &[ParseError], span: Span, delta: isize) -> Vec<ParseError> {
+    let shift = |o: usize| match o >= span.end {
+        true => o.saturating_add_signed(delta),
+        false => o,
+    };
+    errors.iter().map(|err| err.map_offsets(shift)).collect()
+}
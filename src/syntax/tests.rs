@@ -0,0 +1,301 @@
+use super::*;
+use super::lower::lower;
+use super::reparse::{reparse, Edit};
+use crate::parse::ParseError;
+use crate::parse::Parser;
+
+const PROGRAM: &str = "\
+// Adds things up.
+import geom::shapes;
+mod lines;
+pub struct Point { x: i32, y: i32, }
+enum Color { Red Green }
+struct Pair<A, B> { a: A, b: {Pair<B, A>}, }
+trait Sized { fn size(self) -> i32; fn twice(self) -> i32 { self.size() * 2 } }
+impl<T> Sized for {T} { fn size(self: {T}) -> i32 { 2 } }
+
+fn wrap<T>(x: T, p: Pair<T, Pair<T, T>>) -> {T} { [x] }
+
+fn add(a: i32, b: i32) -> i32 {
+    a + b // the sum
+}
+
+fn main(args: {str}) -> void {
+    let f: fn(i32, i32) -> i32 = \\(a: i32, b: i32) -> i32 { a * (b - 1) };
+    let t = (1, 'c', [2.5, 3.0]);
+    let cafe\u{301} = (r\"\\n\", \"{café} {t}\");
+    if !true && f(1, 2) == 3 {
+        add(1, -2);
+    } else if false {} else { ~4 }
+    args.size().twice(1, t);
+    let c: shapes::Circle = shapes::unit(geom :: ORIGIN);
+    {
+        (1 + 2) * 3
+    }
+}
+";
+
+fn check_lossless(s: &str) {
+    let (root, _) = Parser::parse_syntax(s);
+    assert_eq!(root.to_string(), s);
+    assert_eq!(root.span(), crate::ast::Span::new(0, s.len()));
+}
+
+#[test]
+fn lossless() {
+    check_lossless(PROGRAM);
+    check_lossless("");
+    check_lossless("  \n// only a comment\n");
+    check_lossless("let x = 1;   // trailing\n\n\n");
+
+    // Whatever the parser gives up on still ends up in the tree.
+    check_lossless("fn f( -> { let = ; } }}} $ 1 + ");
+    check_lossless("let x = (1 + ;\nfn g() -> i32 { 2 }");
+    check_lossless("{ a + }");
+    check_lossless("fn f() -> void { { 1 }");
+    check_lossless("let x: A<i32>> = 1;");
+}
+
+#[test]
+fn lowers_to_the_same_tree() {
+    let (root, errors) = Parser::parse_syntax(PROGRAM);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (tree, _) = Parser::parse(PROGRAM);
+    assert_eq!(format!("{:?}", lower(&root)), format!("{tree:?}"));
+}
+
+#[test]
+fn lowers_errors_to_the_same_tree() {
+    let src = "let a = ;\nfn f() -> void { let b = 1 +* 2; f() }\n} struct S { x: i32 y: i32 }\n";
+    let (root, errors) = Parser::parse_syntax(src);
+    assert_eq!(root.to_string(), src);
+    assert_eq!(errors.len(), 4);
+
+    let (tree, _) = Parser::parse(src);
+    assert_eq!(format!("{:?}", lower(&root)), format!("{tree:?}"));
+}
+
+#[test]
+fn tree_shape() {
+    let (root, _) = Parser::parse_syntax("let x = -a + 1; // hi\n");
+    let expected = r#"Root@0..22
+  Let@0..15
+    Token(Let)@0..3 "let"
+    Whitespace@3..4 " "
+    Token(Identifier)@4..5 "x"
+    Whitespace@5..6 " "
+    Token(Eq)@6..7 "="
+    Whitespace@7..8 " "
+    Binary@8..14
+      Unary@8..10
+        Token(Op { kind: Minus })@8..9 "-"
+        Name@9..10
+          Token(Identifier)@9..10 "a"
+      Whitespace@10..11 " "
+      Token(Op { kind: Plus })@11..12 "+"
+      Whitespace@12..13 " "
+      Literal@13..14
+        Token(Literal { kind: Int })@13..14 "1"
+    Token(Semi)@14..15 ";"
+  Whitespace@15..16 " "
+  Comment@16..21 "// hi"
+  Whitespace@21..22 "\n"
+"#;
+    assert_eq!(format!("{root:?}"), expected);
+}
+
+#[test]
+fn unparsed_rest() {
+    let (root, errors) = Parser::parse_syntax("1; ) 2");
+    assert!(!errors.is_empty());
+
+    let kinds: Vec<SyntaxKind> = root.child_nodes().map(|node| node.kind()).collect();
+    assert_eq!(kinds, [SyntaxKind::ExprStatement, SyntaxKind::Error]);
+}
+
+// Reparsing after an edit has to end up with the same tree and errors as parsing the new text.
+fn check_edit(s: &str, start: usize, end: usize, insert: &str) -> SyntaxNode {
+    let (root, errors) = Parser::parse_syntax(s);
+    let edit = Edit::new(crate::ast::Span::new(start, end), insert);
+    let (reparsed, reparsed_errors) = reparse(&root, &errors, &edit);
+
+    let text = edit.apply(s);
+    let (expected, expected_errors) = Parser::parse_syntax(&text);
+    assert_eq!(format!("{reparsed:?}"), format!("{expected:?}"), "{text:?}");
+    assert_eq!(sorted(&reparsed_errors), sorted(&expected_errors), "{text:?}");
+    reparsed
+}
+
+// Errors in an order that doesn't depend on when the parser ran into them.
+fn sorted(errors: &[ParseError]) -> Vec<String> {
+    let mut errors: Vec<String> = errors.iter().map(|err| format!("{err:?}")).collect();
+    errors.sort();
+    errors
+}
+
+#[test]
+fn reparse_matches_full_parse() {
+    let offset = |needle: &str| PROGRAM.find(needle).unwrap();
+
+    // Within a single token.
+    let add = offset("add(a");
+    check_edit(PROGRAM, add + 1, add + 2, "dd_an");
+    check_edit(PROGRAM, add + 3, add + 3, "2");
+    let comment = offset("the sum");
+    check_edit(PROGRAM, comment, comment + 3, "a");
+    let number = offset("2.5");
+    check_edit(PROGRAM, number, number + 3, "12.75");
+    check_edit(PROGRAM, offset("    let t"), offset("    let t") + 4, "  ");
+
+    // Tokens that turn into something else, or run into their neighbours.
+    let name = offset("add(1, -2)");
+    check_edit(PROGRAM, name, name + 3, "if");
+    check_edit(PROGRAM, name + 3, name + 3, " ");
+    check_edit(PROGRAM, number + 3, number + 5, "");
+    check_edit(PROGRAM, comment, comment, "\n");
+
+    // Literals that stop or start being valid.
+    let huge = "1".repeat(40);
+    let one = offset("1, 'c'");
+    check_edit(PROGRAM, one, one + 1, &huge);
+    check_edit(PROGRAM, one, one + 1, "1x");
+    check_edit(PROGRAM, offset("2.5"), offset("2.5") + 3, "1e999");
+    let invalid = PROGRAM.replacen("(1, 'c'", &format!("({huge}, 'c'"), 1);
+    check_edit(&invalid, one, one + huge.len(), "1");
+    check_edit(&invalid, one, one + huge.len(), "2");
+    check_edit(&PROGRAM.replacen("(1, 'c'", "(1x, 'c'", 1), one, one + 2, "1");
+    check_edit("let x = 1;", 8, 9, &huge);
+    let strings = "fn f() -> void { let s = \"ab\"; let c = 'a'; }";
+    let ab = strings.find("ab").unwrap();
+    let a = strings.find("'a'").unwrap() + 1;
+    check_edit(strings, ab, ab + 2, "\\q");
+    check_edit(strings, ab, ab + 2, "\\u{110000}");
+    check_edit(&strings.replace("ab", "\\q"), ab, ab + 2, "ab");
+    check_edit(strings, a, a + 1, "ab");
+    check_edit(strings, a, a + 1, "\\q");
+    check_edit(&strings.replace("'a'", "'ab'"), a, a + 2, "a");
+
+    // Expressions inside of strings get parsed too.
+    let interpolated = "fn f() -> void { let s = \"hi {z}\"; }";
+    let z = interpolated.find('z').unwrap();
+    check_edit(interpolated, z, z + 1, "1 +");
+    check_edit(interpolated, z - 1, z + 2, "{let }");
+    check_edit(interpolated, z, z + 1, "\"{1 +}\"");
+    check_edit(&interpolated.replace("{z}", "{1 +}"), z, z + 3, "z");
+    check_edit("\"hi {z}\";", 5, 6, "1 +");
+
+    // Names the parser treats differently from the rest.
+    let receiver = offset("self) -> i32;");
+    check_edit(PROGRAM, receiver, receiver + 4, "s");
+    check_edit("fn g(s) -> void {}", 5, 6, "self");
+    check_edit(PROGRAM, offset("T>(x"), offset("T>(x") + 1, "Self");
+
+    // Within a block or an item.
+    let body = offset("a + b");
+    check_edit(PROGRAM, body, body + 5, "let c = a; c - b");
+    check_edit(PROGRAM, offset("y: i32"), offset("y: i32") + 6, "y: bool, z: (i32, char)");
+    check_edit(PROGRAM, offset("Green"), offset("Green"), "Blue ");
+    check_edit(PROGRAM, offset("<T>(x"), offset("<T>(x") + 3, "<T, U>");
+    check_edit(PROGRAM, offset("T>>"), offset("T>>") + 3, "T> >");
+    check_edit(PROGRAM, offset("self: {T}"), offset("self: {T}") + 9, "self");
+    check_edit(PROGRAM, offset("i32; fn"), offset("i32; fn") + 4, "i32 { 1 }");
+    check_edit(PROGRAM, offset("size().twice"), offset("size().twice") + 6, "");
+    check_edit(PROGRAM, offset("= (1,"), offset("= (1,") + 2, "= [1, 2];\n    let u = (");
+
+    // Edits that leave errors behind, or fix them again.
+    check_edit(PROGRAM, body, body + 5, "a +");
+    check_edit(PROGRAM, body, body + 5, "a; b c");
+    check_edit("fn f() -> i32 { 1 + }\nlet x = 2;", 18, 19, "2");
+    check_edit("fn f() -> i32 { a b }", 17, 18, "; ");
+
+    // Edits next to errors that were reported outside of what changed.
+    let missing = "fn main() -> void {\n    let a = 1;\nself   let c = 2;\n}";
+    let c = missing.find("c =").unwrap();
+    check_edit(missing, c, c + 1, "d");
+    check_edit(missing, c + 4, c + 5, "3");
+    check_edit(missing, c, c + 1, "c + 1");
+    let misspelled = "if x { p() } el_se { println(1) };";
+    let p = misspelled.find("println").unwrap();
+    check_edit(misspelled, p, p + 7, "print");
+    check_edit(misspelled, p + 8, p + 9, "2");
+    check_edit(misspelled, p, p, "a; ");
+
+    // Edits that change how the braces line up.
+    check_edit(PROGRAM, body, body, "}");
+    check_edit(PROGRAM, body, body, "{");
+    check_edit(PROGRAM, offset("fn main"), offset("fn main") + 2, "");
+    check_edit(PROGRAM, 0, PROGRAM.len(), "let x = 1;");
+    check_edit(PROGRAM, PROGRAM.len(), PROGRAM.len(), "fn g() -> void {}");
+    check_edit("", 0, 0, "1;");
+}
+
+// Random edits, each one made to what the one before left behind, have to give back what parsing
+// the text from scratch would.
+#[test]
+fn reparse_matches_full_parse_after_random_edits() {
+    const SOURCES: [&str; 4] = [
+        PROGRAM,
+        "fn main() -> void {\n    let a = 1;\nself   let c = 2;\n}\n",
+        "if x { p() } el_se { println(1) };\nfn f(a: i32) i32 { a }\n",
+        "fn g() -> i32 { 1 } 2 + 3\nstruct S { x: i32 }\nlet y = [1, 2];\n",
+    ];
+    const INSERTS: [&str; 20] = [
+        "", "", "x", "c", "2", ";", "let ", "1", " ", "\n", "(", ")", "{", "}", "+", "el_se", "->", ",", "fn ", "a; ",
+    ];
+
+    // A xorshift generator, so that any failure can be reproduced.
+    let mut state: u64 = 0x1234_5678_9abc_def1;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+
+    // A few edits at a time, since once the braces stop lining up everything gets parsed again.
+    for source in SOURCES.iter().cycle().take(SOURCES.len() * 500) {
+        let (mut root, mut errors) = Parser::parse_syntax(source);
+        let mut text = source.to_string();
+        for _ in 0..1 + next(3) {
+            let mut start = next(text.len() + 1);
+            let mut end = (start + next(4)).min(text.len());
+            while !text.is_char_boundary(start) { start -= 1 }
+            while !text.is_char_boundary(end) { end += 1 }
+
+            let edit = Edit::new(crate::ast::Span::new(start, end), INSERTS[next(INSERTS.len())]);
+            let (reparsed, reparsed_errors) = reparse(&root, &errors, &edit);
+            text = edit.apply(&text);
+
+            let (expected, expected_errors) = Parser::parse_syntax(&text);
+            assert_eq!(format!("{reparsed:?}"), format!("{expected:?}"), "{text:?}");
+            assert_eq!(sorted(&reparsed_errors), sorted(&expected_errors), "{text:?}");
+            (root, errors) = (reparsed, reparsed_errors);
+        }
+    }
+}
+
+#[test]
+fn reparse_reuses_untouched_nodes() {
+    let (root, errors) = Parser::parse_syntax(PROGRAM);
+    let check = |needle: &str, insert: &str| {
+        let start = PROGRAM.find(needle).unwrap();
+        let edit = Edit::new(crate::ast::Span::new(start, start + needle.len()), insert);
+        let (reparsed, _) = reparse(&root, &errors, &edit);
+
+        let old: Vec<SyntaxNode> = root.child_nodes().collect();
+        let new: Vec<SyntaxNode> = reparsed.child_nodes().collect();
+        assert_eq!(old.len(), new.len());
+
+        // Only `add` got rebuilt, everything else is the exact same node as before.
+        for (old, new) in old.iter().zip(&new) {
+            let shared = Rc::ptr_eq(old.green(), new.green());
+            assert_eq!(shared, !old.to_string().starts_with("fn add"), "{old}");
+        }
+    };
+
+    // Reparses the block.
+    check("a + b", "b + a");
+    // Only relexes the comment.
+    check("the sum", "the total");
+}
@@ -0,0 +1,1168 @@
+use std::collections::HashMap;
+
+use crate::ast::{ASTree, Statement, Expression, ExprId, Exprs};
+use crate::ast::{FunctionStatement, StructStatement, EnumStatement, LetStatement};
+use crate::ast::{ImplStatement, TraitStatement, TraitMethod, MethodCallExpression};
+use crate::ast::{StructExpression, FieldExpression};
+use crate::prelude::{self, NativeFunction};
+use crate::ast::{BlockExpression, ClosureExpression, CallExpression, IfExpression, ElseExpression};
+use crate::ast::{BinaryExpression, BinaryOperator, UnaryExpression, UnaryOperator};
+use crate::ast::{LiteralExpression, LitKind, StrPart, Tuple, List};
+use crate::ast::{Parameter, Type, IntKind, FloatKind, TupleType};
+use crate::ast::{IdentExpression, Span, Symbol};
+use crate::fix::{self, Fix};
+
+// The types integer and float literals fall back to when nothing else says what they should be.
+pub const DEFAULT_INT: Type = Type::Int { sign: true, kind: IntKind::Bit32 };
+pub const DEFAULT_FLOAT: Type = Type::Float { kind: FloatKind::Bit64 };
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub ty: Type,
+    // Where the name was declared.
+    pub span: Span,
+    // The type parameters that get filled in every time a generic function is called.
+    pub generics: Vec<Symbol>,
+}
+
+// Every place a name gets declared or used, along with what it refers to. Editor tooling uses
+// these to answer "what is this" and "where is this from".
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference {
+    pub name: Symbol,
+    pub span: Span,
+    pub ty: Type,
+    pub definition: Span,
+}
+
+// The methods an `impl` gives `self_type`, including the default methods of its trait.
+#[derive(Clone, Debug)]
+struct Impl {
+    generics: Vec<Symbol>,
+    self_type: Type,
+    methods: HashMap<Symbol, Binding>,
+}
+
+// What a method's type comes from, whether it's declared in an `impl` or a `trait`.
+struct Signature<'a> {
+    generics: &'a [Symbol],
+    arguments: &'a [Parameter],
+    return_type: &'a Type,
+    name_span: Span,
+}
+
+impl<'a> From<&'a FunctionStatement> for Signature<'a> {
+    fn from(method: &'a FunctionStatement) -> Self {
+        let FunctionStatement { generics, arguments, return_type, name_span, .. } = method;
+        Signature { generics, arguments, return_type, name_span: *name_span }
+    }
+}
+
+impl<'a> From<&'a TraitMethod> for Signature<'a> {
+    fn from(method: &'a TraitMethod) -> Self {
+        let TraitMethod { generics, arguments, return_type, name_span, .. } = method;
+        Signature { generics, arguments, return_type, name_span: *name_span }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeChecker {
+    // Variables, innermost scope last. The first scope holds top-level `let`s.
+    scopes: Vec<HashMap<Symbol, Binding>>,
+    functions: HashMap<Symbol, Binding>,
+    pub structs: HashMap<Symbol, StructStatement>,
+    pub enums: HashMap<Symbol, EnumStatement>,
+    pub traits: HashMap<Symbol, TraitStatement>,
+    // Every `impl` that's been declared, in order.
+    impls: Vec<Impl>,
+    // The trait whose default methods are being checked. `self` is only known to implement it.
+    current_trait: Option<Symbol>,
+    // The expressions of whatever is being checked, lent to the checker until it's done.
+    exprs: Exprs,
+
+    record: bool,
+    pub references: Vec<Reference>,
+    // While recording, a statement that doesn't type check doesn't stop the ones after it from
+    // being checked. It's skipped and its error ends up here instead.
+    pub errors: Vec<TypeError>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        let mut checker = Self {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            traits: HashMap::new(),
+            impls: Vec::new(),
+            current_trait: None,
+            exprs: Exprs::new(),
+            record: false,
+            references: Vec::new(),
+            errors: Vec::new(),
+        };
+        for function in prelude::functions() {
+            checker.declare_native(&function);
+        }
+        checker
+    }
+
+    // Native functions aren't declared anywhere, so they don't have a span to point to.
+    pub fn declare_native(&mut self, function: &NativeFunction) {
+        let binding = Binding { ty: function.ty.clone(), span: Span::default(), generics: function.generics.clone() };
+        self.functions.insert(function.name, binding);
+    }
+
+    // A top-level variable given to the program from outside of it, like a native function.
+    pub fn declare_global(&mut self, name: &str, ty: Type) {
+        self.scopes[0].insert(Symbol::intern(name), Binding { ty, span: Span::default(), generics: Vec::new() });
+    }
+
+    // A function declared at the top level, or a native one.
+    pub fn function(&self, name: &str) -> Option<&Binding> {
+        self.functions.get(&Symbol::intern(name))
+    }
+
+    // A type checker that also fills in `references` as it goes, and keeps going past errors.
+    pub fn recording() -> Self {
+        Self { record: true, ..Self::new() }
+    }
+
+    // Checks a whole program. Declarations stay registered afterwards so that later calls can
+    // refer to them, which is what the REPL relies on.
+    pub fn check_tree(&mut self, tree: &mut ASTree) -> TypeResult<Vec<Type>> {
+        self.with_exprs(&mut tree.exprs, |checker| checker.check_statements(&mut tree.root))
+    }
+
+    // Returns the type of `expr`, one of `exprs`. See `expr_type()` for what `expected` does.
+    pub fn check_expr(&mut self, exprs: &mut Exprs, expr: ExprId, expected: Option<&Type>) -> TypeResult<Type> {
+        self.with_exprs(exprs, |checker| checker.expr_type(expr, expected))
+    }
+
+    // Lends `exprs` to the checker while `f` runs, which is where the types it fills in end up.
+    fn with_exprs<T>(&mut self, exprs: &mut Exprs, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.exprs = std::mem::take(exprs);
+        let result = f(self);
+        *exprs = std::mem::take(&mut self.exprs);
+        result
+    }
+
+    // Returns the type of every statement, `void` for declarations.
+    fn check_statements(&mut self, statements: &mut [Statement]) -> TypeResult<Vec<Type>> {
+        self.declare_items(statements)?;
+        statements
+            .iter_mut()
+            .map(|statement| {
+                let result = self.check_statement(statement);
+                self.recover(result, Type::Void)
+            })
+            .collect()
+    }
+
+    // Outside of recording, an error stops everything. While recording it's kept, and checking
+    // goes on as if `fallback` had been the result.
+    fn recover<T>(&mut self, result: TypeResult<T>, fallback: T) -> TypeResult<T> {
+        match result {
+            Err(err) if self.record => {
+                self.errors.push(err);
+                Ok(fallback)
+            }
+            result => result,
+        }
+    }
+
+    // Functions, structs and enums can be used before the point they are declared in, so their
+    // signatures get registered before anything else is checked.
+    fn declare_items(&mut self, statements: &[Statement]) -> TypeResult<()> {
+        for statement in statements {
+            match statement {
+                Statement::Struct(item) => {
+                    self.structs.insert(item.name, item.clone());
+                }
+                // Variants are values of their enum, like `Color::Red`, which is declared as if it
+                // were a top-level `let`. A generic enum's variants are generic values, the same as
+                // a generic function used as a value.
+                Statement::Enum(item) => {
+                    self.enums.insert(item.name, item.clone());
+                    let ty = declared_type(item.name, &item.generics);
+                    for (variant, span) in item.variants.iter().zip(&item.variant_spans) {
+                        let name = Symbol::intern(&format!("{}::{variant}", item.name));
+                        self.record(name, *span, &ty, *span);
+                        let binding = Binding { ty: ty.clone(), span: *span, generics: item.generics.clone() };
+                        self.scopes[0].insert(name, binding);
+                    }
+                }
+                Statement::Trait(item) => {
+                    self.traits.insert(item.name, item.clone());
+                }
+                _ => (),
+            }
+        }
+
+        for statement in statements {
+            if let Statement::Function(function) = statement {
+                let fn_type = function_type(&function.arguments, &function.return_type);
+                let valid = self.validate_type(&fn_type).map_err(|err| err.or_span(function.span));
+                self.recover(valid, ())?;
+                self.record(function.name, function.name_span, &fn_type, function.name_span);
+
+                let generics = function.generics.clone();
+                let binding = Binding { ty: fn_type, span: function.name_span, generics };
+                self.functions.insert(function.name, binding);
+            }
+        }
+
+        for statement in statements {
+            if let Statement::Impl(item) = statement {
+                let declared = self.declare_impl(item).map_err(|err| err.or_span(item.span));
+                self.recover(declared, ())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn declare_impl(&mut self, item: &ImplStatement) -> TypeResult<()> {
+        self.validate_type(&item.self_type)?;
+        if let Type::Generic { .. } = item.self_type {
+            return Err(TypeErrorKind::Unsupported { what: "an `impl` for a type parameter" }.into());
+        }
+
+        let mut methods = HashMap::new();
+        for method in &item.methods {
+            let binding = self.method_binding(&item.generics, method.into())
+                .map_err(|err| err.or_span(method.span))?;
+            self.record(method.name, method.name_span, &binding.ty, method.name_span);
+            methods.insert(method.name, binding);
+        }
+
+        if let Some(trait_name) = &item.trait_name {
+            self.implement_trait(*trait_name, item, &mut methods)?;
+        }
+
+        // Two methods with the same name for types that can turn out to be the same would leave it
+        // up to the order of the `impl`s which one gets called.
+        for other in &self.impls {
+            let overlaps = unify(&other.self_type, &item.self_type, &other.generics, &mut HashMap::new())
+                || unify(&item.self_type, &other.self_type, &item.generics, &mut HashMap::new());
+            if !overlaps { continue }
+            if let Some(name) = methods.keys().find(|name| other.methods.contains_key(*name)) {
+                let ty = item.self_type.clone();
+                return Err(TypeErrorKind::DuplicateMethod { ty, name: *name }.into());
+            }
+        }
+
+        let self_type = item.self_type.clone();
+        self.impls.push(Impl { generics: item.generics.clone(), self_type, methods });
+        Ok(())
+    }
+
+    // Checks `methods` against what the trait requires, and adds its default methods to them.
+    fn implement_trait(&self, trait_name: Symbol, item: &ImplStatement, methods: &mut HashMap<Symbol, Binding>) -> TypeResult<()> {
+        let Some(trait_item) = self.traits.get(&trait_name) else {
+            return Err(TypeErrorKind::UnknownTrait { name: trait_name }.into());
+        };
+        let instances = HashMap::from([(Symbol::intern("Self"), item.self_type.clone())]);
+
+        for method in &trait_item.methods {
+            let mut required = self.method_binding(&item.generics, method.into())?;
+            required.ty = substitute(&required.ty, &instances);
+
+            match methods.get(&method.name) {
+                Some(found) => {
+                    expect_type(&required.ty, found.ty.clone()).map_err(|err| err.or_span(found.span))?;
+                }
+                None if method.block.is_some() => {
+                    methods.insert(method.name, required);
+                }
+                None => {
+                    let name = method.name;
+                    return Err(TypeErrorKind::MissingMethod { trait_name, name }.into());
+                }
+            }
+        }
+
+        for method in &item.methods {
+            if !trait_item.methods.iter().any(|required| required.name == method.name) {
+                let name = method.name;
+                let err = TypeErrorKind::NotATraitMethod { trait_name, name };
+                return Err(TypeError::from(err).or_span(method.span));
+            }
+        }
+        Ok(())
+    }
+
+    // The type of a method in an `impl` with `generics`, which take `self` as their first argument.
+    fn method_binding(&self, generics: &[Symbol], method: Signature) -> TypeResult<Binding> {
+        if method.arguments.first().is_none_or(|argument| argument.name != "self") {
+            return Err(TypeErrorKind::Unsupported { what: "a method without `self`" }.into());
+        }
+
+        let ty = function_type(method.arguments, method.return_type);
+        self.validate_type(&ty)?;
+        let generics = [generics, method.generics].concat();
+        Ok(Binding { ty, span: method.name_span, generics })
+    }
+
+    fn check_statement(&mut self, statement: &mut Statement) -> TypeResult<Type> {
+        let span = statement.span();
+        self.check_statement_inner(statement).map_err(|err| err.or_span(span))
+    }
+
+    fn check_statement_inner(&mut self, statement: &mut Statement) -> TypeResult<Type> {
+        match statement {
+            Statement::Function(function) => {
+                self.check_function(&function.arguments, &mut function.block, &function.return_type)?;
+                Ok(Type::Void)
+            }
+
+            Statement::Struct(StructStatement { fields, .. }) => {
+                for field in fields {
+                    self.validate_type(&field.param_type)?;
+                }
+                Ok(Type::Void)
+            }
+
+            // Imports are resolved before type checking, see `module::ModuleGraph::link()`.
+            Statement::Enum(_) | Statement::Import(_) | Statement::Error { .. } | Statement::EOF => Ok(Type::Void),
+
+            Statement::Impl(item) => {
+                for method in &mut item.methods {
+                    self.check_function(&method.arguments, &mut method.block, &method.return_type)
+                        .map_err(|err| err.or_span(method.span))?;
+                }
+                Ok(Type::Void)
+            }
+
+            Statement::Trait(item) => {
+                let outer = self.current_trait.replace(item.name);
+                let result = self.check_trait(item);
+                self.current_trait = outer;
+                result?;
+                Ok(Type::Void)
+            }
+
+            Statement::Let(LetStatement { name, var_type, value, name_span, .. }) => {
+                if let Some(var_type) = var_type {
+                    self.validate_type(var_type)?;
+                }
+                let value_type = self.expr_type(*value, var_type.as_ref())?;
+                self.declare_variable(*name, value_type, *name_span);
+                Ok(Type::Void)
+            }
+
+            Statement::Expression { expr, .. } => self.expr_type(*expr, None),
+        }
+    }
+
+    fn check_function(&mut self, arguments: &[Parameter], block: &mut BlockExpression, return_type: &Type) -> TypeResult<()> {
+        // Functions don't capture the locals around them, only the top-level scope.
+        let outer = self.scopes.split_off(1);
+        let result = self.check_body(arguments, block, return_type);
+        self.scopes.truncate(1);
+        self.scopes.extend(outer);
+        result
+    }
+
+    fn check_trait(&mut self, item: &mut TraitStatement) -> TypeResult<()> {
+        for method in &mut item.methods {
+            let result = match &mut method.block {
+                Some(block) => self.check_function(&method.arguments, block, &method.return_type),
+                None => self.method_binding(&[], (&*method).into()).map(|_| ()),
+            };
+            result.map_err(|err| err.or_span(method.span))?;
+        }
+        Ok(())
+    }
+
+    // Checks a function or closure body against its declared return type.
+    fn check_body(&mut self, arguments: &[Parameter], block: &mut BlockExpression, return_type: &Type) -> TypeResult<()> {
+        let mut scope = HashMap::new();
+        for argument in arguments {
+            self.validate_type(&argument.param_type)?;
+            self.record(argument.name, argument.name_span, &argument.param_type, argument.name_span);
+
+            let binding = Binding { ty: argument.param_type.clone(), span: argument.name_span, generics: Vec::new() };
+            scope.insert(argument.name, binding);
+        }
+        self.validate_type(return_type)?;
+
+        self.scopes.push(scope);
+        let result = self.check_block(block, Some(return_type));
+        self.scopes.pop();
+
+        expect_type(return_type, result?)?;
+        Ok(())
+    }
+
+    // Returns the type of `expr`. If `expected` is given, it's used to decide what type untyped
+    // literals should be, but it's up to the caller to check that the result actually matches.
+    // The expression is taken out of `exprs` while it's checked, the ones inside of it aren't.
+    fn expr_type(&mut self, expr: ExprId, expected: Option<&Type>) -> TypeResult<Type> {
+        let mut node = self.exprs.take(expr);
+        let span = node.span();
+        let result = self.check_expr_inner(&mut node, expected).map_err(|err| err.or_span(span));
+        self.exprs.put(expr, node);
+        result
+    }
+
+    fn check_expr_inner(&mut self, expr: &mut Expression, expected: Option<&Type>) -> TypeResult<Type> {
+        let found = match expr {
+            Expression::Literal(literal) => self.check_literal(literal, expected, false)?,
+
+            Expression::Identifier(ident) => self.check_value(ident, expected)?,
+
+            Expression::Block(block) => self.check_block(block, expected)?,
+
+            Expression::If(if_expr) => self.check_if(if_expr, expected)?,
+
+            Expression::Closure(closure) => {
+                let ClosureExpression { arguments, block, return_type, .. } = closure;
+                self.check_body(arguments, block, return_type)?;
+                function_type(arguments, return_type)
+            }
+
+            Expression::Call(call) => self.check_call(call, expected)?,
+
+            Expression::MethodCall(call) => self.check_method_call(call, expected)?,
+
+            Expression::Struct(struct_expr) => self.check_struct(struct_expr, expected)?,
+
+            Expression::Field(field) => self.check_field(field)?,
+
+            Expression::Unary(unary) => self.check_unary(unary, expected)?,
+
+            Expression::Binary(binary) => self.check_binary(binary, expected)?,
+        };
+
+        match expected {
+            Some(expected) => expect_type(expected, found),
+            None => Ok(found),
+        }
+    }
+
+    // `negated` is for literals right after a `-`, which can go one further than the largest value
+    // of a signed type.
+    fn check_literal(&mut self, literal: &mut LiteralExpression, expected: Option<&Type>, negated: bool) -> TypeResult<Type> {
+        let found = match &mut literal.kind {
+            LitKind::Bool(_) => Type::Bool,
+            LitKind::Str(_) => Type::Str,
+            LitKind::Interpolated(parts) => {
+                // Anything can be embedded in a string.
+                for part in parts {
+                    if let StrPart::Expr(expr) = part {
+                        self.expr_type(*expr, None)?;
+                    }
+                }
+                Type::Str
+            }
+            LitKind::Char(_) => Type::Char,
+
+            LitKind::Int { value, ty } => {
+                let ty = match (ty, expected) {
+                    (Some(ty), _) => ty.clone(),
+                    (None, Some(ty @ Type::Int { .. })) => ty.clone(),
+                    (None, _) => DEFAULT_INT,
+                };
+                if let Type::Int { sign, kind } = ty {
+                    if *value > int_max(sign, kind, negated) {
+                        let literal = format!("{}{value}", if negated { "-" } else { "" });
+                        return Err(TypeErrorKind::LiteralOutOfRange { literal, ty }.into());
+                    }
+                }
+                ty
+            }
+
+            LitKind::Float { value, ty } => {
+                let ty = match (ty, expected) {
+                    (Some(ty), _) => ty.clone(),
+                    (None, Some(ty @ Type::Float { .. })) => ty.clone(),
+                    (None, _) => DEFAULT_FLOAT,
+                };
+                // Values are all stored as `f64`, so an `f32` one is rounded to what it can hold
+                // here, the same way operations on it are when it's run.
+                if let Type::Float { kind } = ty {
+                    *value = prelude::narrow(kind, *value);
+                }
+                ty
+            }
+
+            LitKind::Tuple(Tuple(expressions)) => {
+                let expected = match expected {
+                    Some(Type::Tuple(TupleType(types))) if types.len() == expressions.len() => Some(types),
+                    _ => None,
+                };
+
+                let mut types = Vec::new();
+                for (i, &expr) in expressions.iter().enumerate() {
+                    let expected = expected.map(|types| &types[i]);
+                    types.push(self.expr_type(expr, expected)?);
+                }
+                Type::Tuple(TupleType(types))
+            }
+
+            LitKind::List(List(expressions)) => {
+                let mut element = match expected {
+                    Some(Type::List(element)) => Some(element.as_ref().clone()),
+                    _ => None,
+                };
+
+                for &expr in expressions.iter() {
+                    let found = self.expr_type(expr, element.as_ref())?;
+                    element.get_or_insert(found);
+                }
+
+                match element {
+                    Some(element) => Type::List(Box::new(element)),
+                    None => return Err(TypeErrorKind::CannotInfer.into()),
+                }
+            }
+        };
+        Ok(found)
+    }
+
+    fn check_block(&mut self, block: &mut BlockExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        self.scopes.push(HashMap::new());
+        let result = self.check_block_inner(block, expected);
+        self.scopes.pop();
+        result
+    }
+
+    fn check_block_inner(&mut self, block: &mut BlockExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        // Functions are all declared at the top level. One in a block would have to stay inside of it,
+        // which the interpreter has no way of doing yet.
+        if let Some(function) = block.statements.iter().find(|statement| matches!(statement, Statement::Function(_))) {
+            let err = TypeErrorKind::Unsupported { what: "a function inside a block" };
+            return Err(TypeError::from(err).or_span(function.span()));
+        }
+        self.check_statements(&mut block.statements)?;
+        match block.expression {
+            Some(expr) => self.expr_type(expr, expected),
+            None => Ok(Type::Void),
+        }
+    }
+
+    fn check_if(&mut self, if_expr: &mut IfExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        self.expr_type(if_expr.condition, Some(&Type::Bool))?;
+
+        let else_body = match &mut if_expr.else_body {
+            Some(else_body) => else_body,
+            None => {
+                self.check_block(&mut if_expr.body, None)?;
+                return Ok(Type::Void);
+            }
+        };
+
+        let body_type = self.check_block(&mut if_expr.body, expected)?;
+        let else_type = match else_body {
+            ElseExpression::Else(block) => self.check_block(block, Some(&body_type))?,
+            ElseExpression::ElseIf(else_if) => self.expr_type(*else_if, Some(&body_type))?,
+        };
+        expect_type(&body_type, else_type)
+    }
+
+    fn check_call(&mut self, call: &mut CallExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        let (arguments, return_type, generics) = match self.check_callee(call.function)? {
+            (Type::Fn { arguments, return_type }, generics) => (arguments, return_type, generics),
+            (found, _) => return Err(TypeErrorKind::NotCallable { found }.into()),
+        };
+        self.check_arguments(&arguments, &return_type, &generics, None, &call.arguments, expected)
+    }
+
+    fn check_method_call(&mut self, call: &mut MethodCallExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        let receiver = self.expr_type(call.receiver, None)?;
+        let Some((self_type, method)) = self.find_method(&receiver, call.method) else {
+            let fix = self.suggest(call.method, call.method_span, self.method_names(&receiver));
+            let err = TypeErrorKind::UnknownMethod { ty: receiver, name: call.method };
+            return Err(TypeError::from(err).or_span(call.method_span).with_fix(fix));
+        };
+        self.record(call.method, call.method_span, &method.ty, method.span);
+        call.self_type = Some(self_type);
+
+        let Type::Fn { arguments, return_type } = method.ty else { unreachable!("methods are functions") };
+        self.check_arguments(&arguments, &return_type, &method.generics, Some(receiver), &call.arguments, expected)
+    }
+
+    // Checked like a call to a function taking the struct's fields in the order they're declared
+    // in, so that the type parameters of a generic struct get worked out the same way.
+    fn check_struct(&mut self, struct_expr: &mut StructExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        let Some(item) = self.structs.get(&struct_expr.name).cloned() else {
+            let err = TypeErrorKind::UnknownType { name: struct_expr.name };
+            return Err(TypeError::from(err).or_span(struct_expr.name_span));
+        };
+        let ty = declared_type(item.name, &item.generics);
+        self.record(item.name, struct_expr.name_span, &ty, item.name_span);
+
+        let fields = &struct_expr.fields;
+        for (i, field) in fields.iter().enumerate() {
+            let name = field.name;
+            let err = if !item.fields.iter().any(|declared| declared.name == name) {
+                TypeErrorKind::UnknownField { ty: ty.clone(), name }
+            } else if fields[..i].iter().any(|other| other.name == name) {
+                TypeErrorKind::DuplicateField { name }
+            } else {
+                continue
+            };
+            return Err(TypeError::from(err).or_span(field.name_span));
+        }
+        if let Some(missing) = item.fields.iter().find(|declared| fields.iter().all(|field| field.name != declared.name)) {
+            return Err(TypeErrorKind::MissingField { ty, name: missing.name }.into());
+        }
+
+        let position = |name: Symbol| item.fields.iter().position(|declared| declared.name == name);
+        let mut values: Vec<_> = struct_expr.fields.iter().map(|field| (position(field.name), field.value)).collect();
+        values.sort_by_key(|(position, _)| *position);
+        let values: Vec<_> = values.into_iter().map(|(_, value)| value).collect();
+
+        let params: Vec<Type> = item.fields.iter().map(|field| field.param_type.clone()).collect();
+        self.check_arguments(&params, &ty, &item.generics, None, &values, expected)
+    }
+
+    fn check_field(&mut self, field: &mut FieldExpression) -> TypeResult<Type> {
+        let receiver = self.expr_type(field.receiver, None)?;
+        let (name, arguments) = match &receiver {
+            Type::UserDefined { name } => (name, &[][..]),
+            Type::Applied { name, arguments } => (name, &arguments[..]),
+            _ => (&field.field, &[][..]),
+        };
+        let item = self.structs.get(name);
+        let Some(declared) = item.and_then(|item| item.fields.iter().find(|declared| declared.name == field.field)) else {
+            let names = item.map(|item| item.fields.iter().map(|declared| declared.name).collect());
+            let fix = self.suggest(field.field, field.field_span, names.unwrap_or_default());
+            let err = TypeErrorKind::UnknownField { ty: receiver.clone(), name: field.field };
+            return Err(TypeError::from(err).or_span(field.field_span).with_fix(fix));
+        };
+
+        let generics = item.map_or(&[][..], |item| &item.generics[..]);
+        let instances = generics.iter().copied().zip(arguments.iter().cloned()).collect();
+        let ty = substitute(&declared.param_type, &instances);
+        self.record(field.field, field.field_span, &ty, declared.name_span);
+        Ok(ty)
+    }
+
+    // The method `name` that `receiver` has, from the first `impl` that fits, along with the type
+    // that `impl` is for.
+    fn find_method(&self, receiver: &Type, name: Symbol) -> Option<(Type, Binding)> {
+        // In a trait's default methods, `self` only has what the trait says it has. Which `impl`
+        // it comes from is only known once the method is given to one.
+        if let (Type::Generic { name: generic }, Some(trait_name)) = (receiver, &self.current_trait) {
+            if generic != "Self" { return None }
+            let method = self.traits.get(trait_name)?.methods.iter().find(|method| method.name == name)?;
+            return Some((receiver.clone(), self.method_binding(&[], method.into()).ok()?));
+        }
+
+        self.impls.iter().find_map(|item| {
+            let method = item.methods.get(&name)?;
+            let fits = unify(&item.self_type, receiver, &item.generics, &mut HashMap::new());
+            fits.then(|| (item.self_type.clone(), method.clone()))
+        })
+    }
+
+    // The names of every method `receiver` has from an `impl`.
+    fn method_names(&self, receiver: &Type) -> Vec<Symbol> {
+        self.impls
+            .iter()
+            .filter(|item| unify(&item.self_type, receiver, &item.generics, &mut HashMap::new()))
+            .flat_map(|item| item.methods.keys().copied())
+            .collect()
+    }
+
+    // A fix replacing `name` at `span` with whichever of `candidates` it's most likely a typo of.
+    fn suggest(&self, name: Symbol, span: Span, candidates: Vec<Symbol>) -> Option<Fix> {
+        let candidates: Vec<_> = candidates.into_iter().map(Symbol::as_str).collect();
+        let closest = fix::closest(&name.as_str(), candidates.iter().map(|candidate| &**candidate))?;
+        Some(Fix::replace(format!("did you mean `{closest}`?"), span, closest))
+    }
+
+    // Checks the arguments of a call to a function taking `params` and returns what the call
+    // evaluates to. A method's `receiver` is its first argument.
+    fn check_arguments(
+        &mut self,
+        params: &[Type],
+        return_type: &Type,
+        generics: &[Symbol],
+        receiver: Option<Type>,
+        arguments: &[ExprId],
+        expected: Option<&Type>,
+    ) -> TypeResult<Type> {
+        let mut instances = HashMap::new();
+        let params = match (receiver, params) {
+            (Some(receiver), [self_type, params @ ..]) => {
+                unify(self_type, &receiver, generics, &mut instances);
+                params
+            }
+            _ => params,
+        };
+
+        if params.len() != arguments.len() {
+            return Err(TypeErrorKind::ArgumentCount {
+                expected: params.len(),
+                found: arguments.len(),
+            }.into());
+        }
+
+        // The type parameters are worked out from where the call is first, then from the arguments
+        // left to right. Once they're known they're used the same way any other expected type is.
+        if let Some(expected) = expected {
+            let mut with_expected = instances.clone();
+            if unify(return_type, expected, generics, &mut with_expected) {
+                instances = with_expected;
+            }
+        }
+
+        for (param, &argument) in params.iter().zip(arguments) {
+            let param = substitute(param, &instances);
+            if !mentions(&param, generics) {
+                self.expr_type(argument, Some(&param))?;
+                continue;
+            }
+
+            let found = self.expr_type(argument, None)?;
+            if !unify(&param, &found, generics, &mut instances) {
+                let err = TypeErrorKind::Mismatch { expected: substitute(&param, &instances), found };
+                return Err(TypeError::from(err).or_span(self.exprs[argument].span()));
+            }
+        }
+
+        let mut unknown = generics.iter().filter(|name| !instances.contains_key(*name));
+        if let Some(name) = unknown.find(|name| mentions(return_type, std::slice::from_ref(*name))) {
+            return Err(TypeErrorKind::CannotInferGeneric { name: *name }.into());
+        }
+        Ok(substitute(return_type, &instances))
+    }
+
+    // The type of the function that gets called, along with its type parameters if it's a generic
+    // function called by name. Those get filled in from the call's arguments.
+    fn check_callee(&mut self, function: ExprId) -> TypeResult<(Type, Vec<Symbol>)> {
+        match &self.exprs[function] {
+            Expression::Identifier(ident) => {
+                let binding = self.lookup(&ident.clone())?;
+                Ok((binding.ty, binding.generics))
+            }
+            _ => Ok((self.expr_type(function, None)?, Vec::new())),
+        }
+    }
+
+    // A name used as a value rather than called. A generic function used that way has nothing to
+    // fill in its type parameters but the type it's expected to have.
+    fn check_value(&mut self, ident: &IdentExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        let Binding { ty, generics, .. } = self.lookup(ident)?;
+        let mut instances = HashMap::new();
+        if let Some(expected) = expected {
+            unify(&ty, expected, &generics, &mut instances);
+        }
+
+        match generics.iter().find(|name| !instances.contains_key(*name)) {
+            Some(generic) => Err(TypeErrorKind::GenericValue { name: ident.name, generic: *generic }.into()),
+            None => Ok(substitute(&ty, &instances)),
+        }
+    }
+
+    fn check_unary(&mut self, unary: &mut UnaryExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        let operand = match (unary.op, &self.exprs[unary.rhs]) {
+            (UnaryOperator::Minus, Expression::Literal(_)) => {
+                let Expression::Literal(mut literal) = self.exprs.take(unary.rhs) else { unreachable!() };
+                let result = self.check_literal(&mut literal, expected, true).map_err(|err| err.or_span(literal.span));
+                self.exprs.put(unary.rhs, Expression::Literal(literal));
+                result?
+            }
+            _ => self.expr_type(unary.rhs, expected)?,
+        };
+        let valid = match unary.op {
+            UnaryOperator::BoolNot => operand == Type::Bool,
+            UnaryOperator::BitNot => is_int(&operand),
+            UnaryOperator::Plus => is_number(&operand),
+            // Nothing but zero can be negated and still be unsigned.
+            UnaryOperator::Minus => is_number(&operand) && !matches!(operand, Type::Int { sign: false, .. }),
+        };
+
+        match valid {
+            true => {
+                unary.ty = Some(operand.clone());
+                Ok(operand)
+            }
+            false => Err(TypeErrorKind::UnaryOperand { op: unary.op, found: operand }.into()),
+        }
+    }
+
+    fn check_binary(&mut self, binary: &mut BinaryExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        use BinaryOperator as Op;
+
+        let op = binary.op;
+        if op == Op::Pipe {
+            return self.check_pipe(binary, expected);
+        }
+
+        // The left-hand side decides what the right-hand side should be, unless it's an untyped
+        // literal and the right-hand side isn't.
+        let operand_hint = match op {
+            Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod
+            | Op::BitOr | Op::BitAnd | Op::BitXor | Op::BitRight | Op::BitLeft => expected,
+            Op::BoolOr | Op::BoolAnd => Some(&Type::Bool),
+            Op::Eq | Op::Ne | Op::Ge | Op::Le | Op::Gt | Op::Lt | Op::Pipe => None,
+        };
+
+        let untyped = |expr| is_untyped_literal(&self.exprs, expr);
+        let (lhs, rhs) = match (untyped(binary.lhs), untyped(binary.rhs)) {
+            (true, false) => {
+                let rhs = self.expr_type(binary.rhs, operand_hint)?;
+                let lhs = self.expr_type(binary.lhs, operand_hint.or(Some(&rhs)))?;
+                (lhs, rhs)
+            }
+            _ => {
+                let lhs = self.expr_type(binary.lhs, operand_hint)?;
+                let rhs_hint = match op {
+                    // Shift amounts don't need to be the same type as what's being shifted.
+                    Op::BitRight | Op::BitLeft => None,
+                    _ => Some(&lhs),
+                };
+                let rhs = self.expr_type(binary.rhs, rhs_hint)?;
+                (lhs, rhs)
+            }
+        };
+
+        binary.ty = Some(lhs.clone());
+        let invalid = || TypeError::from(TypeErrorKind::BinaryOperands { op, lhs: lhs.clone(), rhs: rhs.clone() });
+
+        match op {
+            Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod => {
+                if !is_number(&lhs) || lhs != rhs { return Err(invalid()); }
+                Ok(lhs)
+            }
+
+            Op::BitOr | Op::BitAnd | Op::BitXor => {
+                if !is_int(&lhs) || lhs != rhs { return Err(invalid()); }
+                Ok(lhs)
+            }
+
+            Op::BitRight | Op::BitLeft => {
+                if !is_int(&lhs) || !is_int(&rhs) { return Err(invalid()); }
+                Ok(lhs)
+            }
+
+            Op::BoolOr | Op::BoolAnd => Ok(Type::Bool),
+
+            Op::Eq | Op::Ne => {
+                if lhs != rhs { return Err(invalid()); }
+                Ok(Type::Bool)
+            }
+
+            Op::Ge | Op::Le | Op::Gt | Op::Lt => {
+                let ordered = is_number(&lhs) || matches!(lhs, Type::Char | Type::Str);
+                if !ordered || lhs != rhs { return Err(invalid()); }
+                Ok(Type::Bool)
+            }
+
+            Op::Pipe => unreachable!("pipes are checked as calls"),
+        }
+    }
+
+    // Checked as the call it stands for, except that the right-hand side gets its own error when
+    // it isn't a function. The call isn't built, so that the types get filled in where they are.
+    fn check_pipe(&mut self, binary: &mut BinaryExpression, expected: Option<&Type>) -> TypeResult<Type> {
+        let rhs_span = self.exprs[binary.rhs].span();
+        let (function, rest) = match &self.exprs[binary.rhs] {
+            Expression::Call(call) => (call.function, &call.arguments[..]),
+            _ => (binary.rhs, &[][..]),
+        };
+        let call_arguments: Vec<_> = std::iter::once(binary.lhs).chain(rest.iter().copied()).collect();
+        let (arguments, return_type, generics) = match self.check_callee(function)? {
+            (Type::Fn { arguments, return_type }, generics) => (arguments, return_type, generics),
+            (found, _) => return Err(TypeError::from(TypeErrorKind::NotPipeable { found }).or_span(rhs_span)),
+        };
+        self.check_arguments(&arguments, &return_type, &generics, None, &call_arguments, expected)
+    }
+
+    fn lookup(&mut self, ident: &IdentExpression) -> TypeResult<Binding> {
+        let name = ident.name;
+        let Some(binding) = self.find(name).cloned() else {
+            let names = self.scopes.iter().flat_map(|scope| scope.keys()).chain(self.functions.keys());
+            let fix = self.suggest(name, ident.span, names.copied().collect());
+            let err = TypeErrorKind::UnknownIdentifier { name };
+            return Err(TypeError::from(err).or_span(ident.span).with_fix(fix));
+        };
+
+        self.record(name, ident.span, &binding.ty, binding.span);
+        Ok(binding)
+    }
+
+    fn find(&self, name: Symbol) -> Option<&Binding> {
+        let variable = self.scopes.iter().rev().find_map(|scope| scope.get(&name));
+        variable.or_else(|| self.functions.get(&name))
+    }
+
+    fn declare_variable(&mut self, name: Symbol, var_type: Type, span: Span) {
+        self.record(name, span, &var_type, span);
+        let binding = Binding { ty: var_type, span, generics: Vec::new() };
+        match self.scopes.last_mut() {
+            Some(scope) => { scope.insert(name, binding); }
+            None => unreachable!("there should always be a top-level scope"),
+        }
+    }
+
+    fn record(&mut self, name: Symbol, span: Span, ty: &Type, definition: Span) {
+        if self.record {
+            let reference = Reference { name, span, ty: ty.clone(), definition };
+            self.references.push(reference);
+        }
+    }
+
+    // Makes sure every user defined type in `ty` has actually been declared, with as many type
+    // arguments as it has type parameters.
+    fn validate_type(&self, ty: &Type) -> TypeResult<()> {
+        match ty {
+            Type::UserDefined { name } => self.validate_type_arguments(*name, &[]),
+            Type::Applied { name, arguments } => {
+                self.validate_type_arguments(*name, arguments)?;
+                arguments.iter().try_for_each(|ty| self.validate_type(ty))
+            }
+            Type::Tuple(TupleType(types)) => types.iter().try_for_each(|ty| self.validate_type(ty)),
+            Type::List(element) => self.validate_type(element),
+            Type::Fn { arguments, return_type } => {
+                arguments.iter().try_for_each(|ty| self.validate_type(ty))?;
+                self.validate_type(return_type)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_type_arguments(&self, name: Symbol, arguments: &[Type]) -> TypeResult<()> {
+        let structs = self.structs.get(&name).map(|item| &item.generics);
+        let Some(generics) = structs.or_else(|| self.enums.get(&name).map(|item| &item.generics)) else {
+            return Err(TypeErrorKind::UnknownType { name }.into());
+        };
+
+        if generics.len() != arguments.len() {
+            return Err(TypeErrorKind::TypeArgumentCount {
+                name,
+                expected: generics.len(),
+                found: arguments.len(),
+            }.into());
+        }
+        Ok(())
+    }
+}
+
+// The type a struct or enum declared with `generics` has inside of its own declaration.
+fn declared_type(name: Symbol, generics: &[Symbol]) -> Type {
+    match generics.is_empty() {
+        true => Type::UserDefined { name },
+        false => {
+            let arguments = generics.iter().map(|&name| Type::Generic { name }).collect();
+            Type::Applied { name, arguments }
+        }
+    }
+}
+
+pub fn function_type(arguments: &[Parameter], return_type: &Type) -> Type {
+    Type::Fn {
+        arguments: arguments.iter().map(|argument| argument.param_type.clone()).collect(),
+        return_type: Box::new(return_type.clone()),
+    }
+}
+
+// Fills in the `generics` in `param` with whatever is in the same place in `found`. Returns false if
+// the two can't be made the same type.
+fn unify(param: &Type, found: &Type, generics: &[Symbol], instances: &mut HashMap<Symbol, Type>) -> bool {
+    let all = |params: &[Type], found: &[Type], instances: &mut HashMap<Symbol, Type>| {
+        params.len() == found.len()
+            && params.iter().zip(found).all(|(param, found)| unify(param, found, generics, instances))
+    };
+
+    match (param, found) {
+        (Type::Generic { name }, _) if generics.contains(name) => match instances.get(name) {
+            Some(instance) => instance == found,
+            None => {
+                instances.insert(*name, found.clone());
+                true
+            }
+        },
+        (Type::Tuple(TupleType(params)), Type::Tuple(TupleType(found))) => all(params, found, instances),
+        (Type::List(param), Type::List(found)) => unify(param, found, generics, instances),
+        (Type::Fn { arguments, return_type }, Type::Fn { arguments: found, return_type: found_return }) => {
+            all(arguments, found, instances) && unify(return_type, found_return, generics, instances)
+        }
+        (Type::Applied { name, arguments }, Type::Applied { name: found_name, arguments: found }) => {
+            name == found_name && all(arguments, found, instances)
+        }
+        _ => param == found,
+    }
+}
+
+// `ty` with every type parameter that has an instance swapped out for it.
+fn substitute(ty: &Type, instances: &HashMap<Symbol, Type>) -> Type {
+    let all = |types: &[Type]| types.iter().map(|ty| substitute(ty, instances)).collect();
+    match ty {
+        Type::Generic { name } => instances.get(name).cloned().unwrap_or_else(|| ty.clone()),
+        Type::Tuple(TupleType(types)) => Type::Tuple(TupleType(all(types))),
+        Type::List(element) => Type::List(Box::new(substitute(element, instances))),
+        Type::Fn { arguments, return_type } => Type::Fn {
+            arguments: all(arguments),
+            return_type: Box::new(substitute(return_type, instances)),
+        },
+        Type::Applied { name, arguments } => Type::Applied { name: *name, arguments: all(arguments).into() },
+        _ => ty.clone(),
+    }
+}
+
+// Whether any of `generics` show up in `ty`.
+fn mentions(ty: &Type, generics: &[Symbol]) -> bool {
+    match ty {
+        Type::Generic { name } => generics.contains(name),
+        Type::Tuple(TupleType(types)) => types.iter().any(|ty| mentions(ty, generics)),
+        Type::Applied { arguments, .. } => arguments.iter().any(|ty| mentions(ty, generics)),
+        Type::List(element) => mentions(element, generics),
+        Type::Fn { arguments, return_type } => {
+            arguments.iter().any(|ty| mentions(ty, generics)) || mentions(return_type, generics)
+        }
+        _ => false,
+    }
+}
+
+fn expect_type(expected: &Type, found: Type) -> TypeResult<Type> {
+    if *expected != found {
+        return Err(TypeErrorKind::Mismatch { expected: expected.clone(), found }.into());
+    }
+    Ok(found)
+}
+
+// The largest value a literal of the type can have.
+fn int_max(sign: bool, kind: IntKind, negated: bool) -> u128 {
+    let bits = kind.bits();
+    match sign {
+        true => (1 << (bits - 1)) - u128::from(!negated),
+        false => (1 << bits) - 1,
+    }
+}
+
+fn is_int(ty: &Type) -> bool {
+    matches!(ty, Type::Int { .. })
+}
+
+fn is_number(ty: &Type) -> bool {
+    matches!(ty, Type::Int { .. } | Type::Float { .. })
+}
+
+// Integer and float literals (with any amount of leading signs) take whatever type is around them.
+fn is_untyped_literal(exprs: &Exprs, expr: ExprId) -> bool {
+    match &exprs[expr] {
+        Expression::Literal(literal) => matches!(literal.kind, LitKind::Int { ty: None, .. } | LitKind::Float { ty: None, .. }),
+        Expression::Unary(unary) => is_untyped_literal(exprs, unary.rhs),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub kind: TypeErrorKind,
+    // The innermost statement or expression the error came from.
+    pub span: Option<Span>,
+    // Boxed so that errors stay small, since most don't have one.
+    pub fix: Option<Box<Fix>>,
+}
+
+impl TypeError {
+    fn or_span(self, span: Span) -> Self {
+        Self { span: self.span.or(Some(span)), ..self }
+    }
+
+    fn with_fix(self, fix: Option<Fix>) -> Self {
+        Self { fix: fix.map(Box::new), ..self }
+    }
+}
+
+impl From<TypeErrorKind> for TypeError {
+    fn from(kind: TypeErrorKind) -> Self {
+        Self { kind, span: None, fix: None }
+    }
+}
+
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeErrorKind {
+    Mismatch { expected: Type, found: Type },
+    UnknownIdentifier { name: Symbol },
+    UnknownType { name: Symbol },
+    NotCallable { found: Type },
+    // An integer literal that's too large for its type, with the `-` before it if there is one.
+    LiteralOutOfRange { literal: String, ty: Type },
+    // The right-hand side of a `|>`.
+    NotPipeable { found: Type },
+    ArgumentCount { expected: usize, found: usize },
+    UnknownTrait { name: Symbol },
+    UnknownMethod { ty: Type, name: Symbol },
+    MissingMethod { trait_name: Symbol, name: Symbol },
+    NotATraitMethod { trait_name: Symbol, name: Symbol },
+    DuplicateMethod { ty: Type, name: Symbol },
+    UnknownField { ty: Type, name: Symbol },
+    MissingField { ty: Type, name: Symbol },
+    // A field given twice in the same struct literal.
+    DuplicateField { name: Symbol },
+    TypeArgumentCount { name: Symbol, expected: usize, found: usize },
+    CannotInferGeneric { name: Symbol },
+    // A generic function used as a value, without anything saying what `generic` should be.
+    GenericValue { name: Symbol, generic: Symbol },
+    UnaryOperand { op: UnaryOperator, found: Type },
+    BinaryOperands { op: BinaryOperator, lhs: Type, rhs: Type },
+    CannotInfer,
+    Unsupported { what: &'static str },
+}
+
+impl std::fmt::Display for TypeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeErrorKind::Mismatch { expected, found } => write!(f, "expected `{expected}`, found `{found}`"),
+            TypeErrorKind::UnknownIdentifier { name } => write!(f, "cannot find `{name}` in this scope"),
+            TypeErrorKind::UnknownType { name } => write!(f, "cannot find type `{name}`"),
+            TypeErrorKind::NotCallable { found } => write!(f, "`{found}` is not a function"),
+            TypeErrorKind::LiteralOutOfRange { literal, ty } => write!(f, "`{literal}` does not fit in `{ty}`"),
+            TypeErrorKind::NotPipeable { found } => {
+                write!(f, "cannot pipe into `{found}`, the right side of `|>` has to be a function or a call")
+            }
+            TypeErrorKind::ArgumentCount { expected, found } => {
+                write!(f, "expected {expected} argument(s), found {found}")
+            }
+            TypeErrorKind::UnknownTrait { name } => write!(f, "cannot find trait `{name}`"),
+            TypeErrorKind::UnknownMethod { ty, name } => write!(f, "no method named `{name}` found for `{ty}`"),
+            TypeErrorKind::MissingMethod { trait_name, name } => {
+                write!(f, "missing `{name}` in the implementation of `{trait_name}`")
+            }
+            TypeErrorKind::NotATraitMethod { trait_name, name } => {
+                write!(f, "`{name}` is not a method of `{trait_name}`")
+            }
+            TypeErrorKind::DuplicateMethod { ty, name } => {
+                write!(f, "`{ty}` already has a method named `{name}`")
+            }
+            TypeErrorKind::UnknownField { ty, name } => write!(f, "no field named `{name}` found for `{ty}`"),
+            TypeErrorKind::MissingField { ty, name } => write!(f, "missing field `{name}` in `{ty}`"),
+            TypeErrorKind::DuplicateField { name } => write!(f, "field `{name}` is given more than once"),
+            TypeErrorKind::TypeArgumentCount { name, expected, found } => {
+                write!(f, "expected {expected} type argument(s) for `{name}`, found {found}")
+            }
+            TypeErrorKind::CannotInferGeneric { name } => write!(f, "cannot infer what type `{name}` is here"),
+            TypeErrorKind::GenericValue { name, generic } => {
+                write!(f, "cannot infer what type `{generic}` is for `{name}`, it needs a type annotation to be used as a value")
+            }
+            TypeErrorKind::UnaryOperand { op, found } => write!(f, "cannot apply `{op}` to `{found}`"),
+            TypeErrorKind::BinaryOperands { op, lhs, rhs } => write!(f, "cannot apply `{op}` to `{lhs}` and `{rhs}`"),
+            TypeErrorKind::CannotInfer => write!(f, "cannot infer the type of an empty list"),
+            TypeErrorKind::Unsupported { what } => write!(f, "{what} is not supported yet"),
+        }
+    }
+}
+
+pub type TypeResult<T> = Result<T, TypeError>;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,265 @@
+use super::*;
+use crate::parse::Parser;
+
+fn check(s: &str, expected: Type) {
+    let (expr, mut exprs) = Parser::parse_expression(s).unwrap();
+    assert_eq!(TypeChecker::new().check_expr(&mut exprs, expr, None), Ok(expected));
+}
+
+fn check_err(s: &str) {
+    let (mut tree, errors) = Parser::parse_interactive(s);
+    assert!(errors.is_empty());
+    assert!(TypeChecker::new().check_tree(&mut tree).is_err());
+}
+
+#[test]
+fn literal_types() {
+    check(r#""{1} {"a"}""#, Type::Str);
+    check("true", Type::Bool);
+    check("1", DEFAULT_INT);
+    check("1.5", DEFAULT_FLOAT);
+    check("(1, true)", Type::Tuple(TupleType(vec![DEFAULT_INT, Type::Bool])));
+    check("[1, 2, 3]", Type::List(Box::new(DEFAULT_INT)));
+}
+
+#[test]
+fn operator_types() {
+    check("1 + 2 * 3", DEFAULT_INT);
+    check("1 < 2 && !false", Type::Bool);
+    check("-1.5 / 2.", DEFAULT_FLOAT);
+    check("if 1 == 2 { 3 } else { 4 }", DEFAULT_INT);
+}
+
+#[test]
+fn declarations() {
+    let (mut tree, _) = Parser::parse_interactive("
+        let x: u8 = 3;
+        fn add(a: u8, b: u8) -> u8 { a + b }
+        add(x, 4)
+    ");
+    let types = TypeChecker::new().check_tree(&mut tree).unwrap();
+    assert_eq!(types.last(), Some(&Type::Int { sign: false, kind: IntKind::Bit8 }));
+}
+
+#[test]
+fn type_errors() {
+    check_err("1 + true");
+    check_err("let x: bool = 1;");
+    check_err("if 1 { 2 }");
+    check_err("fn f(a: i32) -> i32 { a } f(true)");
+    check_err("fn f() -> i32 { 1 } f(1)");
+    check_err("undefined");
+    check_err("fn f(p: Point) -> void {}");
+    check_err(r#""{1 + true}""#);
+}
+
+#[test]
+fn recording_keeps_going() {
+    let (mut tree, _) = Parser::parse_interactive("
+        fn a() -> i32 { let x: bool = 1; true }
+        fn b() -> i32 { a() + undefined }
+        let c = b();
+    ");
+    let mut checker = TypeChecker::recording();
+    assert!(checker.check_tree(&mut tree).is_ok());
+
+    let errors: Vec<String> = checker.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(errors, [
+        "expected `bool`, found `i32`",
+        "expected `i32`, found `bool`",
+        "cannot find `undefined` in this scope",
+    ]);
+    // What comes after the errors is still recorded.
+    assert!(checker.references.iter().any(|reference| reference.name == "c" && reference.ty == DEFAULT_INT));
+}
+
+#[test]
+fn literal_ranges() {
+    check("255u8", Type::Int { sign: false, kind: IntKind::Bit8 });
+    check("-128i8", Type::Int { sign: true, kind: IntKind::Bit8 });
+    check("0xffff_ffff_ffff_ffffu64", Type::Int { sign: false, kind: IntKind::Bit64 });
+    check("1.5f32 * 2.", Type::Float { kind: FloatKind::Bit32 });
+    check("2147483647", DEFAULT_INT);
+
+    let err = |s: &str| {
+        let (expr, mut exprs) = Parser::parse_expression(s).unwrap();
+        TypeChecker::new().check_expr(&mut exprs, expr, None).unwrap_err().to_string()
+    };
+    assert_eq!(err("256u8"), "`256` does not fit in `u8`");
+    assert_eq!(err("128i8"), "`128` does not fit in `i8`");
+    assert_eq!(err("-129i8"), "`-129` does not fit in `i8`");
+    assert_eq!(err("2147483648"), "`2147483648` does not fit in `i32`");
+    check_err("let x: u8 = 300;");
+    check_err("let x: u8 = 1i32;");
+    check_err("1u8 + 1i64");
+
+    // Unsigned integers can't be negated, not even literals.
+    assert_eq!(err("-1u8"), "cannot apply `-` to `u8`");
+    assert_eq!(err("-(1u64 + 2)"), "cannot apply `-` to `u64`");
+    check_err("let u: u8 = -1;");
+    check_err("fn neg(n: u32) -> u32 { -n }");
+    check("-(1i8)", Type::Int { sign: true, kind: IntKind::Bit8 });
+}
+
+#[test]
+fn generics() {
+    let (mut tree, _) = Parser::parse_interactive("
+        struct Pair<A, B> { first: A, second: B, }
+        fn id<T>(x: T) -> T { x }
+        fn both<T>(a: T, b: T) -> {T} { [a, b] }
+        fn apply<A, B>(f: fn(A) -> B, x: A) -> B { f(x) }
+        fn pair<A, B>(p: Pair<A, B>) -> Pair<A, B> { p }
+        let x: u8 = id(3);
+        let l = both(x, 4);
+        apply(\\(n: u8) -> bool { n == x }, id(x))
+    ");
+    let types = TypeChecker::new().check_tree(&mut tree).unwrap();
+    assert_eq!(types.last(), Some(&Type::Bool));
+
+    check_err("fn id<T>(x: T) -> T { x } let x: bool = id(1);");
+    check_err("fn both<T>(a: T, b: T) -> T { a } both(1, true)");
+    check_err("fn add<T>(a: T, b: T) -> T { a + b }");
+    check_err("struct Pair<A, B> { a: A, b: B, } fn f(p: Pair<i32>) -> void {}");
+    check_err("struct Pair<A, B> { a: A, b: B, } fn f(p: Pair) -> void {}");
+    check_err("fn make<T>() -> {T} { [] } make();");
+
+    // A generic function used as a value gets its type parameters from the type it's expected to be.
+    let (mut tree, _) = Parser::parse_interactive("
+        fn id<T>(x: T) -> T { x }
+        let f: fn(i32) -> i32 = id;
+        f(2)
+    ");
+    let types = TypeChecker::new().check_tree(&mut tree).unwrap();
+    assert_eq!(types.last(), Some(&DEFAULT_INT));
+
+    let (mut tree, _) = Parser::parse_interactive("fn id<T>(x: T) -> T { x } let f = id; f(2)");
+    let err = TypeChecker::new().check_tree(&mut tree).unwrap_err();
+    assert_eq!(err.to_string(), "cannot infer what type `T` is for `id`, it needs a type annotation to be used as a value");
+}
+
+#[test]
+fn pipes() {
+    let (mut tree, _) = Parser::parse_interactive("
+        fn double(n: u8) -> u8 { n * 2 }
+        fn add(a: u8, b: u8) -> u8 { a + b }
+        fn id<T>(x: T) -> T { x }
+        let n = 3 |> double |> add(1);
+        let same = [n] |> id;
+        (n, same, 1 + 2 |> add(n) == 6)
+    ");
+    let types = TypeChecker::new().check_tree(&mut tree).unwrap();
+    let u8 = Type::Int { sign: false, kind: IntKind::Bit8 };
+    let expected = Type::Tuple(TupleType(vec![u8.clone(), Type::List(Box::new(u8)), Type::Bool]));
+    assert_eq!(types.last(), Some(&expected));
+
+    check_err("fn f(a: i32) -> i32 { a } true |> f");
+    check_err("fn f(a: i32, b: i32) -> i32 { a } 1 |> f");
+    check_err("fn f(a: i32) -> i32 { a } 1 |> f(2)");
+
+    let (mut tree, _) = Parser::parse_interactive("let x = 1; 2 |> x");
+    let err = TypeChecker::new().check_tree(&mut tree).unwrap_err();
+    assert_eq!(err.kind, TypeErrorKind::NotPipeable { found: DEFAULT_INT });
+    assert_eq!(err.span, Some(Span::new(16, 17)));
+}
+
+#[test]
+fn structs_and_enums() {
+    let (mut tree, _) = Parser::parse_interactive("
+        struct Point { x: i32, y: i32 }
+        struct Pair<A, B> { first: A, second: B }
+        enum Light { Red Green }
+        enum Maybe<T> { Nothing }
+        let pair = Pair { first: Point { x: 1, y: 2 }, second: Light::Red };
+        let nothing: Maybe<u8> = Maybe::Nothing;
+        (pair.first.y, pair.second == Light::Green)
+    ");
+    let types = TypeChecker::new().check_tree(&mut tree);
+    assert_eq!(types.unwrap().last(), Some(&Type::Tuple(TupleType(vec![DEFAULT_INT, Type::Bool]))));
+
+    check_err("struct Point { x: i32 } Point { x: true }");
+    check_err("struct Point { x: i32 } Point { x: 1, y: 2 }");
+    check_err("struct Point { x: i32, y: i32 } Point { x: 1 }");
+    check_err("struct Point { x: i32 } Point { x: 1, x: 2 }");
+    check_err("struct Point { x: i32 } Point { x: 1 }.z");
+    check_err("Point { x: 1 }");
+    check_err("(1, 2).x");
+    check_err("enum Light { Red } Light::Blue");
+    check_err("enum Maybe<T> { Nothing } let a = Maybe::Nothing;");
+    check_err("struct Point { x: i32 } enum Light { Red } Point { x: 1 } == Light::Red");
+}
+
+#[test]
+fn methods() {
+    let (mut tree, _) = Parser::parse_interactive("
+        struct Point { x: i32, y: i32, }
+        trait Shape {
+            fn area(self) -> i32;
+            fn double(self) -> i32 { self.area() * 2 }
+        }
+        impl Shape for Point {
+            fn area(self) -> i32 { 1 }
+        }
+        impl<T> {T} {
+            fn first(self, fallback: T) -> T { fallback }
+        }
+        impl i32 {
+            fn is_zero(self) -> bool { self == 0 }
+        }
+        let p = Point { y: 2, x: 1 };
+        let l: {u8} = [1, 2];
+        let n: u8 = l.first(3);
+        p.double() + 3 > 4 && (5).is_zero()
+    ");
+    let types = TypeChecker::new().check_tree(&mut tree);
+    assert_eq!(types.unwrap().last(), Some(&Type::Bool));
+
+    check_err("impl i32 { fn f(self) -> i32 { self } } true.f()");
+    check_err("impl i32 { fn f(self) -> i32 { self } } 1.f(2)");
+    check_err("impl i32 { fn f() -> i32 { 1 } }");
+    check_err("impl i32 { fn f(self) -> i32 { self } } impl i32 { fn f(self) -> i32 { 1 } }");
+    check_err("impl<T> {T} { fn f(self) -> i32 { 1 } } impl {i32} { fn f(self) -> i32 { 2 } }");
+    check_err("impl Shape for i32 {}");
+    check_err("trait Shape { fn area(self) -> i32; } impl Shape for i32 {}");
+    check_err("trait Shape { fn area(self) -> i32; } impl Shape for i32 { fn area(self) -> bool { true } }");
+    check_err("trait Shape {} impl Shape for i32 { fn area(self) -> i32 { 1 } }");
+    check_err("impl<T> T { fn f(self) -> T { self } }");
+
+    // Every integer, tuple, and list type gets its own methods.
+    let (mut tree, _) = Parser::parse_interactive("
+        trait Show { fn show(self) -> i32; }
+        impl Show for i32 { fn show(self) -> i32 { 1 } }
+        impl Show for u8 { fn show(self) -> i32 { 2 } }
+        impl Show for (i32, i32) { fn show(self) -> i32 { 3 } }
+        impl Show for (bool, bool) { fn show(self) -> i32 { 4 } }
+        impl Show for {i32} { fn show(self) -> i32 { 5 } }
+        impl Show for {{i32}} { fn show(self) -> i32 { 6 } }
+    ");
+    assert!(TypeChecker::new().check_tree(&mut tree).is_ok());
+}
+
+#[test]
+fn block_functions() {
+    // They'd have to stay inside the block, which isn't supported yet, so they aren't declared at all.
+    let (mut tree, _) = Parser::parse_interactive("{ fn inner() -> i32 { 1 } }; inner()");
+    let err = TypeChecker::new().check_tree(&mut tree).unwrap_err();
+    assert_eq!(err.to_string(), "a function inside a block is not supported yet");
+    check_err("fn outer() -> i32 { fn inner() -> i32 { 1 } inner() }");
+    check_err("let f = \\() -> i32 { fn inner() -> i32 { 1 } inner() };");
+}
+
+#[test]
+fn suggestions() {
+    let suggestion = |s: &str| {
+        let (mut tree, _) = Parser::parse_interactive(s);
+        let err = TypeChecker::new().check_tree(&mut tree).unwrap_err();
+        err.fix.map(|fix| (fix.message, fix.edit.span))
+    };
+    assert_eq!(suggestion("let count = 1; coutn"), Some(("did you mean `count`?".into(), Span::new(15, 20))));
+    assert_eq!(suggestion("fn double(x: i32) -> i32 { x * 2 } duble(1)"), Some(("did you mean `double`?".into(), Span::new(35, 40))));
+    assert_eq!(suggestion("prnt(1)"), Some(("did you mean `print`?".into(), Span::new(0, 4))));
+    assert_eq!(suggestion("
+        impl bool { fn length(self) -> i32 { 1 } }
+        true.lenght()
+    "), Some(("did you mean `length`?".into(), Span::new(65, 71))));
+    assert_eq!(suggestion("zzz"), None);
+}